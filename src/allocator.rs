@@ -0,0 +1,237 @@
+// Purpose: Stable stand-in for the unstable `core::alloc::Allocator`, so every
+// collection and smart pointer can be backed by something other than the global heap.
+
+use alloc::alloc::{alloc, dealloc, realloc};
+use core::{alloc::Layout, fmt, ptr, ptr::NonNull};
+
+/// The allocator could not satisfy a request.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct AllocError;
+
+impl fmt::Display for AllocError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("memory allocation failed")
+    }
+}
+
+impl core::error::Error for AllocError {}
+
+/// A source of memory for the collections in this crate.
+///
+/// Every type takes an `A: Allocator = Global` parameter and has `*_in`
+/// constructors, e.g. `MyVec::new_in(alloc)` or `MyRc::new_in(value, alloc)`.
+///
+/// # Safety
+///
+/// Implementors must hand out blocks that are valid for `layout.size()`
+/// bytes, aligned to `layout.align()`, and stay valid until they are passed
+/// back to `deallocate` (or `grow`/`shrink`) on this allocator or a clone of
+/// it. Zero-sized requests must succeed with a well-aligned dangling pointer.
+pub unsafe trait Allocator {
+    fn allocate(&self, layout: Layout) -> Result<NonNull<u8>, AllocError>;
+
+    /// # Safety
+    ///
+    /// `ptr` must have been returned by this allocator for `layout`.
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout);
+
+    /// Moves a block to a bigger layout, keeping its first `old_layout.size()` bytes.
+    ///
+    /// # Safety
+    ///
+    /// `ptr` must have been returned by this allocator for `old_layout`, and
+    /// `new_layout.size() >= old_layout.size()`. On success the old block is gone.
+    unsafe fn grow(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<u8>, AllocError> {
+        unsafe { reallocate(self, ptr, old_layout, new_layout, old_layout.size()) }
+    }
+
+    /// Moves a block to a smaller layout, keeping its first `new_layout.size()` bytes.
+    ///
+    /// # Safety
+    ///
+    /// `ptr` must have been returned by this allocator for `old_layout`, and
+    /// `new_layout.size() <= old_layout.size()`. On success the old block is gone.
+    unsafe fn shrink(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<u8>, AllocError> {
+        unsafe { reallocate(self, ptr, old_layout, new_layout, new_layout.size()) }
+    }
+}
+
+// `#[track_caller]` lets wrappers like `Tracking` see through the reference.
+unsafe impl<A: Allocator + ?Sized> Allocator for &A {
+    #[track_caller]
+    fn allocate(&self, layout: Layout) -> Result<NonNull<u8>, AllocError> {
+        (**self).allocate(layout)
+    }
+
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+        unsafe { (**self).deallocate(ptr, layout) }
+    }
+
+    #[track_caller]
+    unsafe fn grow(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<u8>, AllocError> {
+        unsafe { (**self).grow(ptr, old_layout, new_layout) }
+    }
+
+    unsafe fn shrink(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<u8>, AllocError> {
+        unsafe { (**self).shrink(ptr, old_layout, new_layout) }
+    }
+}
+
+/// The global heap, i.e. whatever `#[global_allocator]` is registered.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Global;
+
+unsafe impl Allocator for Global {
+    fn allocate(&self, layout: Layout) -> Result<NonNull<u8>, AllocError> {
+        if layout.size() == 0 {
+            return Ok(dangling(layout));
+        }
+        NonNull::new(unsafe { alloc(layout) }).ok_or(AllocError)
+    }
+
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+        if layout.size() != 0 {
+            unsafe { dealloc(ptr.as_ptr(), layout) }
+        }
+    }
+
+    unsafe fn grow(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<u8>, AllocError> {
+        unsafe { self.realloc(ptr, old_layout, new_layout) }
+    }
+
+    unsafe fn shrink(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<u8>, AllocError> {
+        unsafe { self.realloc(ptr, old_layout, new_layout) }
+    }
+}
+
+impl Global {
+    // `alloc::realloc` can only keep the alignment, and knows nothing about
+    // zero-sized blocks, so anything else goes through the generic fallback.
+    unsafe fn realloc(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<u8>, AllocError> {
+        let keep = old_layout.size().min(new_layout.size());
+        if old_layout.align() != new_layout.align()
+            || old_layout.size() == 0
+            || new_layout.size() == 0
+        {
+            return unsafe { reallocate(self, ptr, old_layout, new_layout, keep) };
+        }
+
+        let raw = unsafe { realloc(ptr.as_ptr(), old_layout, new_layout.size()) };
+        NonNull::new(raw).ok_or(AllocError)
+    }
+}
+
+// Allocate-copy-free fallback for allocators that can't resize in place.
+pub(crate) unsafe fn reallocate<A: Allocator + ?Sized>(
+    alloc: &A,
+    ptr: NonNull<u8>,
+    old_layout: Layout,
+    new_layout: Layout,
+    keep: usize,
+) -> Result<NonNull<u8>, AllocError> {
+    let new_ptr = alloc.allocate(new_layout)?;
+    unsafe {
+        ptr::copy_nonoverlapping(ptr.as_ptr(), new_ptr.as_ptr(), keep);
+        alloc.deallocate(ptr, old_layout);
+    }
+    Ok(new_ptr)
+}
+
+/// A non-null pointer aligned for `layout`, for zero-sized allocations.
+pub(crate) fn dangling(layout: Layout) -> NonNull<u8> {
+    unsafe { NonNull::new_unchecked(ptr::without_provenance_mut(layout.align())) }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_global_round_trip() {
+        let layout = Layout::array::<u32>(4).unwrap();
+        let ptr = Global.allocate(layout).unwrap();
+
+        unsafe {
+            ptr.as_ptr().cast::<u32>().write(7);
+
+            let bigger = Layout::array::<u32>(16).unwrap();
+            let ptr = Global.grow(ptr, layout, bigger).unwrap();
+            assert_eq!(ptr.as_ptr().cast::<u32>().read(), 7);
+
+            let ptr = Global.shrink(ptr, bigger, layout).unwrap();
+            assert_eq!(ptr.as_ptr().cast::<u32>().read(), 7);
+
+            Global.deallocate(ptr, layout);
+        }
+    }
+
+    #[test]
+    fn test_zero_sized_is_dangling_and_aligned() {
+        let layout = Layout::from_size_align(0, 64).unwrap();
+        let ptr = Global.allocate(layout).unwrap();
+        assert_eq!(ptr.as_ptr() as usize % 64, 0);
+        unsafe { Global.deallocate(ptr, layout) };
+    }
+
+    #[test]
+    fn test_default_grow_copies() {
+        // Only implements the required methods, so grow/shrink use the fallback
+        struct Plain;
+        unsafe impl Allocator for Plain {
+            fn allocate(&self, layout: Layout) -> Result<NonNull<u8>, AllocError> {
+                Global.allocate(layout)
+            }
+            unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+                unsafe { Global.deallocate(ptr, layout) }
+            }
+        }
+
+        let small = Layout::array::<u8>(2).unwrap();
+        let big = Layout::array::<u8>(8).unwrap();
+        unsafe {
+            let ptr = <&Plain as Allocator>::allocate(&&Plain, small).unwrap();
+            ptr.as_ptr().copy_from([1u8, 2].as_ptr(), 2);
+
+            let ptr = Plain.grow(ptr, small, big).unwrap();
+            assert_eq!(*ptr.as_ptr().add(1), 2);
+            let ptr = Plain.shrink(ptr, big, small).unwrap();
+            assert_eq!(*ptr.as_ptr(), 1);
+            Plain.deallocate(ptr, small);
+        }
+    }
+}
@@ -0,0 +1,241 @@
+// Purpose: Typed arena: values of one type that are allocated one by one and dropped together.
+
+use core::{
+    alloc::Layout,
+    cell::RefCell,
+    fmt,
+    ptr::{self, NonNull},
+};
+
+use crate::{
+    allocator::{AllocError, Allocator, Global},
+    bump::Bump,
+    my_vec::MyVec,
+    raw_vec::RawVec,
+};
+
+// Size of the first chunk of values, later chunks double.
+const FIRST_CHUNK_BYTES: usize = 1024;
+
+/// Hands out `&mut T`s that all live as long as the arena, and drops the
+/// values when the arena is dropped or reset.
+///
+/// It also implements [`Allocator`] through an internal [`Bump`], so the
+/// collections that hold references to its values can live in it too.
+pub struct Arena<T> {
+    chunks: RefCell<MyVec<ArenaChunk<T>>>,
+    bump: Bump,
+}
+
+// Never grown once created, so the values in it never move.
+struct ArenaChunk<T> {
+    buf: RawVec<T>,
+    len: usize,
+}
+
+impl<T> ArenaChunk<T> {
+    fn with_capacity(cap: usize) -> Self {
+        Self {
+            buf: RawVec::with_capacity_in(cap, Global),
+            len: 0,
+        }
+    }
+
+    fn is_full(&self) -> bool {
+        self.len == self.buf.cap
+    }
+
+    // Raw pointers only: references to earlier slots are out there.
+    fn push(&mut self, value: T) -> NonNull<T> {
+        debug_assert!(!self.is_full());
+        unsafe {
+            let slot = self.buf.ptr.as_ptr().add(self.len).cast::<T>();
+            slot.write(value);
+            self.len += 1;
+            NonNull::new_unchecked(slot)
+        }
+    }
+}
+
+impl<T> Drop for ArenaChunk<T> {
+    fn drop(&mut self) {
+        let values = ptr::slice_from_raw_parts_mut(self.buf.ptr.as_ptr().cast::<T>(), self.len);
+        unsafe { ptr::drop_in_place(values) }
+    }
+}
+
+impl<T> Arena<T> {
+    pub fn new() -> Self {
+        Self::with_capacity((FIRST_CHUNK_BYTES / size_of::<T>().max(1)).max(1))
+    }
+
+    /// Creates an arena whose first chunk holds `cap` values.
+    pub fn with_capacity(cap: usize) -> Self {
+        let mut chunks = MyVec::new();
+        chunks.push(ArenaChunk::with_capacity(cap.max(1)));
+        Self {
+            chunks: RefCell::new(chunks),
+            bump: Bump::new(),
+        }
+    }
+
+    /// Moves `value` into the arena and returns a reference that lives as
+    /// long as the arena does.
+    #[allow(clippy::mut_from_ref)]
+    pub fn alloc(&self, value: T) -> &mut T {
+        let mut chunks = self.chunks.borrow_mut();
+        let last = chunks.get_len() - 1;
+        if chunks[last].is_full() {
+            let cap = chunks[last].buf.cap.saturating_mul(2);
+            chunks.push(ArenaChunk::with_capacity(cap));
+        }
+        let last = chunks.get_len() - 1;
+        let slot = chunks[last].push(value);
+        unsafe { &mut *slot.as_ptr() }
+    }
+
+    /// Number of values currently in the arena.
+    pub fn len(&self) -> usize {
+        let chunks = self.chunks.borrow();
+        chunks.as_slice().iter().map(|chunk| chunk.len).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Drops every value and resets the bump memory, keeping the newest
+    /// chunk of each around for reuse.
+    pub fn reset(&mut self) {
+        let chunks = self.chunks.get_mut();
+        while chunks.get_len() > 1 {
+            chunks.remove(0);
+        }
+        let newest = &mut chunks[0];
+        let values = ptr::slice_from_raw_parts_mut(newest.buf.ptr.as_ptr().cast::<T>(), newest.len);
+        newest.len = 0;
+        unsafe { ptr::drop_in_place(values) }
+
+        self.bump.reset();
+    }
+}
+
+unsafe impl<T> Allocator for Arena<T> {
+    fn allocate(&self, layout: Layout) -> Result<NonNull<u8>, AllocError> {
+        self.bump.allocate(layout)
+    }
+
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+        unsafe { self.bump.deallocate(ptr, layout) }
+    }
+
+    unsafe fn grow(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<u8>, AllocError> {
+        unsafe { self.bump.grow(ptr, old_layout, new_layout) }
+    }
+
+    unsafe fn shrink(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<u8>, AllocError> {
+        unsafe { self.bump.shrink(ptr, old_layout, new_layout) }
+    }
+}
+
+impl<T> Default for Arena<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> fmt::Debug for Arena<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Arena")
+            .field("len", &self.len())
+            .field("bump", &self.bump)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+
+    use super::*;
+
+    struct DropCounter<'a>(&'a Cell<usize>);
+
+    impl Drop for DropCounter<'_> {
+        fn drop(&mut self) {
+            self.0.set(self.0.get() + 1);
+        }
+    }
+
+    #[test]
+    fn test_references_stay_valid_across_chunks() {
+        let arena = Arena::with_capacity(2);
+        let refs: Vec<&mut usize> = (0..100).map(|i| arena.alloc(i)).collect();
+        assert_eq!(arena.chunks.borrow().get_len(), 6);
+        for (i, r) in refs.into_iter().enumerate() {
+            assert_eq!(*r, i);
+            *r += 1;
+        }
+        assert_eq!(arena.len(), 100);
+    }
+
+    #[test]
+    fn test_drops_values_once() {
+        let drops = Cell::new(0);
+        {
+            let arena = Arena::with_capacity(3);
+            for _ in 0..10 {
+                arena.alloc(DropCounter(&drops));
+            }
+            assert_eq!(drops.get(), 0);
+        }
+        assert_eq!(drops.get(), 10);
+    }
+
+    #[test]
+    fn test_reset_drops_and_reuses() {
+        let drops = Cell::new(0);
+        let mut arena = Arena::with_capacity(1);
+        for _ in 0..5 {
+            arena.alloc(DropCounter(&drops));
+        }
+        arena.reset();
+        assert_eq!(drops.get(), 5);
+        assert!(arena.is_empty());
+        assert_eq!(arena.chunks.borrow().get_len(), 1);
+
+        arena.alloc(DropCounter(&drops));
+        drop(arena);
+        assert_eq!(drops.get(), 6);
+    }
+
+    #[test]
+    fn test_zst() {
+        let arena = Arena::new();
+        for _ in 0..1000 {
+            arena.alloc(());
+        }
+        assert_eq!(arena.len(), 1000);
+        assert_eq!(arena.chunks.borrow().get_len(), 1);
+    }
+
+    #[test]
+    fn test_backs_a_vec() {
+        let arena: Arena<String> = Arena::new();
+        let mut names = MyVec::new_in(&arena);
+        for name in ["a", "b", "c"] {
+            names.push(arena.alloc(name.to_string()).as_str());
+        }
+        assert_eq!(names.as_slice(), ["a", "b", "c"]);
+    }
+}
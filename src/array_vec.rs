@@ -0,0 +1,711 @@
+// Purpose: A fixed-capacity vector stored entirely inline, for code that
+// must never allocate.
+
+use core::{
+    borrow::{Borrow, BorrowMut},
+    cmp::Ordering,
+    error::Error,
+    fmt,
+    hash::{Hash, Hasher},
+    iter::FusedIterator,
+    mem::{ManuallyDrop, MaybeUninit},
+    ops::{Deref, DerefMut, Index, IndexMut, RangeBounds},
+    ptr::{self, NonNull},
+    slice::{self, SliceIndex},
+};
+
+use crate::drain::{self, Drain, ExtractIf};
+
+/*
+ArrayVec<T, N>:
+
+- The first `len` slots of `[MaybeUninit<T>; N]` are initialised; there
+  is no allocator and no way to grow.
+- The `try_*` methods hand the element back in a `CapacityError` when the
+  vector is full. `push`/`insert` panic instead, like indexing out of bounds.
+- Range removal, retain and dedup go through the same drain.rs code as
+  MyVec, pointed at the inline array.
+*/
+
+pub struct ArrayVec<T, const N: usize> {
+    buf: [MaybeUninit<T>; N],
+    len: usize,
+}
+
+/// The vector was full. Holds the element that didn't fit.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct CapacityError<T = ()> {
+    element: T,
+}
+
+impl<T> CapacityError<T> {
+    pub const fn new(element: T) -> Self {
+        CapacityError { element }
+    }
+
+    /// The element that couldn't be added.
+    pub fn element(self) -> T {
+        self.element
+    }
+
+    /// Drops the element, keeping just the error.
+    pub fn simplify(self) -> CapacityError {
+        CapacityError { element: () }
+    }
+}
+
+impl<T> fmt::Debug for CapacityError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("CapacityError: insufficient capacity")
+    }
+}
+
+impl<T> fmt::Display for CapacityError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("insufficient capacity")
+    }
+}
+
+impl<T> Error for CapacityError<T> {}
+
+impl<T, const N: usize> ArrayVec<T, N> {
+    pub const fn new() -> Self {
+        ArrayVec {
+            buf: [const { MaybeUninit::uninit() }; N],
+            len: 0,
+        }
+    }
+
+    /// # Panics
+    ///
+    /// If the vector is full.
+    #[track_caller]
+    pub fn push(&mut self, value: T) {
+        if self.try_push(value).is_err() {
+            panic!("ArrayVec::push: capacity {N} exceeded");
+        }
+    }
+
+    /// Pushes `value`, or hands it back if the vector is full.
+    pub fn try_push(&mut self, value: T) -> Result<(), CapacityError<T>> {
+        if self.len == N {
+            return Err(CapacityError::new(value));
+        }
+
+        unsafe { self.push_unchecked(value) };
+        Ok(())
+    }
+
+    /// # Safety
+    ///
+    /// The vector must not be full.
+    pub unsafe fn push_unchecked(&mut self, value: T) {
+        debug_assert!(self.len < N);
+        unsafe { self.as_mut_ptr().add(self.len).write(value) };
+        self.len += 1;
+    }
+
+    /// # Panics
+    ///
+    /// If `index > len` or the vector is full.
+    #[track_caller]
+    pub fn insert(&mut self, index: usize, value: T) {
+        if self.try_insert(index, value).is_err() {
+            panic!("ArrayVec::insert: capacity {N} exceeded");
+        }
+    }
+
+    /// Inserts `value` at `index`, or hands it back if the vector is full.
+    ///
+    /// # Panics
+    ///
+    /// If `index > len`.
+    pub fn try_insert(&mut self, index: usize, value: T) -> Result<(), CapacityError<T>> {
+        assert!(index <= self.len);
+        if self.len == N {
+            return Err(CapacityError::new(value));
+        }
+
+        unsafe {
+            let at = self.as_mut_ptr().add(index);
+            ptr::copy(at, at.add(1), self.len - index);
+            at.write(value);
+        }
+        self.len += 1;
+        Ok(())
+    }
+
+    /// Clones every element of `other` onto the end, or does nothing if they
+    /// don't all fit.
+    pub fn try_extend_from_slice(&mut self, other: &[T]) -> Result<(), CapacityError>
+    where
+        T: Clone,
+    {
+        if other.len() > self.remaining_capacity() {
+            return Err(CapacityError::new(()));
+        }
+
+        for item in other {
+            unsafe { self.push_unchecked(item.clone()) };
+        }
+        Ok(())
+    }
+
+    pub fn pop(&mut self) -> Option<T> {
+        if self.len == 0 {
+            None
+        } else {
+            self.len -= 1;
+            Some(unsafe { self.as_ptr().add(self.len).read() })
+        }
+    }
+
+    pub fn remove(&mut self, index: usize) -> T {
+        assert!(index < self.len);
+        unsafe {
+            let at = self.as_mut_ptr().add(index);
+            let value = at.read();
+            ptr::copy(at.add(1), at, self.len - index - 1);
+            self.len -= 1;
+            value
+        }
+    }
+
+    /// Removes the element at `index`, filling the gap with the last one.
+    pub fn swap_remove(&mut self, index: usize) -> T {
+        let last = self.len - 1;
+        self.as_mut_slice().swap(index, last);
+        self.pop().unwrap()
+    }
+
+    pub fn get<I: SliceIndex<[T]>>(&self, index: I) -> Option<&I::Output> {
+        self.as_slice().get(index)
+    }
+
+    pub fn get_mut<I: SliceIndex<[T]>>(&mut self, index: I) -> Option<&mut I::Output> {
+        self.as_mut_slice().get_mut(index)
+    }
+
+    pub fn get_len(&self) -> usize {
+        self.len
+    }
+
+    pub const fn get_capacity(&self) -> usize {
+        N
+    }
+
+    pub fn remaining_capacity(&self) -> usize {
+        N - self.len
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.len == N
+    }
+
+    pub fn clear(&mut self) {
+        self.truncate(0);
+    }
+
+    /// Sets the length without touching the elements.
+    ///
+    /// # Safety
+    ///
+    /// `new_len` must be at most `N`, and the first `new_len` elements must
+    /// be initialised. Any elements cut off are leaked, not dropped.
+    pub unsafe fn set_len(&mut self, new_len: usize) {
+        debug_assert!(new_len <= N);
+        self.len = new_len;
+    }
+
+    /// The uninitialised slots after the last element.
+    pub fn spare_capacity_mut(&mut self) -> &mut [MaybeUninit<T>] {
+        &mut self.buf[self.len..]
+    }
+
+    fn as_ptr(&self) -> *const T {
+        self.buf.as_ptr().cast::<T>()
+    }
+
+    fn as_mut_ptr(&mut self) -> *mut T {
+        self.buf.as_mut_ptr().cast::<T>()
+    }
+
+    fn as_non_null(&mut self) -> NonNull<T> {
+        unsafe { NonNull::new_unchecked(self.as_mut_ptr()) }
+    }
+
+    pub fn as_slice(&self) -> &[T] {
+        unsafe { slice::from_raw_parts(self.as_ptr(), self.len) }
+    }
+
+    pub fn as_mut_slice(&mut self) -> &mut [T] {
+        unsafe { slice::from_raw_parts_mut(self.as_mut_ptr(), self.len) }
+    }
+
+    /// Moves the elements out into a plain array, if the vector is full.
+    pub fn into_inner(self) -> Result<[T; N], Self> {
+        if self.len < N {
+            return Err(self);
+        }
+
+        let this = ManuallyDrop::new(self);
+        Ok(unsafe { ptr::read(this.as_ptr().cast::<[T; N]>()) })
+    }
+
+    /// Removes `range` from the vector, yielding the removed elements.
+    pub fn drain<R: RangeBounds<usize>>(&mut self, range: R) -> Drain<'_, T> {
+        let range = drain::slice_range(range, self.len);
+        let buf = self.as_non_null();
+        unsafe { Drain::new(buf, &mut self.len, range) }
+    }
+
+    /// Removes and yields every element `pred` returns `true` for, keeping
+    /// the rest in order.
+    pub fn extract_if<F>(&mut self, pred: F) -> ExtractIf<'_, T, F>
+    where
+        F: FnMut(&mut T) -> bool,
+    {
+        let buf = self.as_non_null();
+        unsafe { ExtractIf::new(buf, &mut self.len, pred) }
+    }
+
+    /// Keeps only the elements `f` returns `true` for.
+    pub fn retain<F>(&mut self, mut f: F)
+    where
+        F: FnMut(&T) -> bool,
+    {
+        self.retain_mut(|elem| f(elem));
+    }
+
+    pub fn retain_mut<F>(&mut self, mut f: F)
+    where
+        F: FnMut(&mut T) -> bool,
+    {
+        self.extract_if(|elem| !f(elem)).for_each(drop);
+    }
+
+    /// Removes consecutive elements that map to the same key.
+    pub fn dedup_by_key<F, K>(&mut self, mut key: F)
+    where
+        F: FnMut(&mut T) -> K,
+        K: PartialEq,
+    {
+        self.dedup_by(|a, b| key(a) == key(b));
+    }
+
+    /// Removes consecutive elements `same_bucket(current, kept)` calls equal.
+    pub fn dedup_by<F>(&mut self, same_bucket: F)
+    where
+        F: FnMut(&mut T, &mut T) -> bool,
+    {
+        let buf = self.as_non_null();
+        unsafe { drain::dedup_by(buf, &mut self.len, same_bucket) }
+    }
+
+    /// Drops everything past the first `len` elements.
+    pub fn truncate(&mut self, len: usize) {
+        let buf = self.as_non_null();
+        unsafe { drain::truncate(buf, &mut self.len, len) }
+    }
+}
+
+impl<T, const N: usize> Default for ArrayVec<T, N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T, const N: usize> Drop for ArrayVec<T, N> {
+    fn drop(&mut self) {
+        unsafe { ptr::drop_in_place(self.as_mut_slice()) }
+    }
+}
+
+impl<T: Clone, const N: usize> Clone for ArrayVec<T, N> {
+    fn clone(&self) -> Self {
+        self.iter().cloned().collect()
+    }
+}
+
+impl<T: fmt::Debug, const N: usize> fmt::Debug for ArrayVec<T, N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self.as_slice(), f)
+    }
+}
+
+impl<T, const N: usize> Deref for ArrayVec<T, N> {
+    type Target = [T];
+
+    fn deref(&self) -> &[T] {
+        self.as_slice()
+    }
+}
+
+impl<T, const N: usize> DerefMut for ArrayVec<T, N> {
+    fn deref_mut(&mut self) -> &mut [T] {
+        self.as_mut_slice()
+    }
+}
+
+impl<T, I: SliceIndex<[T]>, const N: usize> Index<I> for ArrayVec<T, N> {
+    type Output = I::Output;
+
+    fn index(&self, index: I) -> &Self::Output {
+        Index::index(self.as_slice(), index)
+    }
+}
+
+impl<T, I: SliceIndex<[T]>, const N: usize> IndexMut<I> for ArrayVec<T, N> {
+    fn index_mut(&mut self, index: I) -> &mut Self::Output {
+        IndexMut::index_mut(self.as_mut_slice(), index)
+    }
+}
+
+impl<T, const N: usize> AsRef<[T]> for ArrayVec<T, N> {
+    fn as_ref(&self) -> &[T] {
+        self
+    }
+}
+
+impl<T, const N: usize> AsMut<[T]> for ArrayVec<T, N> {
+    fn as_mut(&mut self) -> &mut [T] {
+        self
+    }
+}
+
+impl<T, const N: usize> Borrow<[T]> for ArrayVec<T, N> {
+    fn borrow(&self) -> &[T] {
+        self
+    }
+}
+
+impl<T, const N: usize> BorrowMut<[T]> for ArrayVec<T, N> {
+    fn borrow_mut(&mut self) -> &mut [T] {
+        self
+    }
+}
+
+macro_rules! impl_slice_eq {
+    ([$($vars:tt)*] $lhs:ty, $rhs:ty) => {
+        impl<T, U, $($vars)*> PartialEq<$rhs> for $lhs
+        where
+            T: PartialEq<U>,
+        {
+            fn eq(&self, other: &$rhs) -> bool {
+                self[..] == other[..]
+            }
+        }
+    };
+}
+
+impl_slice_eq! { [const N1: usize, const N2: usize] ArrayVec<T, N1>, ArrayVec<U, N2> }
+impl_slice_eq! { [const N: usize] ArrayVec<T, N>, [U] }
+impl_slice_eq! { [const N: usize] ArrayVec<T, N>, &[U] }
+impl_slice_eq! { [const N: usize, const M: usize] ArrayVec<T, N>, [U; M] }
+impl_slice_eq! { [const N: usize, const M: usize] ArrayVec<T, N>, &[U; M] }
+
+impl<T: Eq, const N: usize> Eq for ArrayVec<T, N> {}
+
+impl<T: PartialOrd, const N: usize> PartialOrd for ArrayVec<T, N> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        PartialOrd::partial_cmp(&**self, &**other)
+    }
+}
+
+impl<T: Ord, const N: usize> Ord for ArrayVec<T, N> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        Ord::cmp(&**self, &**other)
+    }
+}
+
+impl<T: Hash, const N: usize> Hash for ArrayVec<T, N> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        Hash::hash(&**self, state)
+    }
+}
+
+/// # Panics
+///
+/// If the items don't all fit.
+impl<T, const N: usize> Extend<T> for ArrayVec<T, N> {
+    #[track_caller]
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for item in iter {
+            self.push(item);
+        }
+    }
+}
+
+impl<'a, T: Copy + 'a, const N: usize> Extend<&'a T> for ArrayVec<T, N> {
+    #[track_caller]
+    fn extend<I: IntoIterator<Item = &'a T>>(&mut self, iter: I) {
+        self.extend(iter.into_iter().copied());
+    }
+}
+
+/// # Panics
+///
+/// If the iterator yields more than `N` items.
+impl<T, const N: usize> FromIterator<T> for ArrayVec<T, N> {
+    #[track_caller]
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut vec = ArrayVec::new();
+        vec.extend(iter);
+        vec
+    }
+}
+
+impl<T, const N: usize> From<[T; N]> for ArrayVec<T, N> {
+    fn from(array: [T; N]) -> Self {
+        let array = ManuallyDrop::new(array);
+        let mut vec = ArrayVec::new();
+        unsafe {
+            ptr::copy_nonoverlapping(array.as_ptr(), vec.as_mut_ptr(), N);
+        }
+        vec.len = N;
+        vec
+    }
+}
+
+impl<T: Clone, const N: usize> TryFrom<&[T]> for ArrayVec<T, N> {
+    type Error = CapacityError;
+
+    fn try_from(slice: &[T]) -> Result<Self, CapacityError> {
+        let mut vec = ArrayVec::new();
+        vec.try_extend_from_slice(slice)?;
+        Ok(vec)
+    }
+}
+
+impl<'a, T, const N: usize> IntoIterator for &'a ArrayVec<T, N> {
+    type Item = &'a T;
+    type IntoIter = slice::Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, T, const N: usize> IntoIterator for &'a mut ArrayVec<T, N> {
+    type Item = &'a mut T;
+    type IntoIter = slice::IterMut<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+/// Owning iterator over an [`ArrayVec`]. Yields `[index, end)` of the
+/// array it carries along.
+pub struct IntoIter<T, const N: usize> {
+    vec: ArrayVec<T, N>,
+    index: usize,
+    end: usize,
+}
+
+impl<T, const N: usize> IntoIter<T, N> {
+    /// The elements that haven't been yielded yet.
+    pub fn as_slice(&self) -> &[T] {
+        unsafe { slice::from_raw_parts(self.vec.as_ptr().add(self.index), self.end - self.index) }
+    }
+
+    pub fn as_mut_slice(&mut self) -> &mut [T] {
+        unsafe {
+            slice::from_raw_parts_mut(self.vec.as_mut_ptr().add(self.index), self.end - self.index)
+        }
+    }
+}
+
+impl<T, const N: usize> Iterator for IntoIter<T, N> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        if self.index == self.end {
+            None
+        } else {
+            let item = unsafe { self.vec.as_ptr().add(self.index).read() };
+            self.index += 1;
+            Some(item)
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.end - self.index;
+        (remaining, Some(remaining))
+    }
+
+    fn count(self) -> usize {
+        self.end - self.index
+    }
+}
+
+impl<T, const N: usize> DoubleEndedIterator for IntoIter<T, N> {
+    fn next_back(&mut self) -> Option<T> {
+        if self.index == self.end {
+            None
+        } else {
+            self.end -= 1;
+            Some(unsafe { self.vec.as_ptr().add(self.end).read() })
+        }
+    }
+}
+
+impl<T, const N: usize> ExactSizeIterator for IntoIter<T, N> {}
+
+impl<T, const N: usize> FusedIterator for IntoIter<T, N> {}
+
+impl<T, const N: usize> Drop for IntoIter<T, N> {
+    fn drop(&mut self) {
+        unsafe { ptr::drop_in_place(self.as_mut_slice()) }
+    }
+}
+
+impl<T, const N: usize> IntoIterator for ArrayVec<T, N> {
+    type Item = T;
+    type IntoIter = IntoIter<T, N>;
+
+    fn into_iter(mut self) -> Self::IntoIter {
+        let end = self.len;
+        // The iterator owns the elements now
+        self.len = 0;
+        IntoIter {
+            vec: self,
+            index: 0,
+            end,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+
+    use super::*;
+
+    #[test]
+    fn test_try_push_hands_back_the_element() {
+        let mut vec: ArrayVec<String, 2> = ArrayVec::new();
+        vec.try_push("a".into()).unwrap();
+        vec.try_push("b".into()).unwrap();
+        assert!(vec.is_full());
+
+        let err = vec.try_push("c".into()).unwrap_err();
+        assert_eq!(err.to_string(), "insufficient capacity");
+        assert_eq!(err.element(), "c");
+        assert_eq!(vec, ["a", "b"]);
+    }
+
+    #[test]
+    fn test_try_insert() {
+        let mut vec: ArrayVec<i32, 3> = ArrayVec::from_iter([1, 3]);
+        vec.try_insert(1, 2).unwrap();
+        assert_eq!(vec, [1, 2, 3]);
+        assert_eq!(vec.try_insert(0, 0).unwrap_err().element(), 0);
+        assert_eq!(vec, [1, 2, 3]);
+
+        assert_eq!(vec.remove(0), 1);
+        assert_eq!(vec.swap_remove(0), 2);
+        assert_eq!(vec, [3]);
+    }
+
+    #[test]
+    #[should_panic(expected = "capacity 1 exceeded")]
+    fn test_push_panics_when_full() {
+        let mut vec: ArrayVec<u8, 1> = ArrayVec::new();
+        vec.push(1);
+        vec.push(2);
+    }
+
+    #[test]
+    fn test_drain_machinery() {
+        let mut vec: ArrayVec<i32, 10> = (0..10).collect();
+        let drained: Vec<_> = vec.drain(2..5).rev().collect();
+        assert_eq!(drained, [4, 3, 2]);
+        assert_eq!(vec, [0, 1, 5, 6, 7, 8, 9]);
+
+        let odd: Vec<_> = vec.extract_if(|x| *x % 2 == 1).collect();
+        assert_eq!(odd, [1, 5, 7, 9]);
+        vec.retain(|x| *x != 8);
+        assert_eq!(vec, [0, 6]);
+
+        vec.try_extend_from_slice(&[6, 6, 7]).unwrap();
+        vec.dedup_by_key(|x| *x);
+        assert_eq!(vec, [0, 6, 7]);
+        assert!(vec.try_extend_from_slice(&[0; 8]).is_err());
+        assert_eq!(vec.get_len(), 3);
+    }
+
+    #[test]
+    fn test_into_inner() {
+        let vec: ArrayVec<i32, 3> = ArrayVec::from([1, 2, 3]);
+        assert_eq!(vec.into_inner().unwrap(), [1, 2, 3]);
+
+        let partial: ArrayVec<i32, 3> = [1].into_iter().collect();
+        let partial = partial.into_inner().unwrap_err();
+        assert_eq!(partial, [1]);
+    }
+
+    #[test]
+    fn test_drops_every_element_once() {
+        struct Counted<'a>(&'a Cell<usize>);
+        impl Drop for Counted<'_> {
+            fn drop(&mut self) {
+                self.0.set(self.0.get() + 1);
+            }
+        }
+
+        let drops = Cell::new(0);
+        let mut vec: ArrayVec<Counted<'_>, 6> = ArrayVec::new();
+        for _ in 0..6 {
+            vec.push(Counted(&drops));
+        }
+        let rejected = vec.try_push(Counted(&drops)).unwrap_err();
+        drop(rejected);
+        assert_eq!(drops.get(), 1);
+
+        vec.truncate(4);
+        assert_eq!(drops.get(), 3);
+        let mut iter = vec.into_iter();
+        drop(iter.next_back());
+        drop(iter);
+        assert_eq!(drops.get(), 7);
+    }
+
+    #[test]
+    fn test_iterators() {
+        let mut vec: ArrayVec<i32, 4> = ArrayVec::from([1, 2, 3, 4]);
+        for x in &mut vec {
+            *x *= 2;
+        }
+        assert_eq!(
+            (&vec).into_iter().rev().copied().collect::<Vec<_>>(),
+            [8, 6, 4, 2]
+        );
+        let mut iter = vec.into_iter();
+        assert_eq!(iter.next(), Some(2));
+        assert_eq!(iter.as_slice(), [4, 6, 8]);
+        assert_eq!(iter.len(), 3);
+    }
+
+    #[test]
+    fn test_get_range() {
+        let mut vec: ArrayVec<i32, 4> = ArrayVec::from([1, 2, 3, 4]);
+        assert_eq!(vec.get(1), Some(&2));
+        assert_eq!(vec.get(1..3), Some(&[2, 3][..]));
+        assert_eq!(vec.get(..5), None);
+        vec.get_mut(2..).unwrap().fill(0);
+        assert_eq!(vec.as_slice(), [1, 2, 0, 0]);
+    }
+
+    #[test]
+    fn test_zero_capacity_and_zst() {
+        let mut empty: ArrayVec<i32, 0> = ArrayVec::new();
+        assert!(empty.is_full());
+        assert!(empty.try_push(1).is_err());
+
+        let mut units: ArrayVec<(), 3> = ArrayVec::new();
+        units.extend([(), (), ()]);
+        assert!(units.try_push(()).is_err());
+        assert_eq!(units.into_iter().count(), 3);
+    }
+}
@@ -0,0 +1,331 @@
+// Purpose: Bump allocator that hands out memory from a chain of chunks and frees it all at once.
+
+use alloc::alloc::handle_alloc_error;
+use core::{
+    alloc::Layout,
+    cell::Cell,
+    fmt,
+    ptr::{self, NonNull},
+};
+
+use crate::allocator::{self, AllocError, Allocator, Global};
+
+/*
+Bump:
+
+- Memory comes from chunks on the global heap. Every chunk starts with a
+  `ChunkHeader` pointing at the chunk before it, so the newest chunk is the
+  head of a singly linked list.
+- Allocating only moves `ptr` forward inside the newest chunk. When it runs
+  out, a new chunk at least twice the size of the last one is chained on.
+- `deallocate` only takes memory back if it was the most recent allocation,
+  which is also what lets a MyVec at the end of the chunk grow in place.
+- Everything else is released together by `reset` or Drop.
+*/
+
+const FIRST_CHUNK_SIZE: usize = 512;
+
+struct ChunkHeader {
+    prev: Option<NonNull<ChunkHeader>>,
+    layout: Layout,
+}
+
+pub struct Bump {
+    chunk: Cell<Option<NonNull<ChunkHeader>>>,
+    ptr: Cell<*mut u8>,
+    end: Cell<*mut u8>,
+}
+
+impl Bump {
+    /// Creates a bump allocator without allocating a chunk yet.
+    pub const fn new() -> Self {
+        Self {
+            chunk: Cell::new(None),
+            ptr: Cell::new(ptr::null_mut()),
+            end: Cell::new(ptr::null_mut()),
+        }
+    }
+
+    /// Creates a bump allocator whose first chunk has room for `bytes` bytes.
+    pub fn with_capacity(bytes: usize) -> Self {
+        let bump = Self::new();
+        if bytes > 0 {
+            let layout = Layout::from_size_align(bytes, 1).expect("capacity overflow");
+            if bump.new_chunk(layout).is_err() {
+                handle_alloc_error(layout);
+            }
+        }
+        bump
+    }
+
+    /// Moves `value` into the bump and returns a reference to it.
+    ///
+    /// The value is never dropped, only its memory is reclaimed by `reset`
+    /// or when the `Bump` itself goes away.
+    #[allow(clippy::mut_from_ref)]
+    pub fn alloc<T>(&self, value: T) -> &mut T {
+        let layout = Layout::new::<T>();
+        let ptr = self
+            .allocate(layout)
+            .unwrap_or_else(|_| handle_alloc_error(layout))
+            .cast::<T>();
+        unsafe {
+            ptr.as_ptr().write(value);
+            &mut *ptr.as_ptr()
+        }
+    }
+
+    /// Frees every chunk except the newest (and biggest) one, and starts
+    /// handing out memory from its beginning again.
+    pub fn reset(&mut self) {
+        let Some(chunk) = self.chunk.get() else {
+            return;
+        };
+        unsafe {
+            free_chunks((*chunk.as_ptr()).prev.take());
+            self.ptr
+                .set(chunk.as_ptr().cast::<u8>().add(size_of::<ChunkHeader>()));
+        }
+    }
+
+    /// Total size of all chunks currently held, including unused space.
+    pub fn allocated_bytes(&self) -> usize {
+        let mut total = 0;
+        let mut link = self.chunk.get();
+        while let Some(chunk) = link {
+            let header = unsafe { chunk.as_ref() };
+            total += header.layout.size();
+            link = header.prev;
+        }
+        total
+    }
+
+    // Carves `layout` out of the newest chunk, if it fits.
+    fn try_bump(&self, layout: Layout) -> Option<NonNull<u8>> {
+        let ptr = self.ptr.get();
+        let start = ptr.addr().checked_next_multiple_of(layout.align())?;
+        let end = start.checked_add(layout.size())?;
+        if ptr.is_null() || end > self.end.get().addr() {
+            return None;
+        }
+        self.ptr.set(ptr.wrapping_add(end - ptr.addr()));
+        NonNull::new(ptr.wrapping_add(start - ptr.addr()))
+    }
+
+    // Chains on a chunk big enough for `layout`, and at least double the last one.
+    fn new_chunk(&self, layout: Layout) -> Result<(), AllocError> {
+        let prev = self.chunk.get();
+        let prev_size = prev.map_or(0, |chunk| unsafe { chunk.as_ref().layout.size() });
+
+        // Room for the header, the worst-case padding, and the request itself.
+        let needed = size_of::<ChunkHeader>()
+            .checked_add(layout.align())
+            .and_then(|size| size.checked_add(layout.size()))
+            .ok_or(AllocError)?;
+        let size = needed
+            .max(prev_size.saturating_mul(2))
+            .max(FIRST_CHUNK_SIZE);
+        let chunk_layout =
+            Layout::from_size_align(size, align_of::<ChunkHeader>()).map_err(|_| AllocError)?;
+
+        let chunk = Global.allocate(chunk_layout)?.cast::<ChunkHeader>();
+        unsafe {
+            chunk.as_ptr().write(ChunkHeader {
+                prev,
+                layout: chunk_layout,
+            });
+            let base = chunk.as_ptr().cast::<u8>();
+            self.ptr.set(base.add(size_of::<ChunkHeader>()));
+            self.end.set(base.add(size));
+        }
+        self.chunk.set(Some(chunk));
+        Ok(())
+    }
+
+    fn is_last(&self, ptr: NonNull<u8>, layout: Layout) -> bool {
+        ptr.as_ptr().wrapping_add(layout.size()) == self.ptr.get()
+    }
+}
+
+unsafe fn free_chunks(mut link: Option<NonNull<ChunkHeader>>) {
+    while let Some(chunk) = link {
+        unsafe {
+            let ChunkHeader { prev, layout } = chunk.as_ptr().read();
+            Global.deallocate(chunk.cast(), layout);
+            link = prev;
+        }
+    }
+}
+
+unsafe impl Allocator for Bump {
+    fn allocate(&self, layout: Layout) -> Result<NonNull<u8>, AllocError> {
+        if layout.size() == 0 {
+            return Ok(allocator::dangling(layout));
+        }
+        if let Some(ptr) = self.try_bump(layout) {
+            return Ok(ptr);
+        }
+        self.new_chunk(layout)?;
+        self.try_bump(layout).ok_or(AllocError)
+    }
+
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+        if self.is_last(ptr, layout) {
+            self.ptr.set(ptr.as_ptr());
+        }
+    }
+
+    unsafe fn grow(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<u8>, AllocError> {
+        let fits = new_layout.size() <= self.end.get().addr().wrapping_sub(ptr.addr().get());
+        if self.is_last(ptr, old_layout)
+            && ptr.addr().get().is_multiple_of(new_layout.align())
+            && fits
+        {
+            unsafe { self.ptr.set(ptr.as_ptr().add(new_layout.size())) };
+            return Ok(ptr);
+        }
+        unsafe { allocator::reallocate(self, ptr, old_layout, new_layout, old_layout.size()) }
+    }
+
+    unsafe fn shrink(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<u8>, AllocError> {
+        if !ptr.addr().get().is_multiple_of(new_layout.align()) {
+            return unsafe {
+                allocator::reallocate(self, ptr, old_layout, new_layout, new_layout.size())
+            };
+        }
+        if self.is_last(ptr, old_layout) {
+            unsafe { self.ptr.set(ptr.as_ptr().add(new_layout.size())) };
+        }
+        Ok(ptr)
+    }
+}
+
+impl Default for Bump {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Drop for Bump {
+    fn drop(&mut self) {
+        unsafe { free_chunks(self.chunk.get()) }
+    }
+}
+
+impl fmt::Debug for Bump {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Bump")
+            .field("allocated_bytes", &self.allocated_bytes())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{my_deque::MyDeque, my_vec::MyVec};
+
+    #[test]
+    fn test_new_does_not_allocate() {
+        let bump = Bump::new();
+        assert_eq!(bump.allocated_bytes(), 0);
+        assert_eq!(*bump.alloc(()), ());
+        assert_eq!(bump.allocated_bytes(), 0);
+    }
+
+    #[test]
+    fn test_alloc_values() {
+        let bump = Bump::new();
+        let a = bump.alloc(1u8);
+        let b = bump.alloc(2u64);
+        let c = bump.alloc([3u16; 3]);
+        *a += 10;
+        assert_eq!((*a, *b, *c), (11, 2, [3; 3]));
+        assert_eq!(b as *mut u64 as usize % align_of::<u64>(), 0);
+    }
+
+    #[test]
+    fn test_chunks_chain_and_grow() {
+        let bump = Bump::new();
+        let first = bump.alloc([0u8; 400]);
+        assert_eq!(bump.allocated_bytes(), FIRST_CHUNK_SIZE);
+
+        // Doesn't fit in what's left, so a second, bigger chunk is chained on
+        let second = bump.alloc([1u8; 400]);
+        assert_eq!(bump.allocated_bytes(), FIRST_CHUNK_SIZE * 3);
+
+        // Requests bigger than double the last chunk get a chunk of their own
+        let big = bump.alloc([2u8; 4000]);
+        assert!(bump.allocated_bytes() >= FIRST_CHUNK_SIZE * 3 + 4000);
+
+        assert!(first.iter().all(|&b| b == 0));
+        assert!(second.iter().all(|&b| b == 1));
+        assert!(big.iter().all(|&b| b == 2));
+    }
+
+    #[test]
+    fn test_reset_keeps_newest_chunk() {
+        let mut bump = Bump::with_capacity(64);
+        for i in 0..200u64 {
+            bump.alloc(i);
+        }
+        let before = bump.allocated_bytes();
+        let newest = unsafe { bump.chunk.get().unwrap().as_ref().layout.size() };
+        assert!(newest < before);
+
+        bump.reset();
+        assert_eq!(bump.allocated_bytes(), newest);
+
+        let first = bump.alloc(7u64) as *mut u64;
+        let chunk = bump.chunk.get().unwrap().as_ptr() as usize;
+        assert_eq!(first as usize, chunk + size_of::<ChunkHeader>());
+    }
+
+    #[test]
+    fn test_vec_grows_in_place_at_the_end() {
+        let bump = Bump::new();
+        let mut vec = MyVec::new_in(&bump);
+        vec.push(0u32);
+        let start = vec.as_slice().as_ptr();
+        for i in 1..64 {
+            vec.push(i);
+        }
+        assert_eq!(vec.as_slice().as_ptr(), start);
+        assert_eq!(vec.as_slice(), (0..64).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_vec_moves_when_not_last() {
+        let bump = Bump::new();
+        let mut vec = MyVec::new_in(&bump);
+        vec.push(String::from("a"));
+        let _blocker = bump.alloc(1u8);
+        for i in 0..10 {
+            vec.push(i.to_string());
+        }
+        assert_eq!(vec.get_len(), 11);
+        assert_eq!(vec.get(10).map(String::as_str), Some("9"));
+    }
+
+    #[test]
+    fn test_deque_in_bump() {
+        let bump = Bump::new();
+        let mut deque = MyDeque::new_in(&bump);
+        for i in 0..20 {
+            deque.push_front(i);
+        }
+        deque.shrink_to_fit();
+        assert_eq!(deque.pop_back(), Some(0));
+        assert_eq!(deque.pop_front(), Some(19));
+    }
+}
@@ -0,0 +1,677 @@
+// Purpose: A small dependency-free binary format for the collections and
+// smart pointers, for shipping snapshots between processes.
+
+use alloc::boxed::Box;
+use core::{any::Any, fmt, marker::PhantomData};
+
+use crate::{
+    allocator::Allocator, my_arc::MyArc, my_deque::MyDeque, my_linked_list::LinkedList,
+    my_rc::MyRc, my_vec::MyVec, raw_vec::cautious_capacity,
+};
+
+/*
+Format:
+
+- Unsigned integers (except u8 and u128) are LEB128 varints: seven bits per
+  byte, low bits first, high bit set on every byte but the last. Signed
+  integers are zigzag-mapped first so small negatives stay short.
+- u8/i8 and bool are one raw byte, u128/i128 and floats are little-endian
+  fixed width, char is its scalar value as a varint.
+- A collection is its length as a varint followed by the elements in order.
+  Elements that encode to nothing (`()`) can't be checked against the
+  input left, so those sequences are capped at MAX_ZERO_BYTE_SEQ_LEN, on
+  both sides.
+- MyRc/MyArc start with a varint tag. 0 means the value follows inline and
+  gets the next id (0, 1, 2, ... in the order they finish encoding). A tag
+  of n > 0 points back at id n - 1, so a shared value is written once and
+  decodes back into a single shared allocation.
+- Decoding never preallocates more than the remaining input could hold (and
+  never more than about a megabyte), so a lying length prefix can only make
+  us fail, not allocate.
+*/
+
+/// Types that can be written with an [`Encoder`].
+pub trait Encode {
+    fn encode(&self, encoder: &mut Encoder<'_>);
+}
+
+/// Types that can be read back with a [`Decoder`].
+pub trait Decode: Sized {
+    /// The fewest bytes a value ever encodes to. Sequence lengths are
+    /// checked against it, so it must never be more than the truth.
+    /// Defaults to 1; a type that can encode to nothing sets 0.
+    const MIN_ENCODED_LEN: usize = 1;
+
+    fn decode(decoder: &mut Decoder<'_>) -> Result<Self, DecodeError>;
+}
+
+/// The longest sequence of zero-byte elements (`MIN_ENCODED_LEN == 0`)
+/// that will be encoded or decoded. Anything else is bounded by its input.
+pub const MAX_ZERO_BYTE_SEQ_LEN: usize = 1 << 16;
+
+/// Why a byte string couldn't be decoded.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DecodeError {
+    /// The input ended in the middle of a value.
+    UnexpectedEof,
+    /// A varint was longer than ten bytes or didn't fit the target type.
+    IntegerOverflow,
+    /// A bool byte other than 0 or 1.
+    InvalidBool(u8),
+    /// A char that isn't a Unicode scalar value.
+    InvalidChar(u32),
+    /// A pointer tag naming an id that hasn't been decoded yet, or that
+    /// was decoded as a different type.
+    InvalidBackReference(usize),
+    /// `decode_from_slice` finished with this many bytes left over.
+    TrailingBytes(usize),
+    /// A sequence of zero-byte elements longer than [`MAX_ZERO_BYTE_SEQ_LEN`].
+    SequenceTooLong(usize),
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DecodeError::UnexpectedEof => f.write_str("unexpected end of input"),
+            DecodeError::IntegerOverflow => f.write_str("integer out of range"),
+            DecodeError::InvalidBool(byte) => write!(f, "invalid bool byte {byte:#04x}"),
+            DecodeError::InvalidChar(value) => write!(f, "invalid char {value:#x}"),
+            DecodeError::InvalidBackReference(id) => {
+                write!(f, "invalid back-reference to shared value {id}")
+            }
+            DecodeError::TrailingBytes(n) => write!(f, "{n} trailing bytes after the value"),
+            DecodeError::SequenceTooLong(len) => {
+                write!(f, "sequence of {len} zero-byte elements is too long")
+            }
+        }
+    }
+}
+
+impl core::error::Error for DecodeError {}
+
+// =====================
+// Encoder and decoder
+// =====================
+
+/// Writes values into a byte buffer.
+///
+/// Shared pointers are recognised by address, so everything encoded into
+/// one `Encoder` is borrowed for `'a` and can't be freed (and its address
+/// reused) before the buffer is finished.
+pub struct Encoder<'a> {
+    buf: MyVec<u8>,
+    // (address, id), sorted by address
+    shared: MyVec<(usize, usize)>,
+    _borrow: PhantomData<&'a ()>,
+}
+
+impl<'a> Encoder<'a> {
+    pub fn new() -> Self {
+        Encoder {
+            buf: MyVec::new(),
+            shared: MyVec::new(),
+            _borrow: PhantomData,
+        }
+    }
+
+    /// Appends `value`. Pointers shared with earlier values are written as
+    /// back-references.
+    ///
+    /// Panics on a sequence of more than [`MAX_ZERO_BYTE_SEQ_LEN`]
+    /// zero-byte elements, which couldn't be decoded.
+    pub fn encode<T: Encode + ?Sized>(&mut self, value: &'a T) {
+        value.encode(self);
+    }
+
+    pub fn write_u8(&mut self, byte: u8) {
+        self.buf.push(byte);
+    }
+
+    pub fn write_bytes(&mut self, bytes: &[u8]) {
+        self.buf.extend(bytes);
+    }
+
+    pub fn write_varint(&mut self, mut value: u64) {
+        while value >= 0x80 {
+            self.write_u8(value as u8 | 0x80);
+            value >>= 7;
+        }
+        self.write_u8(value as u8);
+    }
+
+    /// Writes a collection length.
+    pub fn write_len(&mut self, len: usize) {
+        self.write_varint(len as u64);
+    }
+
+    /// The bytes written so far.
+    pub fn finish(self) -> MyVec<u8> {
+        self.buf
+    }
+
+    fn encode_shared<T: Encode + ?Sized>(&mut self, value: &T) {
+        let addr = value as *const T as *const () as usize;
+        match self.shared.binary_search_by_key(&addr, |&(addr, _)| addr) {
+            Ok(i) => {
+                let id = self.shared[i].1;
+                self.write_varint(id as u64 + 1);
+            }
+            Err(_) => {
+                self.write_u8(0);
+                value.encode(self);
+                // Search again, the value may have registered pointers of its own
+                let id = self.shared.len();
+                let slot = self
+                    .shared
+                    .binary_search_by_key(&addr, |&(addr, _)| addr)
+                    .unwrap_err();
+                self.shared.insert(slot, (addr, id));
+            }
+        }
+    }
+}
+
+impl Default for Encoder<'_> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Reads values back out of a byte slice.
+pub struct Decoder<'de> {
+    input: &'de [u8],
+    // Every MyRc/MyArc decoded so far, by id
+    shared: MyVec<Box<dyn Any>>,
+}
+
+impl<'de> Decoder<'de> {
+    pub fn new(input: &'de [u8]) -> Self {
+        Decoder {
+            input,
+            shared: MyVec::new(),
+        }
+    }
+
+    pub fn decode<T: Decode>(&mut self) -> Result<T, DecodeError> {
+        T::decode(self)
+    }
+
+    /// How many bytes haven't been read yet.
+    pub fn remaining(&self) -> usize {
+        self.input.len()
+    }
+
+    pub fn read_u8(&mut self) -> Result<u8, DecodeError> {
+        let (&byte, rest) = self.input.split_first().ok_or(DecodeError::UnexpectedEof)?;
+        self.input = rest;
+        Ok(byte)
+    }
+
+    pub fn read_bytes(&mut self, n: usize) -> Result<&'de [u8], DecodeError> {
+        if n > self.input.len() {
+            return Err(DecodeError::UnexpectedEof);
+        }
+        let (bytes, rest) = self.input.split_at(n);
+        self.input = rest;
+        Ok(bytes)
+    }
+
+    pub fn read_array<const N: usize>(&mut self) -> Result<[u8; N], DecodeError> {
+        Ok(self.read_bytes(N)?.try_into().unwrap())
+    }
+
+    pub fn read_varint(&mut self) -> Result<u64, DecodeError> {
+        let mut value = 0u64;
+        for shift in (0..64).step_by(7) {
+            let byte = self.read_u8()?;
+            let bits = u64::from(byte & 0x7f);
+            // The tenth byte only has room for the top bit
+            if shift == 63 && bits > 1 {
+                return Err(DecodeError::IntegerOverflow);
+            }
+            value |= bits << shift;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        Err(DecodeError::IntegerOverflow)
+    }
+
+    /// Reads a length prefix. Collections use [`read_seq_len`](Self::read_seq_len),
+    /// which also checks it against the input left.
+    pub fn read_len(&mut self) -> Result<usize, DecodeError> {
+        usize::try_from(self.read_varint()?).map_err(|_| DecodeError::IntegerOverflow)
+    }
+
+    /// Reads the length of a sequence of `T`s, which come next.
+    ///
+    /// Lengths the rest of the input can't hold at `T::MIN_ENCODED_LEN`
+    /// bytes apiece are rejected. Zero-byte elements like `()` would let a
+    /// ten-byte prefix stand for 2^63 of them (one linked-list node or loop
+    /// iteration apiece), so those are capped at [`MAX_ZERO_BYTE_SEQ_LEN`]
+    /// instead.
+    pub fn read_seq_len<T: Decode>(&mut self) -> Result<usize, DecodeError> {
+        let len = self.read_len()?;
+        match T::MIN_ENCODED_LEN {
+            0 if len > MAX_ZERO_BYTE_SEQ_LEN => Err(DecodeError::SequenceTooLong(len)),
+            0 => Ok(len),
+            min if len > self.remaining() / min => Err(DecodeError::UnexpectedEof),
+            _ => Ok(len),
+        }
+    }
+
+    /// How many `T`s it's safe to reserve for a length prefix of `len`.
+    ///
+    /// Every non-empty element takes at least a byte, so a length longer
+    /// than the rest of the input is a lie we don't have to pay for.
+    pub fn cautious_capacity<T>(&self, len: usize) -> usize {
+        cautious_capacity::<T>(Some(len.min(self.remaining())))
+    }
+
+    /// Errors unless every byte has been read.
+    pub fn finish(self) -> Result<(), DecodeError> {
+        match self.input.len() {
+            0 => Ok(()),
+            n => Err(DecodeError::TrailingBytes(n)),
+        }
+    }
+
+    fn decode_shared<P, T>(&mut self, new: fn(T) -> P) -> Result<P, DecodeError>
+    where
+        P: Clone + 'static,
+        T: Decode,
+    {
+        match self.read_len()? {
+            0 => {
+                let pointer = new(T::decode(self)?);
+                self.shared.push(Box::new(pointer.clone()));
+                Ok(pointer)
+            }
+            tag => {
+                let id = tag - 1;
+                self.shared
+                    .get(id)
+                    .and_then(|pointer| pointer.downcast_ref::<P>())
+                    .cloned()
+                    .ok_or(DecodeError::InvalidBackReference(id))
+            }
+        }
+    }
+}
+
+/// Encodes `value` into a fresh buffer.
+pub fn encode_to_vec<T: Encode + ?Sized>(value: &T) -> MyVec<u8> {
+    let mut encoder = Encoder::new();
+    encoder.encode(value);
+    encoder.finish()
+}
+
+/// Decodes a `T` that must take up all of `bytes`.
+pub fn decode_from_slice<T: Decode>(bytes: &[u8]) -> Result<T, DecodeError> {
+    let mut decoder = Decoder::new(bytes);
+    let value = decoder.decode()?;
+    decoder.finish()?;
+    Ok(value)
+}
+
+/// Writes `value` as one frame: its encoded length as a varint, then the
+/// encoding. Frames can be sent back to back over a pipe or socket.
+#[cfg(feature = "std")]
+pub fn write_frame<T, W>(writer: &mut W, value: &T) -> std::io::Result<()>
+where
+    T: Encode + ?Sized,
+    W: std::io::Write,
+{
+    let bytes = encode_to_vec(value);
+    let mut prefix = Encoder::new();
+    prefix.write_len(bytes.len());
+    writer.write_all(&prefix.finish())?;
+    writer.write_all(&bytes)
+}
+
+/// Reads one frame written by [`write_frame`].
+///
+/// The frame is buffered as it arrives rather than reserved from its
+/// length prefix, so a bogus prefix fails with `UnexpectedEof` instead of
+/// allocating. Malformed frames are `InvalidData` errors.
+#[cfg(feature = "std")]
+pub fn read_frame<T, R>(reader: &mut R) -> std::io::Result<T>
+where
+    T: Decode,
+    R: std::io::Read,
+{
+    use std::io::{Error, ErrorKind, Read};
+
+    // The prefix is at most ten bytes, read it one at a time
+    let mut prefix = MyVec::new();
+    loop {
+        let mut byte = [0];
+        reader.read_exact(&mut byte)?;
+        prefix.push(byte[0]);
+        if byte[0] & 0x80 == 0 || prefix.len() == 10 {
+            break;
+        }
+    }
+    let len = Decoder::new(&prefix)
+        .read_len()
+        .map_err(|err| Error::new(ErrorKind::InvalidData, err))?;
+
+    let mut bytes = std::vec::Vec::new();
+    reader.take(len as u64).read_to_end(&mut bytes)?;
+    if bytes.len() != len {
+        return Err(ErrorKind::UnexpectedEof.into());
+    }
+    decode_from_slice(&bytes).map_err(|err| Error::new(ErrorKind::InvalidData, err))
+}
+
+// ==========
+// Primitives
+// ==========
+
+macro_rules! varint_unsigned {
+    ($($ty:ty)*) => {$(
+        impl Encode for $ty {
+            fn encode(&self, encoder: &mut Encoder<'_>) {
+                encoder.write_varint(*self as u64);
+            }
+        }
+
+        impl Decode for $ty {
+            fn decode(decoder: &mut Decoder<'_>) -> Result<Self, DecodeError> {
+                <$ty>::try_from(decoder.read_varint()?).map_err(|_| DecodeError::IntegerOverflow)
+            }
+        }
+    )*};
+}
+
+macro_rules! varint_signed {
+    ($($ty:ty)*) => {$(
+        impl Encode for $ty {
+            fn encode(&self, encoder: &mut Encoder<'_>) {
+                let value = *self as i64;
+                encoder.write_varint(((value << 1) ^ (value >> 63)) as u64);
+            }
+        }
+
+        impl Decode for $ty {
+            fn decode(decoder: &mut Decoder<'_>) -> Result<Self, DecodeError> {
+                let zigzag = decoder.read_varint()?;
+                let value = (zigzag >> 1) as i64 ^ -((zigzag & 1) as i64);
+                <$ty>::try_from(value).map_err(|_| DecodeError::IntegerOverflow)
+            }
+        }
+    )*};
+}
+
+macro_rules! fixed_le {
+    ($($ty:ty)*) => {$(
+        impl Encode for $ty {
+            fn encode(&self, encoder: &mut Encoder<'_>) {
+                encoder.write_bytes(&self.to_le_bytes());
+            }
+        }
+
+        impl Decode for $ty {
+            fn decode(decoder: &mut Decoder<'_>) -> Result<Self, DecodeError> {
+                Ok(<$ty>::from_le_bytes(decoder.read_array()?))
+            }
+        }
+    )*};
+}
+
+varint_unsigned!(u16 u32 u64 usize);
+varint_signed!(i16 i32 i64 isize);
+fixed_le!(u8 i8 u128 i128 f32 f64);
+
+impl Encode for bool {
+    fn encode(&self, encoder: &mut Encoder<'_>) {
+        encoder.write_u8(*self as u8);
+    }
+}
+
+impl Decode for bool {
+    fn decode(decoder: &mut Decoder<'_>) -> Result<Self, DecodeError> {
+        match decoder.read_u8()? {
+            0 => Ok(false),
+            1 => Ok(true),
+            byte => Err(DecodeError::InvalidBool(byte)),
+        }
+    }
+}
+
+impl Encode for char {
+    fn encode(&self, encoder: &mut Encoder<'_>) {
+        (*self as u32).encode(encoder);
+    }
+}
+
+impl Decode for char {
+    fn decode(decoder: &mut Decoder<'_>) -> Result<Self, DecodeError> {
+        let value = u32::decode(decoder)?;
+        char::from_u32(value).ok_or(DecodeError::InvalidChar(value))
+    }
+}
+
+impl Encode for () {
+    fn encode(&self, _: &mut Encoder<'_>) {}
+}
+
+impl Decode for () {
+    const MIN_ENCODED_LEN: usize = 0;
+
+    fn decode(_: &mut Decoder<'_>) -> Result<Self, DecodeError> {
+        Ok(())
+    }
+}
+
+// ===========
+// Collections
+// ===========
+
+// Panics on a sequence the decoder would refuse: more than
+// MAX_ZERO_BYTE_SEQ_LEN elements that took less than a byte apiece.
+fn encode_seq<'a, T, I>(encoder: &mut Encoder<'_>, len: usize, iter: I)
+where
+    T: Encode + 'a,
+    I: IntoIterator<Item = &'a T>,
+{
+    encoder.write_len(len);
+    let start = encoder.buf.len();
+    for item in iter {
+        item.encode(encoder);
+    }
+    assert!(
+        len <= MAX_ZERO_BYTE_SEQ_LEN || encoder.buf.len() - start >= len,
+        "a sequence of {len} zero-byte elements is longer than MAX_ZERO_BYTE_SEQ_LEN"
+    );
+}
+
+impl<T: Encode, A: Allocator> Encode for MyVec<T, A> {
+    fn encode(&self, encoder: &mut Encoder<'_>) {
+        encode_seq(encoder, self.len(), self);
+    }
+}
+
+impl<T: Decode> Decode for MyVec<T> {
+    fn decode(decoder: &mut Decoder<'_>) -> Result<Self, DecodeError> {
+        let len = decoder.read_seq_len::<T>()?;
+        let mut vec = MyVec::with_capacity(decoder.cautious_capacity::<T>(len));
+        for _ in 0..len {
+            vec.push(T::decode(decoder)?);
+        }
+        Ok(vec)
+    }
+}
+
+impl<T: Encode, A: Allocator> Encode for MyDeque<T, A> {
+    fn encode(&self, encoder: &mut Encoder<'_>) {
+        encode_seq(encoder, self.len(), self);
+    }
+}
+
+impl<T: Decode> Decode for MyDeque<T> {
+    fn decode(decoder: &mut Decoder<'_>) -> Result<Self, DecodeError> {
+        let len = decoder.read_seq_len::<T>()?;
+        let mut deque = MyDeque::with_capacity(decoder.cautious_capacity::<T>(len));
+        for _ in 0..len {
+            deque.push_back(T::decode(decoder)?);
+        }
+        Ok(deque)
+    }
+}
+
+impl<T: Encode, A: Allocator> Encode for LinkedList<T, A> {
+    fn encode(&self, encoder: &mut Encoder<'_>) {
+        encode_seq(encoder, self.len(), self);
+    }
+}
+
+impl<T: Decode> Decode for LinkedList<T> {
+    fn decode(decoder: &mut Decoder<'_>) -> Result<Self, DecodeError> {
+        let len = decoder.read_seq_len::<T>()?;
+        let mut list = LinkedList::new();
+        for _ in 0..len {
+            list.push_back(T::decode(decoder)?);
+        }
+        Ok(list)
+    }
+}
+
+// ==============
+// Smart pointers
+// ==============
+
+impl<T: Encode, A: Allocator> Encode for MyRc<T, A> {
+    fn encode(&self, encoder: &mut Encoder<'_>) {
+        encoder.encode_shared::<T>(self);
+    }
+}
+
+impl<T: Decode + 'static> Decode for MyRc<T> {
+    fn decode(decoder: &mut Decoder<'_>) -> Result<Self, DecodeError> {
+        decoder.decode_shared(MyRc::new)
+    }
+}
+
+impl<T: Encode, A: Allocator> Encode for MyArc<T, A> {
+    fn encode(&self, encoder: &mut Encoder<'_>) {
+        encoder.encode_shared::<T>(self);
+    }
+}
+
+impl<T: Decode + 'static> Decode for MyArc<T> {
+    fn decode(decoder: &mut Decoder<'_>) -> Result<Self, DecodeError> {
+        decoder.decode_shared(MyArc::new)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn round_trip<T: Encode + Decode>(value: &T) -> T {
+        decode_from_slice(&encode_to_vec(value)).unwrap()
+    }
+
+    #[test]
+    fn test_varint_lengths() {
+        for (value, len) in [(0u64, 1), (127, 1), (128, 2), (16_383, 2), (u64::MAX, 10)] {
+            let bytes = encode_to_vec(&value);
+            assert_eq!(bytes.len(), len, "{value}");
+            assert_eq!(round_trip(&value), value);
+        }
+    }
+
+    #[test]
+    fn test_primitives_round_trip() {
+        for value in [0i64, -1, 1, i64::MIN, i64::MAX] {
+            assert_eq!(round_trip(&value), value);
+        }
+        assert_eq!(encode_to_vec(&-1i32)[..], [1]);
+        assert_eq!(round_trip(&u128::MAX), u128::MAX);
+        assert_eq!(round_trip(&-2.5f64), -2.5);
+        assert_eq!(round_trip(&'ß'), 'ß');
+        assert!(round_trip(&true));
+    }
+
+    #[test]
+    fn test_malformed_input() {
+        assert_eq!(
+            decode_from_slice::<u32>(&[]),
+            Err(DecodeError::UnexpectedEof)
+        );
+        assert_eq!(
+            decode_from_slice::<u64>(&[0xff; 11]),
+            Err(DecodeError::IntegerOverflow)
+        );
+        assert_eq!(
+            decode_from_slice::<u64>(&[0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x02]),
+            Err(DecodeError::IntegerOverflow)
+        );
+        assert_eq!(
+            decode_from_slice::<u8>(&[1, 2]),
+            Err(DecodeError::TrailingBytes(1))
+        );
+        assert_eq!(
+            decode_from_slice::<u16>(&[0x80, 0x80, 0x04]),
+            Err(DecodeError::IntegerOverflow)
+        );
+        assert_eq!(
+            decode_from_slice::<bool>(&[2]),
+            Err(DecodeError::InvalidBool(2))
+        );
+        assert_eq!(
+            decode_from_slice::<char>(&encode_to_vec(&0xd800u32)),
+            Err(DecodeError::InvalidChar(0xd800))
+        );
+    }
+
+    #[test]
+    fn test_lying_length_prefix() {
+        // Claims u64::MAX elements but only carries two
+        let mut encoder = Encoder::new();
+        encoder.write_varint(u64::MAX);
+        encoder.write_bytes(&[0, 0]);
+        let bytes = encoder.finish();
+
+        let mut decoder = Decoder::new(&bytes);
+        decoder.read_len().unwrap();
+        assert_eq!(decoder.cautious_capacity::<u64>(usize::MAX), 2);
+        assert_eq!(
+            decode_from_slice::<MyVec<u64>>(&bytes),
+            Err(DecodeError::UnexpectedEof)
+        );
+    }
+
+    #[test]
+    fn test_shared_pointers_are_written_once() {
+        let shared = MyRc::new(MyVec::from([7u64; 100]));
+        let pair = MyVec::from([shared.clone(), shared.clone(), MyRc::new(MyVec::new())]);
+        let bytes = encode_to_vec(&pair);
+        // length, tag + 101 bytes, back-reference, tag + empty vec
+        assert_eq!(bytes.len(), 1 + 102 + 1 + 2);
+
+        let back: MyVec<MyRc<MyVec<u64>>> = decode_from_slice(&bytes).unwrap();
+        assert_eq!(back[0].get_count(), 2);
+        assert_eq!(back[2].get_count(), 1);
+        assert_eq!(back[1][..], [7; 100]);
+    }
+
+    #[test]
+    fn test_invalid_back_references() {
+        // A MyRc<u32>, then a MyArc<u32> pointing back at it
+        let bytes = [0, 5, 1];
+        let mut decoder = Decoder::new(&bytes);
+        assert_eq!(*decoder.decode::<MyRc<u32>>().unwrap(), 5);
+        assert_eq!(
+            decoder.decode::<MyArc<u32>>().err(),
+            Some(DecodeError::InvalidBackReference(0))
+        );
+
+        // Points at an id that was never decoded
+        assert_eq!(
+            decode_from_slice::<MyRc<u8>>(&[3]).err(),
+            Some(DecodeError::InvalidBackReference(2))
+        );
+    }
+}
@@ -0,0 +1,506 @@
+// Purpose: Range removal and filtering shared by the contiguous vectors.
+//
+// Everything here works on a base pointer plus the owner's `len`, so any
+// vector whose first `len` slots are initialised can use it: MyVec hands in
+// its RawVec pointer, fixed-capacity vectors their inline array.
+
+#[cfg(feature = "alloc")]
+use core::cmp;
+use core::{
+    fmt,
+    iter::FusedIterator,
+    marker::PhantomData,
+    mem,
+    ops::{Bound, Range, RangeBounds},
+    ptr::{self, NonNull},
+    slice,
+};
+
+#[cfg(feature = "alloc")]
+use crate::{
+    allocator::{Allocator, Global},
+    my_vec::MyVec,
+    raw_vec::{self, RawVec, TryReserveError},
+};
+
+/*
+Panic safety:
+
+- While a Drain or ExtractIf is alive the owner's `len` only covers the
+  elements nothing can touch, so leaking one with `mem::forget` leaks
+  elements but never exposes moved-out slots.
+- Each of them keeps enough state to close the gap from its Drop, which
+  also runs while unwinding out of a user closure or element destructor.
+- Elements are moved once: into their final slot, or out to the caller.
+*/
+
+/// Turns any `RangeBounds` into a `Range` inside `0..len`, panicking like
+/// slice indexing does when it isn't.
+pub(crate) fn slice_range<R: RangeBounds<usize>>(range: R, len: usize) -> Range<usize> {
+    let start = match range.start_bound() {
+        Bound::Included(&start) => start,
+        Bound::Excluded(&start) => start
+            .checked_add(1)
+            .expect("attempted to index slice from after maximum usize"),
+        Bound::Unbounded => 0,
+    };
+    let end = match range.end_bound() {
+        Bound::Included(&end) => end
+            .checked_add(1)
+            .expect("attempted to index slice up to maximum usize"),
+        Bound::Excluded(&end) => end,
+        Bound::Unbounded => len,
+    };
+
+    assert!(
+        start <= end,
+        "slice index starts at {start} but ends at {end}"
+    );
+    assert!(
+        end <= len,
+        "range end index {end} out of range for slice of length {len}"
+    );
+    start..end
+}
+
+/// Drops everything from `new_len` on. Does nothing if `new_len >= *len`.
+///
+/// # Safety
+///
+/// `buf[..*len]` must be initialised.
+pub(crate) unsafe fn truncate<T>(buf: NonNull<T>, len: &mut usize, new_len: usize) {
+    if new_len >= *len {
+        return;
+    }
+    let tail = ptr::slice_from_raw_parts_mut(unsafe { buf.as_ptr().add(new_len) }, *len - new_len);
+    // Shorten first: if a destructor panics the rest are still dropped by
+    // the slice drop glue, and none of them are reachable again.
+    *len = new_len;
+    unsafe { ptr::drop_in_place(tail) }
+}
+
+/// Removes consecutive elements `same` says are equal to the one kept before them.
+///
+/// # Safety
+///
+/// `buf[..*len]` must be initialised.
+pub(crate) unsafe fn dedup_by<T, F>(buf: NonNull<T>, len: &mut usize, mut same: F)
+where
+    F: FnMut(&mut T, &mut T) -> bool,
+{
+    let old_len = *len;
+    if old_len <= 1 {
+        return;
+    }
+
+    // `[write, read)` is the gap left by removed duplicates. If `same` or a
+    // destructor panics, the unvisited elements are slid down over it.
+    struct FillGapOnDrop<'a, T> {
+        buf: NonNull<T>,
+        len: &'a mut usize,
+        read: usize,
+        write: usize,
+        old_len: usize,
+    }
+
+    impl<T> Drop for FillGapOnDrop<'_, T> {
+        fn drop(&mut self) {
+            let rest = self.old_len - self.read;
+            unsafe {
+                let src = self.buf.as_ptr().add(self.read);
+                let dst = self.buf.as_ptr().add(self.write);
+                ptr::copy(src, dst, rest);
+            }
+            *self.len = self.write + rest;
+        }
+    }
+
+    let mut gap = FillGapOnDrop {
+        buf,
+        len,
+        read: 1,
+        write: 1,
+        old_len,
+    };
+    let ptr = buf.as_ptr();
+    while gap.read < old_len {
+        unsafe {
+            let cur = ptr.add(gap.read);
+            let prev = ptr.add(gap.write - 1);
+            if same(&mut *cur, &mut *prev) {
+                // Step past it before dropping, so a panicking drop isn't repeated
+                gap.read += 1;
+                ptr::drop_in_place(cur);
+            } else {
+                ptr::copy(cur, ptr.add(gap.write), 1);
+                gap.write += 1;
+                gap.read += 1;
+            }
+        }
+    }
+}
+
+/// Removes a range from a vector, yielding the removed elements.
+///
+/// Created by [`MyVec::drain`]. Whatever isn't yielded is dropped along with
+/// the iterator, and the elements after the range slide down to close the gap.
+pub struct Drain<'a, T> {
+    pub(crate) buf: NonNull<T>,
+    // Stays at the start of the range until the tail is moved back.
+    pub(crate) len: &'a mut usize,
+    idx: usize,
+    end: usize,
+    pub(crate) tail_start: usize,
+    pub(crate) tail_len: usize,
+    _marker: PhantomData<T>,
+}
+
+unsafe impl<T: Send> Send for Drain<'_, T> {}
+unsafe impl<T: Sync> Sync for Drain<'_, T> {}
+
+impl<'a, T> Drain<'a, T> {
+    /// # Safety
+    ///
+    /// `buf[..*len]` must be initialised and `range` must lie inside it.
+    pub(crate) unsafe fn new(buf: NonNull<T>, len: &'a mut usize, range: Range<usize>) -> Self {
+        let old_len = mem::replace(len, range.start);
+        Drain {
+            buf,
+            len,
+            idx: range.start,
+            end: range.end,
+            tail_start: range.end,
+            tail_len: old_len - range.end,
+            _marker: PhantomData,
+        }
+    }
+
+    /// The elements that haven't been yielded yet.
+    pub fn as_slice(&self) -> &[T] {
+        unsafe { slice::from_raw_parts(self.buf.as_ptr().add(self.idx), self.end - self.idx) }
+    }
+
+    // Writes items from `iter` into the gap between `len` and the tail.
+    // Returns false if the iterator ran out before the gap was full.
+    #[cfg(feature = "alloc")]
+    fn fill<I: Iterator<Item = T>>(&mut self, iter: &mut I) -> bool {
+        while *self.len < self.tail_start {
+            match iter.next() {
+                Some(item) => {
+                    unsafe { self.buf.as_ptr().add(*self.len).write(item) };
+                    *self.len += 1;
+                }
+                None => return false,
+            }
+        }
+        true
+    }
+}
+
+impl<T> Iterator for Drain<'_, T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        if self.idx == self.end {
+            return None;
+        }
+        let item = unsafe { self.buf.as_ptr().add(self.idx).read() };
+        self.idx += 1;
+        Some(item)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.end - self.idx;
+        (len, Some(len))
+    }
+}
+
+impl<T> DoubleEndedIterator for Drain<'_, T> {
+    fn next_back(&mut self) -> Option<T> {
+        if self.idx == self.end {
+            return None;
+        }
+        self.end -= 1;
+        Some(unsafe { self.buf.as_ptr().add(self.end).read() })
+    }
+}
+
+impl<T> ExactSizeIterator for Drain<'_, T> {}
+
+impl<T> FusedIterator for Drain<'_, T> {}
+
+impl<T> Drop for Drain<'_, T> {
+    fn drop(&mut self) {
+        // Moves the tail back even if dropping the leftovers panics.
+        struct MoveTail<'r, 'a, T>(&'r mut Drain<'a, T>);
+
+        impl<T> Drop for MoveTail<'_, '_, T> {
+            fn drop(&mut self) {
+                let drain = &mut *self.0;
+                let start = *drain.len;
+                if drain.tail_len > 0 && drain.tail_start != start {
+                    unsafe {
+                        let src = drain.buf.as_ptr().add(drain.tail_start);
+                        let dst = drain.buf.as_ptr().add(start);
+                        ptr::copy(src, dst, drain.tail_len);
+                    }
+                }
+                *drain.len = start + drain.tail_len;
+            }
+        }
+
+        let leftover = ptr::slice_from_raw_parts_mut(
+            unsafe { self.buf.as_ptr().add(self.idx) },
+            self.end - self.idx,
+        );
+        self.idx = self.end;
+        let _guard = MoveTail(self);
+        unsafe { ptr::drop_in_place(leftover) }
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for Drain<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Drain").field(&self.as_slice()).finish()
+    }
+}
+
+/// Replaces a range of a [`MyVec`] or an
+/// [`InlineVec`](crate::inline_vec::InlineVec) with the items of another
+/// iterator.
+///
+/// Created by [`MyVec::splice`]. The removed elements are yielded; the
+/// replacements are written in when the `Splice` is dropped.
+#[cfg(feature = "alloc")]
+pub struct Splice<'a, I: Iterator + 'a, A: Allocator + 'a = Global> {
+    pub(crate) drain: Drain<'a, I::Item>,
+    pub(crate) buf: &'a mut RawVec<I::Item, A>,
+    // InlineVec's N. Until `buf` has a capacity the elements are in those
+    // inline slots, which is where `drain.buf` points. 0 for MyVec.
+    pub(crate) inline_cap: usize,
+    pub(crate) replace_with: I,
+}
+
+#[cfg(feature = "alloc")]
+impl<I: Iterator, A: Allocator> Splice<'_, I, A> {
+    fn capacity(&self) -> usize {
+        if self.buf.cap == 0 {
+            self.inline_cap
+        } else {
+            self.buf.cap
+        }
+    }
+
+    // Makes room for `len + additional` slots, keeping the first `len`.
+    // Outgrowing the inline slots spills them, to at least twice their
+    // number like `InlineVec::reserve`.
+    fn reserve(&mut self, len: usize, additional: usize) {
+        if self.buf.cap != 0 || self.inline_cap == 0 {
+            self.buf.reserve(len, additional);
+        } else if additional > self.inline_cap - len {
+            let required = raw_vec::handle_error(
+                len.checked_add(additional)
+                    .ok_or(TryReserveError::CapacityOverflow),
+            );
+            let cap = cmp::max(required, self.inline_cap.saturating_mul(2));
+            self.buf.reserve_exact(0, cap);
+            unsafe {
+                ptr::copy_nonoverlapping(
+                    self.drain.buf.as_ptr(),
+                    self.buf.ptr.as_ptr().cast(),
+                    len,
+                );
+            }
+        } else {
+            return;
+        }
+        self.drain.buf = self.buf.ptr.cast();
+    }
+
+    // Makes room for `additional` more items between `len` and the tail.
+    fn move_tail(&mut self, additional: usize) {
+        self.reserve(self.drain.tail_start + self.drain.tail_len, additional);
+
+        let drain = &mut self.drain;
+        let new_tail_start = drain.tail_start + additional;
+        unsafe {
+            let src = drain.buf.as_ptr().add(drain.tail_start);
+            let dst = drain.buf.as_ptr().add(new_tail_start);
+            ptr::copy(src, dst, drain.tail_len);
+        }
+        drain.tail_start = new_tail_start;
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<I: Iterator, A: Allocator> Iterator for Splice<'_, I, A> {
+    type Item = I::Item;
+
+    fn next(&mut self) -> Option<I::Item> {
+        self.drain.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.drain.size_hint()
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<I: Iterator, A: Allocator> DoubleEndedIterator for Splice<'_, I, A> {
+    fn next_back(&mut self) -> Option<I::Item> {
+        self.drain.next_back()
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<I: Iterator, A: Allocator> ExactSizeIterator for Splice<'_, I, A> {}
+
+#[cfg(feature = "alloc")]
+impl<I: Iterator, A: Allocator> Drop for Splice<'_, I, A> {
+    fn drop(&mut self) {
+        self.drain.by_ref().for_each(drop);
+
+        // Drain's Drop moves the tail back to wherever `len` ends up, so
+        // every early return (or panic in `replace_with`) leaves a valid vector.
+        if self.drain.tail_len == 0 {
+            while let Some(item) = self.replace_with.next() {
+                let len = *self.drain.len;
+                if len == self.capacity() {
+                    self.reserve(len, 1);
+                }
+                unsafe { self.drain.buf.as_ptr().add(len).write(item) };
+                *self.drain.len += 1;
+            }
+            self.drain.tail_start = *self.drain.len;
+            return;
+        }
+
+        if !self.drain.fill(&mut self.replace_with) {
+            return;
+        }
+
+        // Trust an exact size hint to move the tail only once
+        let (lower, upper) = self.replace_with.size_hint();
+        if lower > 0 && upper == Some(lower) {
+            self.move_tail(lower);
+            if !self.drain.fill(&mut self.replace_with) {
+                return;
+            }
+        }
+
+        // Otherwise buffer the rest so the tail still moves just once more.
+        // The buffer comes from the vector's allocator, which `move_tail`
+        // needs back through `buf`: it's taken apart before and freed with
+        // a fresh borrow after.
+        let mut rest = MyVec::new_in(&self.buf.alloc);
+        rest.extend(self.replace_with.by_ref());
+        let (ptr, len, cap) = rest.into_raw_parts();
+        if len > 0 {
+            self.move_tail(len);
+            let mut items = (0..len).map(|i| unsafe { ptr.add(i).read().assume_init() });
+            let filled = self.drain.fill(&mut items);
+            debug_assert!(filled);
+        }
+        drop(RawVec {
+            ptr,
+            cap,
+            alloc: &self.buf.alloc,
+        });
+    }
+}
+
+/// Removes and yields every element the predicate returns `true` for.
+///
+/// Created by [`MyVec::extract_if`]. Elements not yet visited when it is
+/// dropped are kept.
+pub struct ExtractIf<'a, T, F>
+where
+    F: FnMut(&mut T) -> bool,
+{
+    buf: NonNull<T>,
+    // Zero while iterating, so a leaked ExtractIf only leaks.
+    len: &'a mut usize,
+    idx: usize,
+    del: usize,
+    old_len: usize,
+    pred: F,
+}
+
+unsafe impl<T: Send, F: FnMut(&mut T) -> bool + Send> Send for ExtractIf<'_, T, F> {}
+unsafe impl<T: Sync, F: FnMut(&mut T) -> bool + Sync> Sync for ExtractIf<'_, T, F> {}
+
+impl<'a, T, F> ExtractIf<'a, T, F>
+where
+    F: FnMut(&mut T) -> bool,
+{
+    /// # Safety
+    ///
+    /// `buf[..*len]` must be initialised.
+    pub(crate) unsafe fn new(buf: NonNull<T>, len: &'a mut usize, pred: F) -> Self {
+        let old_len = mem::replace(len, 0);
+        ExtractIf {
+            buf,
+            len,
+            idx: 0,
+            del: 0,
+            old_len,
+            pred,
+        }
+    }
+}
+
+impl<T, F> Iterator for ExtractIf<'_, T, F>
+where
+    F: FnMut(&mut T) -> bool,
+{
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        while self.idx < self.old_len {
+            unsafe {
+                let cur = self.buf.as_ptr().add(self.idx);
+                // If this panics `idx` still points at `cur`, so Drop keeps it
+                let extract = (self.pred)(&mut *cur);
+                self.idx += 1;
+                if extract {
+                    self.del += 1;
+                    return Some(cur.read());
+                } else if self.del > 0 {
+                    ptr::copy_nonoverlapping(cur, cur.sub(self.del), 1);
+                }
+            }
+        }
+        None
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, Some(self.old_len - self.idx))
+    }
+}
+
+impl<T, F> Drop for ExtractIf<'_, T, F>
+where
+    F: FnMut(&mut T) -> bool,
+{
+    fn drop(&mut self) {
+        let rest = self.old_len - self.idx;
+        if rest > 0 && self.del > 0 {
+            unsafe {
+                let src = self.buf.as_ptr().add(self.idx);
+                ptr::copy(src, src.sub(self.del), rest);
+            }
+        }
+        *self.len = self.old_len - self.del;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_send_sync_bounds() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<Drain<'_, String>>();
+    }
+}
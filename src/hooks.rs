@@ -0,0 +1,252 @@
+// Purpose: opt-in callbacks for MyRc/MyArc lifecycle events, for hunting leaks.
+
+/*
+Cost:
+
+- Without `debug-hooks` only EventKind, PointerKind and an empty inline
+  `emit` are compiled. The call sites in my_rc.rs and my_arc.rs pass
+  values they already have, so nothing is left of them.
+- With it, every event loads the MAX_HOOKS slots and calls whatever is
+  registered. Hooks are plain `fn` pointers in an array of AtomicPtr
+  (the trick std uses for its alloc error hook): no lock, no allocation,
+  works without std, and the registry can be a `static`. That last part
+  is also why it stays on core's atomics rather than `crate::sync`.
+
+Hooks only get the block's address, never a pointer to it: a Drop event
+from a MyArc that wasn't the last can race the block being freed on
+another thread.
+*/
+
+/// What happened to a MyRc or MyArc.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum EventKind {
+    /// A new block was allocated for a value.
+    Allocate,
+    /// A strong pointer was cloned, or upgraded from a weak one.
+    Clone,
+    /// A strong pointer was dropped, or gave its value up (`try_unwrap`,
+    /// `into_inner`, `make_mut` moving it out).
+    Drop,
+    /// The block was freed, after the last strong and weak pointer.
+    Deallocate,
+}
+
+/// Which pointer type an event came from. Weak pointers don't report,
+/// their effect shows up as the `Deallocate`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum PointerKind {
+    /// `MyRc`
+    Rc,
+    /// `MyArc`
+    Arc,
+}
+
+#[cfg(feature = "debug-hooks")]
+pub use registry::*;
+
+#[cfg(not(feature = "debug-hooks"))]
+#[inline(always)]
+pub(crate) fn emit<T: ?Sized>(_: EventKind, _: PointerKind, _: usize, _: usize) {}
+
+#[cfg(feature = "debug-hooks")]
+mod registry {
+    use core::{
+        any, fmt, mem,
+        ptr::null_mut,
+        sync::atomic::{AtomicPtr, Ordering},
+    };
+
+    use super::{EventKind, PointerKind};
+
+    /// One event, as passed to every registered [`Hook`].
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    #[non_exhaustive]
+    pub struct Event {
+        pub kind: EventKind,
+        pub pointer: PointerKind,
+        /// Address of the shared block. The same for every event of one
+        /// value, until it's deallocated and the address reused.
+        pub addr: usize,
+        /// Strong count right after the event, 0 once the value is gone.
+        pub strong_count: usize,
+        /// `type_name` of the value, e.g. `alloc::string::String`.
+        pub type_name: &'static str,
+    }
+
+    /// A callback for every MyRc and MyArc event in the process, on the
+    /// thread it happened on. It mustn't create or drop a MyRc or MyArc
+    /// itself (that's another event), and shouldn't panic: it may be
+    /// running inside a `drop`.
+    pub type Hook = fn(&Event);
+
+    /// How many hooks can be registered at once.
+    pub const MAX_HOOKS: usize = 8;
+
+    static HOOKS: [AtomicPtr<()>; MAX_HOOKS] = [const { AtomicPtr::new(null_mut()) }; MAX_HOOKS];
+
+    /// Returned by [`register`]. Not `Clone`, so a hook can only be
+    /// unregistered once and never takes someone else's slot with it.
+    #[derive(Debug, PartialEq, Eq)]
+    pub struct HookId(usize);
+
+    /// All [`MAX_HOOKS`] slots are taken.
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    pub struct RegistryFull;
+
+    impl fmt::Display for RegistryFull {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(f, "all {MAX_HOOKS} debug hook slots are taken")
+        }
+    }
+
+    impl core::error::Error for RegistryFull {}
+
+    /// Starts calling `hook` for every event.
+    ///
+    /// ```
+    /// use core::sync::atomic::{AtomicUsize, Ordering};
+    /// use my_std::hooks::{self, Event, EventKind};
+    /// use my_std::my_rc::MyRc;
+    ///
+    /// static FREED: AtomicUsize = AtomicUsize::new(0);
+    ///
+    /// fn count_frees(event: &Event) {
+    ///     if event.kind == EventKind::Deallocate {
+    ///         FREED.fetch_add(1, Ordering::Relaxed);
+    ///     }
+    /// }
+    ///
+    /// let id = hooks::register(count_frees).unwrap();
+    /// drop(MyRc::new(1));
+    /// hooks::unregister(id);
+    /// assert_eq!(FREED.load(Ordering::Relaxed), 1);
+    /// ```
+    pub fn register(hook: Hook) -> Result<HookId, RegistryFull> {
+        HOOKS
+            .iter()
+            .position(|slot| {
+                slot.compare_exchange(
+                    null_mut(),
+                    hook as *mut (),
+                    Ordering::Release,
+                    Ordering::Relaxed,
+                )
+                .is_ok()
+            })
+            .map(HookId)
+            .ok_or(RegistryFull)
+    }
+
+    /// Stops calling the hook. An event already under way on another
+    /// thread may still call it once more.
+    pub fn unregister(id: HookId) {
+        HOOKS[id.0].store(null_mut(), Ordering::Release);
+    }
+
+    pub(crate) fn emit<T: ?Sized>(
+        kind: EventKind,
+        pointer: PointerKind,
+        addr: usize,
+        strong_count: usize,
+    ) {
+        let event = Event {
+            kind,
+            pointer,
+            addr,
+            strong_count,
+            type_name: any::type_name::<T>(),
+        };
+        for slot in &HOOKS {
+            let hook = slot.load(Ordering::Acquire);
+            if !hook.is_null() {
+                // Only ever stored from a `Hook` in `register`
+                let hook = unsafe { mem::transmute::<*mut (), Hook>(hook) };
+                hook(&event);
+            }
+        }
+    }
+}
+
+#[cfg(all(test, feature = "debug-hooks"))]
+mod tests {
+    use std::{cell::RefCell, sync::Mutex, thread_local, vec::Vec};
+
+    use super::*;
+    use crate::{my_arc::MyArc, my_rc::MyRc};
+
+    // The registry is global, the tests touching it take turns
+    static SERIAL: Mutex<()> = Mutex::new(());
+
+    thread_local! {
+        // Other tests' pointers report on their own threads, so only ours end up here
+        static SEEN: RefCell<Vec<(EventKind, PointerKind, usize)>> = const { RefCell::new(Vec::new()) };
+    }
+
+    fn record(event: &Event) {
+        SEEN.with_borrow_mut(|seen| seen.push((event.kind, event.pointer, event.strong_count)));
+    }
+
+    fn take_seen() -> Vec<(EventKind, PointerKind, usize)> {
+        SEEN.take()
+    }
+
+    #[test]
+    fn test_rc_and_arc_lifecycles() {
+        use EventKind::*;
+        use PointerKind as P;
+
+        let _serial = SERIAL.lock().unwrap();
+        let id = register(record).unwrap();
+        take_seen();
+
+        let rc = MyRc::new(1);
+        let weak = rc.downgrade();
+        let upgraded = weak.upgrade().unwrap();
+        drop((rc, upgraded));
+        drop(weak);
+        let rc_events = take_seen();
+
+        let arc = MyArc::new(2);
+        let shared = MyArc::try_unwrap(arc.clone()).unwrap_err();
+        drop(shared);
+        assert_eq!(MyArc::try_unwrap(arc).ok(), Some(2));
+        let arc_events = take_seen();
+
+        unregister(id);
+        assert_eq!(
+            rc_events,
+            [
+                (Allocate, P::Rc, 1),
+                (Clone, P::Rc, 2),
+                (Drop, P::Rc, 1),
+                (Drop, P::Rc, 0),
+                (Deallocate, P::Rc, 0),
+            ]
+        );
+        assert_eq!(
+            arc_events,
+            [
+                (Allocate, P::Arc, 1),
+                (Clone, P::Arc, 2),
+                (Drop, P::Arc, 1),
+                (Drop, P::Arc, 0),
+                (Deallocate, P::Arc, 0),
+            ]
+        );
+        drop(MyRc::new(3));
+        assert!(take_seen().is_empty());
+    }
+
+    #[test]
+    fn test_registry_fills_up() {
+        fn ignore(_: &Event) {}
+
+        let _serial = SERIAL.lock().unwrap();
+        let ids: Vec<_> = (0..MAX_HOOKS).map(|_| register(ignore).unwrap()).collect();
+        assert_eq!(register(ignore), Err(RegistryFull));
+        let mut ids = ids.into_iter();
+        unregister(ids.next().unwrap());
+        let again = register(ignore).unwrap();
+        ids.chain([again]).for_each(unregister);
+    }
+}
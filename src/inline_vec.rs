@@ -0,0 +1,1023 @@
+// Purpose: A vector that keeps its first N elements inline and only moves
+// to the heap once it outgrows them.
+
+use alloc::vec::Vec;
+use core::{
+    borrow::{Borrow, BorrowMut},
+    cmp::Ordering,
+    fmt,
+    hash::{Hash, Hasher},
+    iter::FusedIterator,
+    mem::{ManuallyDrop, MaybeUninit},
+    ops::{Deref, DerefMut, Index, IndexMut, RangeBounds},
+    ptr::{self, NonNull},
+    slice::{self, SliceIndex},
+};
+
+use crate::{
+    allocator::{Allocator, Global},
+    drain::{self, Drain, ExtractIf, Splice},
+    my_vec::{MutMyVecIter, MyVecIter},
+    raw_vec::{self, RawVec, TryReserveError},
+    sort,
+};
+
+/*
+InlineVec<T, N>:
+
+- Same API as MyVec, but the first N elements live in an array inside
+  the struct, so small vectors never touch the allocator.
+- Pushing past N "spills": the elements move to a RawVec and stay there
+  while the vector grows.
+- `heap` is an empty RawVec (capacity 0, nothing allocated) until then,
+  which is also how `spilled()` tells the two states apart.
+- `shrink_to_fit` / `shrink_to` move the elements back inline and free
+  the heap buffer once they fit in N again.
+- Zero-sized T never spills: the capacity is usize::MAX from the start.
+*/
+
+pub struct InlineVec<T, const N: usize, A: Allocator = Global> {
+    inline: [MaybeUninit<T>; N],
+    heap: RawVec<T, A>,
+    len: usize,
+}
+
+impl<T, const N: usize> InlineVec<T, N> {
+    pub const fn new() -> Self {
+        Self::new_in(Global)
+    }
+
+    /// Creates an empty vector with room for at least `capacity` elements.
+    /// Only allocates if `capacity > N`.
+    #[track_caller]
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self::with_capacity_in(capacity, Global)
+    }
+
+    #[track_caller]
+    pub fn try_with_capacity(capacity: usize) -> Result<Self, TryReserveError> {
+        Self::try_with_capacity_in(capacity, Global)
+    }
+}
+
+impl<T, const N: usize, A: Allocator> InlineVec<T, N, A> {
+    /// Creates an empty vector that will spill into `alloc`.
+    pub const fn new_in(alloc: A) -> Self {
+        InlineVec {
+            inline: [const { MaybeUninit::uninit() }; N],
+            heap: RawVec::new_in(alloc),
+            len: 0,
+        }
+    }
+
+    #[track_caller]
+    pub fn with_capacity_in(capacity: usize, alloc: A) -> Self {
+        let mut vec = Self::new_in(alloc);
+        vec.reserve_exact(capacity);
+        vec
+    }
+
+    #[track_caller]
+    pub fn try_with_capacity_in(capacity: usize, alloc: A) -> Result<Self, TryReserveError> {
+        let mut vec = Self::new_in(alloc);
+        vec.try_reserve_exact(capacity)?;
+        Ok(vec)
+    }
+
+    /// The allocator the vector spills into.
+    pub fn allocator(&self) -> &A {
+        &self.heap.alloc
+    }
+
+    /// Whether the elements have moved to the heap.
+    pub fn spilled(&self) -> bool {
+        !RawVec::<T, A>::IS_ZST && self.heap.cap != 0
+    }
+
+    /// How many elements fit without spilling.
+    pub const fn inline_size(&self) -> usize {
+        N
+    }
+
+    #[track_caller]
+    pub fn push(&mut self, value: T) {
+        if self.len == self.get_capacity() {
+            self.reserve(1);
+        }
+
+        unsafe { self.as_mut_ptr().add(self.len).write(value) };
+        self.len += 1;
+    }
+
+    /// Pushes `value`, returning an error instead of aborting if the
+    /// vector has to spill or grow and the allocation fails.
+    #[track_caller]
+    pub fn try_push(&mut self, value: T) -> Result<(), TryReserveError> {
+        if self.len == self.get_capacity() {
+            self.try_reserve(1)?;
+        }
+
+        unsafe { self.as_mut_ptr().add(self.len).write(value) };
+        self.len += 1;
+        Ok(())
+    }
+
+    /// Reserves room for at least `additional` more elements. Spilling
+    /// allocates at least twice the inline size.
+    #[track_caller]
+    pub fn reserve(&mut self, additional: usize) {
+        raw_vec::handle_error(self.try_reserve(additional));
+    }
+
+    #[track_caller]
+    pub fn reserve_exact(&mut self, additional: usize) {
+        raw_vec::handle_error(self.try_reserve_exact(additional));
+    }
+
+    #[track_caller]
+    pub fn try_reserve(&mut self, additional: usize) -> Result<(), TryReserveError> {
+        if self.spilled() {
+            return self.heap.try_reserve(self.len, additional);
+        }
+        self.try_spill(additional, |required| required.max(N.saturating_mul(2)))
+    }
+
+    #[track_caller]
+    pub fn try_reserve_exact(&mut self, additional: usize) -> Result<(), TryReserveError> {
+        if self.spilled() {
+            return self.heap.try_reserve_exact(self.len, additional);
+        }
+        self.try_spill(additional, |required| required)
+    }
+
+    // Moves the inline elements to a heap buffer of `cap(len + additional)`
+    // slots, unless they still fit inline.
+    #[track_caller]
+    fn try_spill(
+        &mut self,
+        additional: usize,
+        cap: impl FnOnce(usize) -> usize,
+    ) -> Result<(), TryReserveError> {
+        if additional <= self.get_capacity() - self.len {
+            return Ok(());
+        }
+        if RawVec::<T, A>::IS_ZST {
+            return Err(TryReserveError::CapacityOverflow);
+        }
+
+        let required = self
+            .len
+            .checked_add(additional)
+            .ok_or(TryReserveError::CapacityOverflow)?;
+        self.heap.try_reserve_exact(0, cap(required))?;
+        unsafe {
+            ptr::copy_nonoverlapping(self.inline.as_ptr(), self.heap.ptr.as_ptr(), self.len);
+        }
+        Ok(())
+    }
+
+    /// Gives back as much spare capacity as possible, moving the elements
+    /// back inline if they fit.
+    pub fn shrink_to_fit(&mut self) {
+        self.shrink_to(0);
+    }
+
+    /// Shrinks the capacity down to `max(min_capacity, len)`, or to the
+    /// inline array if that is enough.
+    pub fn shrink_to(&mut self, min_capacity: usize) {
+        if !self.spilled() {
+            return;
+        }
+
+        let target = min_capacity.max(self.len);
+        if target <= N {
+            unsafe {
+                ptr::copy_nonoverlapping(
+                    self.heap.ptr.as_ptr(),
+                    self.inline.as_mut_ptr(),
+                    self.len,
+                );
+            }
+            self.heap.shrink_to(0);
+        } else if self.heap.cap > target {
+            self.heap.shrink_to(target);
+        }
+    }
+
+    /// The uninitialised slots after the last element, for filling in place
+    /// before a [`set_len`](Self::set_len).
+    pub fn spare_capacity_mut(&mut self) -> &mut [MaybeUninit<T>] {
+        let spare = self.get_capacity() - self.len;
+        unsafe {
+            slice::from_raw_parts_mut(
+                self.as_mut_ptr().add(self.len).cast::<MaybeUninit<T>>(),
+                spare,
+            )
+        }
+    }
+
+    /// Sets the length without touching the elements.
+    ///
+    /// # Safety
+    ///
+    /// `new_len` must be at most the capacity, and the first `new_len`
+    /// elements must be initialised. Any elements cut off are leaked, not dropped.
+    pub unsafe fn set_len(&mut self, new_len: usize) {
+        debug_assert!(new_len <= self.get_capacity());
+        self.len = new_len;
+    }
+
+    pub fn pop(&mut self) -> Option<T> {
+        if self.len == 0 {
+            None
+        } else {
+            self.len -= 1;
+            Some(unsafe { self.as_ptr().add(self.len).read() })
+        }
+    }
+
+    pub fn get<I: SliceIndex<[T]>>(&self, index: I) -> Option<&I::Output> {
+        self.as_slice().get(index)
+    }
+
+    pub fn get_mut<I: SliceIndex<[T]>>(&mut self, index: I) -> Option<&mut I::Output> {
+        self.as_mut_slice().get_mut(index)
+    }
+
+    pub fn get_len(&self) -> usize {
+        self.len
+    }
+
+    pub fn get_capacity(&self) -> usize {
+        if RawVec::<T, A>::IS_ZST {
+            usize::MAX
+        } else if self.spilled() {
+            self.heap.cap
+        } else {
+            N
+        }
+    }
+
+    // Points at whichever buffer holds the elements right now
+    fn as_ptr(&self) -> *const T {
+        if self.spilled() {
+            self.heap.ptr.as_ptr().cast::<T>()
+        } else {
+            self.inline.as_ptr().cast::<T>()
+        }
+    }
+
+    fn as_mut_ptr(&mut self) -> *mut T {
+        if self.spilled() {
+            self.heap.ptr.as_ptr().cast::<T>()
+        } else {
+            self.inline.as_mut_ptr().cast::<T>()
+        }
+    }
+
+    fn as_non_null(&mut self) -> NonNull<T> {
+        unsafe { NonNull::new_unchecked(self.as_mut_ptr()) }
+    }
+
+    pub fn as_slice(&self) -> &[T] {
+        unsafe { slice::from_raw_parts(self.as_ptr(), self.len) }
+    }
+
+    pub fn as_mut_slice(&mut self) -> &mut [T] {
+        unsafe { slice::from_raw_parts_mut(self.as_mut_ptr(), self.len) }
+    }
+
+    #[track_caller]
+    pub fn insert(&mut self, index: usize, value: T) {
+        assert!(index <= self.len);
+        if self.len == self.get_capacity() {
+            self.reserve(1);
+        }
+
+        unsafe {
+            let at = self.as_mut_ptr().add(index);
+            ptr::copy(at, at.add(1), self.len - index);
+            at.write(value);
+        }
+        self.len += 1;
+    }
+
+    pub fn remove(&mut self, index: usize) -> T {
+        assert!(index < self.len);
+        unsafe {
+            let at = self.as_mut_ptr().add(index);
+            let value = at.read();
+            ptr::copy(at.add(1), at, self.len - index - 1);
+            self.len -= 1;
+            value
+        }
+    }
+
+    /// Removes `range` from the vector, yielding the removed elements.
+    ///
+    /// Works the same as [`MyVec::drain`](crate::my_vec::MyVec::drain) and
+    /// never spills or un-spills.
+    pub fn drain<R: RangeBounds<usize>>(&mut self, range: R) -> Drain<'_, T> {
+        let range = drain::slice_range(range, self.len);
+        let buf = self.as_non_null();
+        unsafe { Drain::new(buf, &mut self.len, range) }
+    }
+
+    /// Replaces `range` with the items of `replace_with`, yielding the
+    /// removed elements.
+    ///
+    /// Works the same as [`MyVec::splice`](crate::my_vec::MyVec::splice),
+    /// spilling to the heap if the replacements don't fit inline.
+    pub fn splice<R, I>(&mut self, range: R, replace_with: I) -> Splice<'_, I::IntoIter, A>
+    where
+        R: RangeBounds<usize>,
+        I: IntoIterator<Item = T>,
+    {
+        let range = drain::slice_range(range, self.len);
+        let buf = self.as_non_null();
+        let InlineVec { heap, len, .. } = self;
+        Splice {
+            drain: unsafe { Drain::new(buf, len, range) },
+            buf: heap,
+            inline_cap: N,
+            replace_with: replace_with.into_iter(),
+        }
+    }
+
+    /// Removes and yields every element `pred` returns `true` for, keeping
+    /// the rest in order.
+    pub fn extract_if<F>(&mut self, pred: F) -> ExtractIf<'_, T, F>
+    where
+        F: FnMut(&mut T) -> bool,
+    {
+        let buf = self.as_non_null();
+        unsafe { ExtractIf::new(buf, &mut self.len, pred) }
+    }
+
+    /// Keeps only the elements `f` returns `true` for.
+    pub fn retain<F>(&mut self, mut f: F)
+    where
+        F: FnMut(&T) -> bool,
+    {
+        self.retain_mut(|elem| f(elem));
+    }
+
+    pub fn retain_mut<F>(&mut self, mut f: F)
+    where
+        F: FnMut(&mut T) -> bool,
+    {
+        self.extract_if(|elem| !f(elem)).for_each(drop);
+    }
+
+    /// Removes consecutive elements that map to the same key.
+    pub fn dedup_by_key<F, K>(&mut self, mut key: F)
+    where
+        F: FnMut(&mut T) -> K,
+        K: PartialEq,
+    {
+        self.dedup_by(|a, b| key(a) == key(b));
+    }
+
+    /// Removes consecutive elements `same_bucket(current, kept)` calls equal.
+    pub fn dedup_by<F>(&mut self, same_bucket: F)
+    where
+        F: FnMut(&mut T, &mut T) -> bool,
+    {
+        let buf = self.as_non_null();
+        unsafe { drain::dedup_by(buf, &mut self.len, same_bucket) }
+    }
+
+    /// Drops everything past the first `len` elements. The capacity is unchanged.
+    pub fn truncate(&mut self, len: usize) {
+        let buf = self.as_non_null();
+        unsafe { drain::truncate(buf, &mut self.len, len) }
+    }
+
+    /// Splits off `[at, len)` into a new vector, inline if it fits.
+    #[track_caller]
+    pub fn split_off(&mut self, at: usize) -> Self
+    where
+        A: Clone,
+    {
+        assert!(
+            at <= self.len,
+            "`at` split index (is {at}) should be <= len (is {})",
+            self.len
+        );
+        let count = self.len - at;
+        let mut other = Self::with_capacity_in(count, self.heap.alloc.clone());
+        unsafe {
+            ptr::copy_nonoverlapping(self.as_ptr().add(at), other.as_mut_ptr(), count);
+        }
+        self.len = at;
+        other.len = count;
+        other
+    }
+
+    /// Moves every element of `other` onto the end of `self`, leaving it empty.
+    #[track_caller]
+    pub fn append(&mut self, other: &mut Self) {
+        let count = other.len;
+        self.reserve(count);
+        unsafe {
+            ptr::copy_nonoverlapping(other.as_ptr(), self.as_mut_ptr().add(self.len), count);
+        }
+        other.len = 0;
+        self.len += count;
+    }
+
+    /// Stable sort. The merge scratch buffer comes from this vector's
+    /// allocator, even while the elements are inline.
+    pub fn sort(&mut self)
+    where
+        T: Ord,
+    {
+        self.sort_by(T::cmp);
+    }
+
+    pub fn sort_by<F>(&mut self, mut compare: F)
+    where
+        F: FnMut(&T, &T) -> Ordering,
+    {
+        let v = unsafe { slice::from_raw_parts_mut(self.as_mut_ptr(), self.len) };
+        sort::merge_sort(
+            v,
+            &mut |a, b| compare(a, b) == Ordering::Less,
+            &self.heap.alloc,
+        );
+    }
+
+    pub fn sort_by_key<K, F>(&mut self, mut f: F)
+    where
+        F: FnMut(&T) -> K,
+        K: Ord,
+    {
+        self.sort_by(|a, b| f(a).cmp(&f(b)));
+    }
+
+    /// Unstable sort (pattern-defeating quicksort). Doesn't allocate.
+    pub fn sort_unstable(&mut self)
+    where
+        T: Ord,
+    {
+        self.sort_unstable_by(T::cmp);
+    }
+
+    pub fn sort_unstable_by<F>(&mut self, mut compare: F)
+    where
+        F: FnMut(&T, &T) -> Ordering,
+    {
+        sort::quicksort(self, &mut |a, b| compare(a, b) == Ordering::Less);
+    }
+
+    pub fn sort_unstable_by_key<K, F>(&mut self, mut f: F)
+    where
+        F: FnMut(&T) -> K,
+        K: Ord,
+    {
+        self.sort_unstable_by(|a, b| f(a).cmp(&f(b)));
+    }
+
+    /// Moves the element that belongs at `index` in sorted order there,
+    /// returning the elements before and after it.
+    ///
+    /// # Panics
+    ///
+    /// If `index >= len`.
+    pub fn select_nth_unstable(&mut self, index: usize) -> (&mut [T], &mut T, &mut [T])
+    where
+        T: Ord,
+    {
+        self.select_nth_unstable_by(index, T::cmp)
+    }
+
+    pub fn select_nth_unstable_by<F>(
+        &mut self,
+        index: usize,
+        mut compare: F,
+    ) -> (&mut [T], &mut T, &mut [T])
+    where
+        F: FnMut(&T, &T) -> Ordering,
+    {
+        sort::select_nth_unstable(self, index, &mut |a, b| compare(a, b) == Ordering::Less)
+    }
+
+    pub fn select_nth_unstable_by_key<K, F>(
+        &mut self,
+        index: usize,
+        mut f: F,
+    ) -> (&mut [T], &mut T, &mut [T])
+    where
+        F: FnMut(&T) -> K,
+        K: Ord,
+    {
+        self.select_nth_unstable_by(index, |a, b| f(a).cmp(&f(b)))
+    }
+}
+
+impl<T, const N: usize> Default for InlineVec<T, N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T, const N: usize, A: Allocator> Drop for InlineVec<T, N, A> {
+    fn drop(&mut self) {
+        unsafe { ptr::drop_in_place(self.as_mut_slice()) }
+        // RawVec frees the heap buffer, if there is one
+    }
+}
+
+impl<T, const N: usize, A> Clone for InlineVec<T, N, A>
+where
+    T: Clone,
+    A: Allocator + Clone,
+{
+    #[track_caller]
+    fn clone(&self) -> Self {
+        let mut new = Self::with_capacity_in(self.len, self.heap.alloc.clone());
+        new.extend(self.iter().cloned());
+        new
+    }
+}
+
+impl<T: fmt::Debug, const N: usize, A: Allocator> fmt::Debug for InlineVec<T, N, A> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self.as_slice(), f)
+    }
+}
+
+impl<T, const N: usize, A: Allocator> Deref for InlineVec<T, N, A> {
+    type Target = [T];
+
+    fn deref(&self) -> &[T] {
+        self.as_slice()
+    }
+}
+
+impl<T, const N: usize, A: Allocator> DerefMut for InlineVec<T, N, A> {
+    fn deref_mut(&mut self) -> &mut [T] {
+        self.as_mut_slice()
+    }
+}
+
+impl<T, I: SliceIndex<[T]>, const N: usize, A: Allocator> Index<I> for InlineVec<T, N, A> {
+    type Output = I::Output;
+
+    fn index(&self, index: I) -> &Self::Output {
+        Index::index(self.as_slice(), index)
+    }
+}
+
+impl<T, I: SliceIndex<[T]>, const N: usize, A: Allocator> IndexMut<I> for InlineVec<T, N, A> {
+    fn index_mut(&mut self, index: I) -> &mut Self::Output {
+        IndexMut::index_mut(self.as_mut_slice(), index)
+    }
+}
+
+impl<T, const N: usize, A: Allocator> AsRef<[T]> for InlineVec<T, N, A> {
+    fn as_ref(&self) -> &[T] {
+        self
+    }
+}
+
+impl<T, const N: usize, A: Allocator> AsMut<[T]> for InlineVec<T, N, A> {
+    fn as_mut(&mut self) -> &mut [T] {
+        self
+    }
+}
+
+impl<T, const N: usize, A: Allocator> Borrow<[T]> for InlineVec<T, N, A> {
+    fn borrow(&self) -> &[T] {
+        self
+    }
+}
+
+impl<T, const N: usize, A: Allocator> BorrowMut<[T]> for InlineVec<T, N, A> {
+    fn borrow_mut(&mut self) -> &mut [T] {
+        self
+    }
+}
+
+// Equal when the elements are, wherever they happen to be stored.
+macro_rules! impl_slice_eq {
+    ([$($vars:tt)*] $lhs:ty, $rhs:ty) => {
+        impl<T, U, $($vars)*> PartialEq<$rhs> for $lhs
+        where
+            T: PartialEq<U>,
+        {
+            fn eq(&self, other: &$rhs) -> bool {
+                self[..] == other[..]
+            }
+        }
+    };
+}
+
+impl_slice_eq! { [const N1: usize, const N2: usize, A1: Allocator, A2: Allocator] InlineVec<T, N1, A1>, InlineVec<U, N2, A2> }
+impl_slice_eq! { [const N: usize, A: Allocator] InlineVec<T, N, A>, [U] }
+impl_slice_eq! { [const N: usize, A: Allocator] InlineVec<T, N, A>, &[U] }
+impl_slice_eq! { [const N: usize, A: Allocator, const M: usize] InlineVec<T, N, A>, [U; M] }
+impl_slice_eq! { [const N: usize, A: Allocator, const M: usize] InlineVec<T, N, A>, &[U; M] }
+impl_slice_eq! { [const N: usize, A: Allocator] InlineVec<T, N, A>, Vec<U> }
+
+impl<T: Eq, const N: usize, A: Allocator> Eq for InlineVec<T, N, A> {}
+
+impl<T: PartialOrd, const N: usize, A: Allocator> PartialOrd for InlineVec<T, N, A> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        PartialOrd::partial_cmp(&**self, &**other)
+    }
+}
+
+impl<T: Ord, const N: usize, A: Allocator> Ord for InlineVec<T, N, A> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        Ord::cmp(&**self, &**other)
+    }
+}
+
+impl<T: Hash, const N: usize, A: Allocator> Hash for InlineVec<T, N, A> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        Hash::hash(&**self, state)
+    }
+}
+
+impl<T, const N: usize, A: Allocator> Extend<T> for InlineVec<T, N, A> {
+    #[track_caller]
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        let iter = iter.into_iter();
+        let (lower, _) = iter.size_hint();
+        self.reserve(lower);
+        for item in iter {
+            self.push(item);
+        }
+    }
+}
+
+impl<'a, T: Copy + 'a, const N: usize, A: Allocator> Extend<&'a T> for InlineVec<T, N, A> {
+    #[track_caller]
+    fn extend<I: IntoIterator<Item = &'a T>>(&mut self, iter: I) {
+        self.extend(iter.into_iter().copied());
+    }
+}
+
+impl<T, const N: usize> FromIterator<T> for InlineVec<T, N> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut vec = InlineVec::new();
+        vec.extend(iter);
+        vec
+    }
+}
+
+impl<T, const N: usize, const M: usize> From<[T; M]> for InlineVec<T, N> {
+    fn from(array: [T; M]) -> Self {
+        let array = ManuallyDrop::new(array);
+        let mut new = InlineVec::with_capacity(M);
+        unsafe {
+            ptr::copy_nonoverlapping(array.as_ptr(), new.as_mut_ptr(), M);
+        }
+        new.len = M;
+        new
+    }
+}
+
+impl<T: Clone, const N: usize> From<&[T]> for InlineVec<T, N> {
+    fn from(slice: &[T]) -> Self {
+        let mut new = InlineVec::with_capacity(slice.len());
+        new.extend(slice.iter().cloned());
+        new
+    }
+}
+
+impl<T, const N: usize> From<Vec<T>> for InlineVec<T, N> {
+    fn from(vec: Vec<T>) -> Self {
+        vec.into_iter().collect()
+    }
+}
+
+impl<'a, T, const N: usize, A: Allocator> IntoIterator for &'a InlineVec<T, N, A> {
+    type Item = &'a T;
+    type IntoIter = MyVecIter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        MyVecIter::new(self)
+    }
+}
+
+impl<'a, T, const N: usize, A: Allocator> IntoIterator for &'a mut InlineVec<T, N, A> {
+    type Item = &'a mut T;
+    type IntoIter = MutMyVecIter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        MutMyVecIter::new(self)
+    }
+}
+
+/// Owning iterator over an [`InlineVec`].
+///
+/// Holds the vector itself (with its length zeroed) so inline elements
+/// move along with the iterator; yields `[index, end)`.
+pub struct IntoIter<T, const N: usize, A: Allocator = Global> {
+    vec: InlineVec<T, N, A>,
+    index: usize,
+    end: usize,
+}
+
+impl<T, const N: usize, A: Allocator> IntoIter<T, N, A> {
+    /// The elements that haven't been yielded yet.
+    pub fn as_slice(&self) -> &[T] {
+        unsafe { slice::from_raw_parts(self.vec.as_ptr().add(self.index), self.end - self.index) }
+    }
+
+    pub fn as_mut_slice(&mut self) -> &mut [T] {
+        unsafe {
+            slice::from_raw_parts_mut(self.vec.as_mut_ptr().add(self.index), self.end - self.index)
+        }
+    }
+}
+
+impl<T, const N: usize, A: Allocator> Iterator for IntoIter<T, N, A> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        if self.index == self.end {
+            None
+        } else {
+            let item = unsafe { self.vec.as_ptr().add(self.index).read() };
+            self.index += 1;
+            Some(item)
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.end - self.index;
+        (remaining, Some(remaining))
+    }
+
+    fn count(self) -> usize {
+        self.end - self.index
+    }
+}
+
+impl<T, const N: usize, A: Allocator> DoubleEndedIterator for IntoIter<T, N, A> {
+    fn next_back(&mut self) -> Option<T> {
+        if self.index == self.end {
+            None
+        } else {
+            self.end -= 1;
+            Some(unsafe { self.vec.as_ptr().add(self.end).read() })
+        }
+    }
+}
+
+impl<T, const N: usize, A: Allocator> ExactSizeIterator for IntoIter<T, N, A> {}
+
+impl<T, const N: usize, A: Allocator> FusedIterator for IntoIter<T, N, A> {}
+
+impl<T, const N: usize, A: Allocator> Drop for IntoIter<T, N, A> {
+    fn drop(&mut self) {
+        // The vector's own Drop sees len == 0 and only frees the heap buffer
+        unsafe { ptr::drop_in_place(self.as_mut_slice()) }
+    }
+}
+
+impl<T, const N: usize, A: Allocator> IntoIterator for InlineVec<T, N, A> {
+    type Item = T;
+    type IntoIter = IntoIter<T, N, A>;
+
+    fn into_iter(mut self) -> Self::IntoIter {
+        let end = self.len;
+        self.len = 0;
+        IntoIter {
+            vec: self,
+            index: 0,
+            end,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+
+    use super::*;
+    use crate::tracking::Tracking;
+
+    #[test]
+    fn test_stays_inline_up_to_n() {
+        let tracker = Tracking::new();
+        let mut vec: InlineVec<u32, 4, _> = InlineVec::new_in(&tracker);
+        for i in 0..4 {
+            vec.push(i);
+        }
+        assert!(!vec.spilled());
+        assert_eq!(vec.get_capacity(), 4);
+        assert_eq!(vec, [0, 1, 2, 3]);
+        assert_eq!(tracker.allocation_count(), 0);
+
+        vec.push(4);
+        assert!(vec.spilled());
+        assert_eq!(vec.get_capacity(), 8);
+        assert_eq!(vec, [0, 1, 2, 3, 4]);
+        assert_eq!(tracker.allocation_count(), 1);
+
+        drop(vec);
+        assert_eq!(tracker.live_bytes(), 0);
+    }
+
+    #[test]
+    fn test_shrink_moves_back_inline() {
+        let tracker = Tracking::new();
+        let mut vec: InlineVec<String, 2, _> = InlineVec::new_in(&tracker);
+        for i in 0..10 {
+            vec.push(i.to_string());
+        }
+        vec.truncate(6);
+        vec.shrink_to(3);
+        assert!(vec.spilled());
+        assert_eq!(vec.get_capacity(), 6);
+
+        vec.truncate(2);
+        vec.shrink_to_fit();
+        assert!(!vec.spilled());
+        assert_eq!(vec.get_capacity(), 2);
+        assert_eq!(vec, ["0", "1"]);
+        assert_eq!(tracker.live_bytes(), 0);
+
+        // And can spill again afterwards
+        vec.push(String::from("2"));
+        assert!(vec.spilled());
+        assert_eq!(vec, ["0", "1", "2"]);
+    }
+
+    #[test]
+    fn test_insert_remove_across_the_spill() {
+        let mut vec: InlineVec<i32, 3> = InlineVec::new();
+        vec.push(1);
+        vec.push(3);
+        vec.insert(1, 2);
+        assert!(!vec.spilled());
+        vec.insert(0, 0);
+        assert!(vec.spilled());
+        assert_eq!(vec, [0, 1, 2, 3]);
+
+        assert_eq!(vec.remove(1), 1);
+        assert_eq!(vec.pop(), Some(3));
+        assert_eq!(vec, [0, 2]);
+    }
+
+    #[test]
+    fn test_reserve_exact_spills_to_exact_size() {
+        let mut vec: InlineVec<u8, 4> = InlineVec::with_capacity(3);
+        assert!(!vec.spilled());
+        vec.reserve_exact(10);
+        assert_eq!(vec.get_capacity(), 10);
+
+        let vec: InlineVec<u8, 4> = InlineVec::with_capacity(5);
+        assert!(vec.spilled());
+        assert_eq!(vec.get_capacity(), 5);
+
+        let mut vec: InlineVec<u64, 4> = InlineVec::new();
+        assert_eq!(
+            vec.try_reserve(usize::MAX),
+            Err(TryReserveError::CapacityOverflow)
+        );
+        assert!(!vec.spilled());
+    }
+
+    #[test]
+    fn test_drain_and_retain_inline_and_spilled() {
+        for n in [2, 8] {
+            let mut vec: InlineVec<i32, 8> = (0..n * 2).collect();
+            assert_eq!(vec.spilled(), n * 2 > 8);
+            let drained: Vec<_> = vec.drain(1..3).collect();
+            assert_eq!(drained, [1, 2]);
+            vec.retain(|x| x % 2 == 0);
+            vec.dedup_by_key(|x| *x / 4);
+            assert_eq!(vec[0], 0);
+        }
+    }
+
+    #[test]
+    fn test_splice_spills_when_the_replacement_outgrows_n() {
+        let mut vec: InlineVec<String, 4> = ["a", "b", "c"].iter().map(|s| s.to_string()).collect();
+
+        // Still fits inline
+        let removed: Vec<_> = vec
+            .splice(1..2, ["x".to_string(), "y".to_string()])
+            .collect();
+        assert_eq!(removed, ["b"]);
+        assert!(!vec.spilled());
+        assert_eq!(vec.as_slice(), ["a", "x", "y", "c"]);
+
+        // Exact size hint, spilling while moving the tail
+        vec.splice(1..1, ["1", "2"].map(String::from));
+        assert!(vec.spilled());
+        assert_eq!(vec.as_slice(), ["a", "1", "2", "x", "y", "c"]);
+
+        // Back inline, then spilling without a size hint
+        vec.truncate(2);
+        vec.shrink_to_fit();
+        assert!(!vec.spilled());
+        vec.splice(1.., (0..9).filter(|i| i % 2 == 0).map(|i| i.to_string()));
+        assert_eq!(vec.as_slice(), ["a", "0", "2", "4", "6", "8"]);
+
+        // Appending past N with nothing after the range
+        let mut vec: InlineVec<i32, 2> = InlineVec::new();
+        vec.push(1);
+        vec.splice(1.., 2..6);
+        assert!(vec.spilled());
+        assert_eq!(vec.as_slice(), [1, 2, 3, 4, 5]);
+
+        let mut zst: InlineVec<(), 2> = InlineVec::new();
+        zst.splice(.., [(); 10]);
+        assert_eq!(zst.get_len(), 10);
+    }
+
+    #[test]
+    fn test_split_off_and_append() {
+        let mut vec: InlineVec<i32, 4> = (0..6).collect();
+        let mut tail = vec.split_off(2);
+        assert_eq!(vec, [0, 1]);
+        assert!(!tail.spilled());
+        assert_eq!(tail, [2, 3, 4, 5]);
+
+        vec.append(&mut tail);
+        assert!(tail.is_empty());
+        assert!(vec.spilled());
+        assert_eq!(vec, [0, 1, 2, 3, 4, 5]);
+    }
+
+    #[test]
+    fn test_into_iter_from_both_storages() {
+        let inline: InlineVec<String, 4> = ["a", "b"].iter().map(|s| s.to_string()).collect();
+        let spilled: InlineVec<String, 1> = ["a", "b"].iter().map(|s| s.to_string()).collect();
+
+        let mut iter = inline.into_iter();
+        assert_eq!(iter.next_back().as_deref(), Some("b"));
+        assert_eq!(iter.as_slice(), ["a"]);
+        let mut iter = spilled.into_iter();
+        assert_eq!(iter.next().as_deref(), Some("a"));
+        assert_eq!(iter.len(), 1);
+    }
+
+    #[test]
+    fn test_get_range() {
+        let mut vec: InlineVec<i32, 2> = (1..=4).collect();
+        assert_eq!(vec.get(1..3), Some(&[2, 3][..]));
+        assert_eq!(vec.get(4..), Some(&[][..]));
+        assert_eq!(vec.get(5..), None);
+        vec.get_mut(..).unwrap().reverse();
+        assert_eq!(vec.as_slice(), [4, 3, 2, 1]);
+    }
+
+    #[test]
+    fn test_drops_every_element_once() {
+        struct Counted<'a>(&'a Cell<usize>);
+        impl Drop for Counted<'_> {
+            fn drop(&mut self) {
+                self.0.set(self.0.get() + 1);
+            }
+        }
+
+        let drops = Cell::new(0);
+        let mut vec: InlineVec<Counted<'_>, 2> = InlineVec::new();
+        for _ in 0..5 {
+            vec.push(Counted(&drops));
+        }
+        vec.truncate(2);
+        vec.shrink_to_fit();
+        assert_eq!(drops.get(), 3);
+
+        let mut iter = vec.into_iter();
+        drop(iter.next());
+        drop(iter);
+        assert_eq!(drops.get(), 5);
+    }
+
+    #[test]
+    fn test_zst_never_spills() {
+        let mut vec: InlineVec<(), 2> = InlineVec::new();
+        for _ in 0..100 {
+            vec.push(());
+        }
+        assert!(!vec.spilled());
+        assert_eq!(vec.get_capacity(), usize::MAX);
+        assert_eq!(vec.into_iter().count(), 100);
+    }
+
+    #[test]
+    fn test_zero_inline_size_acts_like_my_vec() {
+        let mut vec: InlineVec<i32, 0> = InlineVec::new();
+        assert_eq!(vec.get_capacity(), 0);
+        vec.push(1);
+        assert!(vec.spilled());
+        vec.pop();
+        vec.shrink_to_fit();
+        assert!(!vec.spilled());
+    }
+
+    #[test]
+    fn test_send_sync_bounds() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<InlineVec<String, 4>>();
+    }
+}
@@ -1,5 +1,74 @@
-mod my_arc;
-mod my_rc;
-mod my_vec;
-mod my_deque;
-mod my_linked_list;
\ No newline at end of file
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
+
+//! Without the default `std` feature the crate is `#![no_std]`. With
+//! `default-features = false, features = ["alloc"]` it only needs `alloc`,
+//! and with no features at all only `array_vec` is left, which never
+//! allocates and needs no global allocator.
+
+#[cfg(feature = "alloc")]
+extern crate alloc;
+
+#[cfg(feature = "alloc")]
+pub mod allocator;
+#[cfg(feature = "alloc")]
+pub mod arena;
+pub mod array_vec;
+#[cfg(feature = "alloc")]
+pub mod bump;
+#[cfg(feature = "alloc")]
+pub mod codec;
+mod drain;
+#[cfg(feature = "debug-hooks")]
+pub mod hooks;
+#[cfg(all(feature = "alloc", not(feature = "debug-hooks")))]
+mod hooks;
+#[cfg(feature = "alloc")]
+pub mod inline_vec;
+#[cfg(feature = "alloc")]
+pub mod my_arc;
+#[cfg(feature = "alloc")]
+pub mod my_deque;
+#[cfg(feature = "alloc")]
+pub mod my_linked_list;
+#[cfg(feature = "alloc")]
+pub mod my_rc;
+#[cfg(feature = "alloc")]
+pub mod my_vec;
+#[cfg(feature = "alloc")]
+mod raw_vec;
+#[cfg(feature = "serde")]
+mod serde_impls;
+#[cfg(feature = "alloc")]
+mod sort;
+#[cfg(feature = "alloc")]
+mod sync;
+#[cfg(feature = "alloc")]
+pub mod tracking;
+
+#[cfg(feature = "alloc")]
+pub use raw_vec::TryReserveError;
+
+/// Glob-importable re-exports of every collection and smart pointer.
+///
+/// ```
+/// # #[cfg(feature = "alloc")] {
+/// use my_std::prelude::*;
+///
+/// let mut vec = MyVec::new();
+/// vec.push(1);
+/// assert_eq!(vec.get(0), Some(&1));
+/// # }
+/// ```
+pub mod prelude {
+    pub use crate::array_vec::{ArrayVec, CapacityError};
+    #[cfg(feature = "alloc")]
+    pub use crate::{
+        TryReserveError,
+        inline_vec::InlineVec,
+        my_arc::{MyArc, MyWeak},
+        my_deque::MyDeque,
+        my_linked_list::LinkedList,
+        my_rc::{MyRc, MyRcWeak},
+        my_vec::MyVec,
+    };
+}
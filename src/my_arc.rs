@@ -1,48 +1,176 @@
-use std::{
+use alloc::{
+    alloc::{dealloc, handle_alloc_error},
+    boxed::Box,
+    vec::Vec,
+};
+use core::{
+    alloc::Layout,
+    mem::{self, ManuallyDrop},
     ops::Deref,
-    ptr::NonNull,
-    rc::Weak,
-    sync::atomic::{AtomicUsize, Ordering, fence},
+    ptr::{self, NonNull},
 };
 
-pub struct InnerArc<T> {
-    value: T,
+use crate::{
+    allocator::{Allocator, Global},
+    hooks::{self, EventKind, PointerKind},
+    my_rc::with_addr_of,
+    sync::{AtomicUsize, Ordering, fence, hint},
+};
+
+/*
+Teardown:
+
+- `strong` counts MyArc, `weak` counts MyWeak plus one shared by all the
+  MyArc. The last MyArc drops the value in place and then releases that
+  implicit weak; whoever takes `weak` to 0 frees the block. Only counts
+  are touched after the value is gone.
+- Every decrement is Release and the one that reaches 0 is followed by an
+  Acquire fence, so everything other threads did through their pointers
+  happens before the value is dropped or the block freed.
+- `try_unwrap` and `make_mut` claim the value with a 1 -> 0 CAS on
+  `strong` rather than a load, so a racing `upgrade` (which never
+  increments from 0) either lands before and makes them fail, or fails.
+- `get_mut_ref` briefly locks `weak` at usize::MAX so that no MyWeak can
+  appear between checking it and checking `strong`; `downgrade` waits
+  the lock out.
+
+Overflow:
+
+- Neither count may pass MAX_REFCOUNT (isize::MAX). Leaking clones with
+  `mem::forget` could otherwise wrap one to 0 and free the block under
+  the pointers still using it.
+- `clone` increments first and checks after, like std, so it can't back
+  out and aborts instead. It takes isize::MAX leaked clones to get there,
+  and as many threads cloning at once to get from there to usize::MAX.
+- Everything else goes through a CAS loop anyway and panics before
+  touching the count. For `weak` this also keeps it clear of WEAK_LOCKED.
+*/
+
+// repr(C) with the value last so it can be unsized, see my_rc.rs
+#[repr(C)]
+pub struct InnerArc<T: ?Sized> {
     strong: AtomicUsize,
     weak: AtomicUsize,
+    value: T,
 }
 
 impl<T> InnerArc<T> {
     fn new(value: T) -> Self {
         InnerArc {
-            value,
             strong: AtomicUsize::new(1),
             weak: AtomicUsize::new(1), // start at 1 because Arc holds a weak ref to itself internally
+            value,
+        }
+    }
+}
+
+// Value of `weak` while `get_mut_ref` has it locked
+const WEAK_LOCKED: usize = usize::MAX;
+
+// Largest either count is allowed to reach
+const MAX_REFCOUNT: usize = isize::MAX as usize;
+
+#[cold]
+fn refcount_overflow() -> ! {
+    #[cfg(feature = "std")]
+    std::process::abort();
+    // No `abort` in core, but panicking while a panic unwinds aborts too
+    #[cfg(not(feature = "std"))]
+    {
+        struct Abort;
+        impl Drop for Abort {
+            fn drop(&mut self) {
+                panic!("MyArc count overflow");
+            }
         }
+        let _abort = Abort;
+        panic!("MyArc count overflow");
     }
 }
 
-pub struct MyArc<T> {
+// Tells the debug hooks, if there are any. Only the address goes out: if
+// this wasn't the last MyArc the block may be freed on another thread.
+fn emit<T: ?Sized>(kind: EventKind, ptr: NonNull<InnerArc<T>>, strong_count: usize) {
+    hooks::emit::<T>(kind, PointerKind::Arc, ptr.as_ptr().addr(), strong_count);
+}
+
+// Adds one MyWeak, waiting out `get_mut_ref` if it has the count locked
+fn acquire_weak(weak: &AtomicUsize) {
+    let mut count = weak.load(Ordering::Relaxed);
+    loop {
+        if count == WEAK_LOCKED {
+            hint::spin_loop();
+            count = weak.load(Ordering::Relaxed);
+            continue;
+        }
+        assert!(count < MAX_REFCOUNT, "MyWeak count overflow");
+        // Acquire pairs with `get_mut_ref` putting the count back
+        match weak.compare_exchange_weak(count, count + 1, Ordering::Acquire, Ordering::Relaxed) {
+            Ok(_) => return,
+            Err(updated) => count = updated,
+        }
+    }
+}
+
+// The counts are borrowed field by field, never through `&InnerArc`: the
+// value may be mid-drop on this thread or already gone. The one exception
+// is the last MyWeak sizing the block, see its Drop.
+unsafe fn strong<'a, T: ?Sized>(ptr: NonNull<InnerArc<T>>) -> &'a AtomicUsize {
+    unsafe { &(*ptr.as_ptr()).strong }
+}
+
+unsafe fn weak<'a, T: ?Sized>(ptr: NonNull<InnerArc<T>>) -> &'a AtomicUsize {
+    unsafe { &(*ptr.as_ptr()).weak }
+}
+
+// Drops one weak count, freeing the block when it was the last. `layout`
+// is only asked for then, and mustn't look at the value: it's gone.
+unsafe fn release_weak<T: ?Sized, A: Allocator>(
     ptr: NonNull<InnerArc<T>>,
+    alloc: &A,
+    layout: impl FnOnce() -> Layout,
+) {
+    unsafe {
+        if weak(ptr).fetch_sub(1, Ordering::Release) == 1 {
+            fence(Ordering::Acquire);
+            emit(EventKind::Deallocate, ptr, 0);
+            alloc.deallocate(ptr.cast(), layout());
+        }
+    }
 }
 
-pub struct MyWeak<T> {
+pub struct MyArc<T: ?Sized, A: Allocator = Global> {
     ptr: NonNull<InnerArc<T>>,
+    alloc: A,
 }
 
-impl<T> MyWeak<T> {
-    fn upgrade(&self) -> Option<MyArc<T>> {
+pub struct MyWeak<T: ?Sized, A: Allocator = Global> {
+    ptr: NonNull<InnerArc<T>>,
+    alloc: A,
+}
+
+impl<T: ?Sized, A: Allocator + Clone> MyWeak<T, A> {
+    pub fn upgrade(&self) -> Option<MyArc<T, A>> {
         unsafe {
-            let inner = self.ptr.as_ref();
-            let mut strong_count = inner.strong.load(Ordering::Acquire);
+            let strong = strong(self.ptr);
+            let mut strong_count = strong.load(Ordering::Acquire);
 
+            // Never from 0: once the value is being dropped it stays dropped
             while strong_count != 0 {
-                match inner.strong.compare_exchange(
+                assert!(strong_count < MAX_REFCOUNT, "MyArc count overflow");
+                match strong.compare_exchange_weak(
                     strong_count,
                     strong_count + 1,
                     Ordering::AcqRel,
                     Ordering::Acquire,
                 ) {
-                    Ok(_) => return Some(MyArc { ptr: self.ptr }),
+                    Ok(_) => {
+                        emit(EventKind::Clone, self.ptr, strong_count + 1);
+                        return Some(MyArc {
+                            ptr: self.ptr,
+                            alloc: self.alloc.clone(),
+                        });
+                    }
                     Err(updated) => strong_count = updated,
                 }
             }
@@ -52,137 +180,384 @@ impl<T> MyWeak<T> {
 }
 
 impl<T> MyArc<T> {
-    fn new(value: T) -> Self {
-        let ptr = unsafe { NonNull::new_unchecked(Box::into_raw(Box::new(InnerArc::new(value)))) };
-        MyArc { ptr }
+    #[track_caller]
+    pub fn new(value: T) -> Self {
+        Self::new_in(value, Global)
+    }
+}
+
+impl<T, A: Allocator> MyArc<T, A> {
+    /// Like `new`, but the shared block is allocated from `alloc`.
+    #[track_caller]
+    pub fn new_in(value: T, alloc: A) -> Self {
+        let layout = Layout::new::<InnerArc<T>>();
+        let ptr = alloc
+            .allocate(layout)
+            .unwrap_or_else(|_| handle_alloc_error(layout))
+            .cast::<InnerArc<T>>();
+        unsafe { ptr.as_ptr().write(InnerArc::new(value)) };
+        emit(EventKind::Allocate, ptr, 1);
+        MyArc { ptr, alloc }
     }
 
-    fn get_strong_count(&self) -> usize {
-        unsafe {
-            (*self.ptr.as_ref())
-                .strong
-                .load(std::sync::atomic::Ordering::SeqCst)
+    pub fn try_unwrap(self) -> Result<T, Self> {
+        let claimed = unsafe { strong(self.ptr) }
+            .compare_exchange(1, 0, Ordering::Relaxed, Ordering::Relaxed)
+            .is_ok();
+        if !claimed {
+            return Err(self);
         }
-    }
+        fence(Ordering::Acquire);
+        emit(EventKind::Drop, self.ptr, 0);
 
-    fn get_weak_count(&self) -> usize {
+        let this = ManuallyDrop::new(self); // prevent drop
         unsafe {
-            (*self.ptr.as_ref())
-                .weak
-                .load(std::sync::atomic::Ordering::SeqCst)
+            let value = ptr::read(&raw const (*this.ptr.as_ptr()).value);
+            let alloc = ptr::read(&this.alloc);
+            // Release the implicit weak, the last MyWeak may still be around
+            release_weak(this.ptr, &alloc, Layout::new::<InnerArc<T>>);
+            Ok(value)
         }
     }
 
-    // todo might have to check weak as well, although it would have to upgrade?
-    fn get_mut_ref(&mut self) -> Option<&mut T> {
+    /// Gives up this pointer and returns the value if it was the last
+    /// MyArc. Unlike `try_unwrap`, of several threads racing to call this
+    /// on the last clones exactly one gets the value.
+    pub fn into_inner(self) -> Option<T> {
+        let this = ManuallyDrop::new(self);
+        let alloc = unsafe { ptr::read(&this.alloc) };
         unsafe {
-            let inner_ptr = &(*self.ptr.as_ref());
-            if inner_ptr.strong.load(std::sync::atomic::Ordering::SeqCst) == 1 {
-                return Some(&mut (*self.ptr.as_mut()).value);
+            let count = strong(this.ptr).fetch_sub(1, Ordering::Release);
+            emit(EventKind::Drop, this.ptr, count - 1);
+            if count != 1 {
+                return None;
             }
-            None
+            fence(Ordering::Acquire);
+            let value = ptr::read(&raw const (*this.ptr.as_ptr()).value);
+            // Release the implicit weak, the last MyWeak may still be around
+            release_weak(this.ptr, &alloc, Layout::new::<InnerArc<T>>);
+            Some(value)
         }
     }
 
-    fn get_value_ref(&self) -> &T {
-        unsafe { &(*self.ptr.as_ref()).value }
+    /// The value, moved out if this is the only MyArc and cloned otherwise.
+    pub fn unwrap_or_clone(self) -> T
+    where
+        T: Clone,
+    {
+        self.try_unwrap().unwrap_or_else(|arc| (*arc).clone())
     }
 
-    fn try_unwrap(self) -> Result<T, Self> {
-        if self.get_strong_count() == 1 {
-            let unboxed = unsafe { Box::from_raw(self.ptr.as_ptr()) };
-            let value = unboxed.value;
-            std::mem::forget(self); // prevent drop
-            Ok(value)
+    /// Clone-on-write access. If other MyArc share the value it's cloned
+    /// into a new block first. If only MyWeak point at it, it's moved to a
+    /// new block instead and those weak pointers stop upgrading.
+    #[track_caller]
+    pub fn make_mut(&mut self) -> &mut T
+    where
+        T: Clone,
+        A: Clone,
+    {
+        let (strong, weak) = unsafe { (strong(self.ptr), weak(self.ptr)) };
+        // Claiming strong 1 -> 0 locks out `upgrade` while we look at the weaks
+        if strong
+            .compare_exchange(1, 0, Ordering::Acquire, Ordering::Relaxed)
+            .is_err()
+        {
+            *self = Self::new_in((**self).clone(), self.alloc.clone());
+        } else if weak.load(Ordering::Relaxed) != 1 {
+            unsafe {
+                let value = ptr::read(&raw const (*self.ptr.as_ptr()).value);
+                emit(EventKind::Drop, self.ptr, 0);
+                let fresh = Self::new_in(value, self.alloc.clone());
+                // The old block only has weak pointers left, it's theirs to free
+                let old = ManuallyDrop::new(mem::replace(self, fresh));
+                let alloc = ptr::read(&old.alloc);
+                release_weak(old.ptr, &alloc, Layout::new::<InnerArc<T>>);
+            }
         } else {
-            Err(self)
+            strong.store(1, Ordering::Release);
+        }
+        unsafe { &mut self.ptr.as_mut().value }
+    }
+
+    /// What [`coerce!`](crate::coerce) expands to.
+    ///
+    /// # Safety
+    ///
+    /// Same as `MyRc::coerce_unchecked`: `f` must return the pointer it's
+    /// given, only unsized to `U`.
+    #[doc(hidden)]
+    pub unsafe fn coerce_unchecked<U: ?Sized>(
+        self,
+        f: impl FnOnce(*const T) -> *const U,
+    ) -> MyArc<U, A> {
+        let this = ManuallyDrop::new(self);
+        let given = unsafe { &raw const (*this.ptr.as_ptr()).value };
+        let value = f(given);
+        debug_assert!(ptr::addr_eq(value, given));
+        // Step back from the value to its block, keeping the metadata the
+        // compiler's unsizing gave `value`
+        let offset = given.addr() - this.ptr.as_ptr().addr();
+        let inner = unsafe { value.byte_sub(offset) } as *mut InnerArc<U>;
+        MyArc {
+            ptr: unsafe { NonNull::new_unchecked(inner) },
+            alloc: unsafe { ptr::read(&this.alloc) },
+        }
+    }
+}
+
+impl<T: ?Sized, A: Allocator> MyArc<T, A> {
+    pub fn allocator(&self) -> &A {
+        &self.alloc
+    }
+
+    pub fn get_strong_count(&self) -> usize {
+        unsafe { strong(self.ptr).load(Ordering::SeqCst) }
+    }
+
+    pub fn get_weak_count(&self) -> usize {
+        match unsafe { weak(self.ptr).load(Ordering::SeqCst) } {
+            // Locked by `get_mut_ref`, which only happens when it was 1
+            WEAK_LOCKED => 1,
+            count => count,
         }
     }
 
-    // do we not need to dec strong count
-    fn downgrade(&self) -> MyWeak<T> {
+    // Overwrites both counts, `weak` including the implicit one, so tests can
+    // start next to MAX_REFCOUNT instead of leaking isize::MAX clones. Put
+    // the real counts back before dropping.
+    #[cfg(test)]
+    fn with_counts(self, strong_count: usize, weak_count: usize) -> Self {
         unsafe {
-            let inner = self.ptr.as_ref();
-            // Increment weak count
-            inner.weak.fetch_add(1, Ordering::Relaxed);
-            MyWeak { ptr: self.ptr }
+            strong(self.ptr).store(strong_count, Ordering::Relaxed);
+            weak(self.ptr).store(weak_count, Ordering::Relaxed);
+        }
+        self
+    }
+
+    /// Only succeeds for the one and only pointer, weak ones included:
+    /// a MyWeak could otherwise upgrade and alias the `&mut`.
+    pub fn get_mut_ref(&mut self) -> Option<&mut T> {
+        let (strong, weak) = unsafe { (strong(self.ptr), weak(self.ptr)) };
+        // Lock out `downgrade` so no MyWeak can upgrade behind our back
+        // between the two checks. Acquire pairs with the Release of the
+        // last MyWeak's drop.
+        if weak
+            .compare_exchange(1, WEAK_LOCKED, Ordering::Acquire, Ordering::Relaxed)
+            .is_err()
+        {
+            return None;
+        }
+        // Acquire pairs with the Release of other MyArc's drops
+        let unique = strong.load(Ordering::Acquire) == 1;
+        weak.store(1, Ordering::Release);
+        if unique {
+            Some(unsafe { &mut (*self.ptr.as_ptr()).value })
+        } else {
+            None
+        }
+    }
+
+    pub fn get_value_ref(&self) -> &T {
+        unsafe { &self.ptr.as_ref().value }
+    }
+
+    pub fn downgrade(&self) -> MyWeak<T, A>
+    where
+        A: Clone,
+    {
+        acquire_weak(unsafe { weak(self.ptr) });
+        MyWeak {
+            ptr: self.ptr,
+            alloc: self.alloc.clone(),
         }
     }
 }
 
-impl<T> Clone for MyArc<T> {
-    fn clone(&self) -> Self {
+// ==============
+// Unsized values
+// ==============
+
+// A block for a value with `value_layout`, counts set to one and the value
+// left for the caller to write (see my_rc.rs).
+fn allocate_for_layout<T: ?Sized, A: Allocator>(
+    value_layout: Layout,
+    alloc: &A,
+    to_inner: impl FnOnce(*mut u8) -> *mut InnerArc<T>,
+) -> NonNull<InnerArc<T>> {
+    let layout = Layout::new::<InnerArc<()>>()
+        .extend(value_layout)
+        .expect("capacity overflow")
+        .0
+        .pad_to_align();
+    let mem = alloc
+        .allocate(layout)
+        .unwrap_or_else(|_| handle_alloc_error(layout));
+    unsafe {
+        let inner = to_inner(mem.as_ptr());
+        (&raw mut (*inner).strong).write(AtomicUsize::new(1));
+        (&raw mut (*inner).weak).write(AtomicUsize::new(1));
+        let inner = NonNull::new_unchecked(inner);
+        emit(EventKind::Allocate, inner, 1);
+        inner
+    }
+}
+
+impl<T> MyArc<[T]> {
+    // Moves `len` elements from `src` into a new block
+    unsafe fn copy_from_slice(src: *const T, len: usize) -> Self {
+        let value_layout = Layout::array::<T>(len).expect("capacity overflow");
+        let ptr = allocate_for_layout(value_layout, &Global, |mem| {
+            ptr::slice_from_raw_parts_mut(mem.cast::<T>(), len) as *mut InnerArc<[T]>
+        });
         unsafe {
-            (*self.ptr.as_ref())
-                .strong
-                .fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+            let dst = (&raw mut (*ptr.as_ptr()).value).cast::<T>();
+            ptr::copy_nonoverlapping(src, dst, len);
         }
-        Self { ptr: self.ptr }
+        MyArc { ptr, alloc: Global }
     }
 }
 
-impl<T> Clone for MyWeak<T> {
-    fn clone(&self) -> Self {
+impl From<&str> for MyArc<str> {
+    fn from(s: &str) -> Self {
+        let bytes = unsafe { MyArc::<[u8]>::copy_from_slice(s.as_ptr(), s.len()) };
+        let bytes = ManuallyDrop::new(bytes);
+        // Same block, same length, and the bytes are valid UTF-8
+        MyArc {
+            ptr: unsafe { NonNull::new_unchecked(bytes.ptr.as_ptr() as *mut InnerArc<str>) },
+            alloc: Global,
+        }
+    }
+}
+
+impl<T> From<Vec<T>> for MyArc<[T]> {
+    fn from(mut vec: Vec<T>) -> Self {
         unsafe {
-            (*self.ptr.as_ref()).weak.fetch_add(1, Ordering::Relaxed);
+            let arc = Self::copy_from_slice(vec.as_ptr(), vec.len());
+            // The elements moved, only the buffer is left to free
+            vec.set_len(0);
+            arc
         }
-        Self { ptr: self.ptr }
     }
 }
 
-impl<T> Drop for MyWeak<T> {
-    fn drop(&mut self) {
+impl<T: ?Sized> From<Box<T>> for MyArc<T> {
+    fn from(value: Box<T>) -> Self {
+        let value_layout = Layout::for_value(&*value);
+        let raw = Box::into_raw(value);
+        // The box's metadata on the new block, assuming the data pointer is
+        // a wide pointer's first word (see `with_addr_of`)
+        let ptr = allocate_for_layout(value_layout, &Global, |mem| unsafe {
+            with_addr_of(raw as *mut InnerArc<T>, NonNull::new_unchecked(mem))
+        });
         unsafe {
-            let inner = self.ptr.as_ref();
-            if inner.weak.fetch_sub(1, Ordering::Release) == 1 {
-                fence(Ordering::Acquire);
-                drop(Box::from_raw(self.ptr.as_ptr()));
+            let dst = (&raw mut (*ptr.as_ptr()).value).cast::<u8>();
+            ptr::copy_nonoverlapping(raw.cast::<u8>(), dst, value_layout.size());
+            // The value moved, only the box's memory is left to free
+            if value_layout.size() != 0 {
+                dealloc(raw.cast(), value_layout);
             }
         }
+        MyArc { ptr, alloc: Global }
+    }
+}
+
+/// Collects into a `Vec` first, like `MyRc<[T]>`.
+impl<T> FromIterator<T> for MyArc<[T]> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        Self::from(iter.into_iter().collect::<Vec<T>>())
+    }
+}
+
+impl<T: ?Sized, A: Allocator + Clone> Clone for MyArc<T, A> {
+    fn clone(&self) -> Self {
+        // Relaxed is enough: we already hold a strong count, nothing is freed under us
+        let count = unsafe { strong(self.ptr).fetch_add(1, Ordering::Relaxed) };
+        if count >= MAX_REFCOUNT {
+            refcount_overflow();
+        }
+        emit(EventKind::Clone, self.ptr, count + 1);
+        Self {
+            ptr: self.ptr,
+            alloc: self.alloc.clone(),
+        }
     }
 }
 
-impl<T> Deref for MyArc<T> {
+impl<T: ?Sized, A: Allocator + Clone> Clone for MyWeak<T, A> {
+    fn clone(&self) -> Self {
+        // Never actually locked: `get_mut_ref` only locks when there's no MyWeak
+        acquire_weak(unsafe { weak(self.ptr) });
+        Self {
+            ptr: self.ptr,
+            alloc: self.alloc.clone(),
+        }
+    }
+}
+
+impl<T: ?Sized, A: Allocator> Drop for MyWeak<T, A> {
+    fn drop(&mut self) {
+        // The last MyArc already dropped the value, only the memory is left.
+        // Sizing the block still makes an `&InnerArc` over that dead value:
+        // on stable Rust only `for_value` reads size and alignment out of a
+        // pointer's metadata (`for_value_raw` isn't stable). That relies on
+        // `for_value` reading nothing but the metadata, and on a reference
+        // to dropped, still allocated memory being allowed, which Miri agrees
+        // with. MyArc's own paths size the block without it.
+        let ptr = self.ptr;
+        unsafe { release_weak(ptr, &self.alloc, || Layout::for_value(ptr.as_ref())) };
+    }
+}
+
+impl<T: ?Sized, A: Allocator> Deref for MyArc<T, A> {
     type Target = T;
 
     fn deref(&self) -> &Self::Target {
-        unsafe { &(*self.ptr.as_ref()).value }
+        unsafe { &self.ptr.as_ref().value }
     }
 }
 
-impl<T> Drop for MyArc<T> {
+impl<T: ?Sized, A: Allocator> Drop for MyArc<T, A> {
     fn drop(&mut self) {
         unsafe {
-            let inner = self.ptr.as_ref();
-            if inner
-                .strong
-                .fetch_sub(1, std::sync::atomic::Ordering::Release)
-                != 1
-            {
+            let count = strong(self.ptr).fetch_sub(1, Ordering::Release);
+            emit(EventKind::Drop, self.ptr, count - 1);
+            if count != 1 {
                 return;
             }
-            drop(Box::from_raw(self.ptr.as_ptr()));
+            // Every other MyArc's uses of the value happen before we drop it
+            fence(Ordering::Acquire);
 
-            // Now decrement weak count because the Arc itself holds a weak ref
-            if inner.weak.fetch_sub(1, Ordering::Release) == 1 {
-                std::sync::atomic::fence(Ordering::Acquire);
-                // finally deallocate InnerArc
-                drop(Box::from_raw(self.ptr.as_ptr()));
-            }
+            // Sized while the value is still alive, MyWeak only touch the counts
+            let layout = Layout::for_value(self.ptr.as_ref());
+
+            // Drop the value but keep the allocation alive for any MyWeak
+            ptr::drop_in_place(&raw mut (*self.ptr.as_ptr()).value);
+
+            // Now release the weak count all the MyArc shared
+            release_weak(self.ptr, &self.alloc, || layout);
         }
     }
 }
 
-unsafe impl<T> Send for MyArc<T> {}
-unsafe impl<T> Sync for MyArc<T> {}
+// Sending a MyArc shares the value with the sender, and the last one to
+// drop may be on any thread, so both need T: Send + Sync (same as std's Arc).
+// A MyWeak can upgrade into a MyArc, so it gets the same bounds.
+unsafe impl<T: ?Sized + Send + Sync, A: Allocator + Send> Send for MyArc<T, A> {}
+unsafe impl<T: ?Sized + Send + Sync, A: Allocator + Sync> Sync for MyArc<T, A> {}
+unsafe impl<T: ?Sized + Send + Sync, A: Allocator + Send> Send for MyWeak<T, A> {}
+unsafe impl<T: ?Sized + Send + Sync, A: Allocator + Sync> Sync for MyWeak<T, A> {}
 
 #[cfg(test)]
 pub mod test {
+    #[cfg(feature = "std")]
     use std::{ops::Deref, sync::Mutex, thread};
 
-    use super::MyArc;
+    use super::{MAX_REFCOUNT, MyArc, MyWeak};
+    use crate::tracking::Tracking;
 
     #[test]
+    #[cfg(feature = "std")]
     fn test_multithreaded_ref_counting() {
         let arc = MyArc::new(Mutex::new(0));
 
@@ -258,16 +633,18 @@ pub mod test {
             }
         }
 
+        let tracker = Tracking::new();
         {
-            let a = MyArc::new(Tracker("a"));
+            let a = MyArc::new_in(Tracker("a"), &tracker);
 
             let b = a.clone();
-            let c = b.clone();
+            let _c = b.clone();
             assert_eq!(a.get_strong_count(), 3);
             // all dropped here, should print once
         }
 
         // You should see "Dropped Tracker(a)" once in output
+        assert_eq!(tracker.live_bytes(), 0);
     }
 
     #[test]
@@ -282,7 +659,7 @@ pub mod test {
             *value.unwrap() = 100;
         }
 
-        let rc2 = rc.clone();
+        let _rc2 = rc.clone();
         assert_eq!(rc.get_strong_count(), 2);
 
         // Should fail now
@@ -340,4 +717,201 @@ pub mod test {
         drop(w2);
         assert_eq!(arc.get_weak_count(), 1); // back to implicit only
     }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_no_leaks_through_weak_paths() {
+        let tracker = Tracking::new();
+
+        // Last MyArc goes first, the MyWeak frees the allocation
+        let weak = {
+            let arc = MyArc::new_in(String::from("weak outlives"), &tracker);
+            arc.downgrade()
+        };
+        assert!(weak.upgrade().is_none());
+        assert!(tracker.live_bytes() > 0);
+        drop(weak);
+        assert_eq!(tracker.live_bytes(), 0);
+
+        // Weaks go first, the MyArc frees the allocation
+        let arc = MyArc::new_in(String::from("arc outlives"), &tracker);
+        let weaks: Vec<_> = (0..3).map(|_| arc.downgrade()).collect();
+        drop(weaks);
+        drop(arc);
+        assert_eq!(tracker.live_bytes(), 0);
+
+        // Upgraded here, then the strong references are dropped on other threads
+        let arc = MyArc::new_in(vec![1, 2, 3], &tracker);
+        let weak = arc.downgrade();
+        thread::scope(|s| {
+            for _ in 0..4 {
+                let upgraded = weak.upgrade().unwrap();
+                s.spawn(move || assert_eq!(upgraded.len(), 3));
+            }
+        });
+        drop(arc);
+        drop(weak);
+        assert_eq!(tracker.live_bytes(), 0);
+        assert_eq!(tracker.allocation_count(), 3);
+    }
+
+    #[test]
+    fn test_try_unwrap_leaves_block_to_weak() {
+        let tracker = Tracking::new();
+        let arc = MyArc::new_in(String::from("moved out"), &tracker);
+        let weak = arc.downgrade();
+
+        // The value leaves, the block stays until the MyWeak is gone
+        let Ok(value) = MyArc::try_unwrap(arc) else {
+            panic!("only one MyArc");
+        };
+        assert_eq!(value, "moved out");
+        assert!(weak.upgrade().is_none());
+        drop(value);
+        assert!(tracker.live_bytes() > 0);
+        drop(weak);
+        assert_eq!(tracker.live_bytes(), 0);
+    }
+
+    #[test]
+    fn test_make_mut() {
+        let tracker = Tracking::new();
+
+        // Unique: no copy
+        let mut arc = MyArc::new_in(String::from("a"), &tracker);
+        arc.make_mut().push('b');
+        assert_eq!(tracker.allocation_count(), 1);
+
+        // Shared: the clone is detached, the other MyArc keeps the old value
+        let other = arc.clone();
+        arc.make_mut().push('c');
+        assert_eq!((&**arc, &**other), ("abc", "ab"));
+        assert_eq!(other.get_strong_count(), 1);
+        drop(other);
+
+        // Only weaks: the value moves, the weaks are disassociated
+        let weak = arc.downgrade();
+        assert!(arc.get_mut_ref().is_none());
+        arc.make_mut().push('d');
+        assert!(weak.upgrade().is_none());
+        assert_eq!(arc.get_weak_count(), 1);
+        assert_eq!(*arc, "abcd");
+        drop((arc, weak));
+        assert_eq!(tracker.live_bytes(), 0);
+    }
+
+    #[test]
+    fn test_into_inner_and_unwrap_or_clone() {
+        let tracker = Tracking::new();
+        let arc = MyArc::new_in(String::from("x"), &tracker);
+        let other = arc.clone();
+        let weak = arc.downgrade();
+        assert_eq!(arc.into_inner(), None);
+        assert_eq!(other.get_strong_count(), 1);
+
+        let again = other.clone();
+        assert_eq!(other.unwrap_or_clone(), "x");
+        assert_eq!(again.into_inner().as_deref(), Some("x"));
+        assert!(weak.upgrade().is_none());
+        drop(weak);
+        assert_eq!(tracker.live_bytes(), 0);
+    }
+
+    #[test]
+    fn test_unsized_from() {
+        let tracker = Tracking::new();
+        let s: MyArc<str> = MyArc::from("shared");
+        let weak = s.clone().downgrade();
+        assert_eq!(&*weak.upgrade().unwrap(), "shared");
+
+        let v: MyArc<[MyArc<u8, &Tracking>]> = (0..3).map(|i| MyArc::new_in(i, &tracker)).collect();
+        assert_eq!(v.iter().map(|x| **x).sum::<u8>(), 3);
+        assert_eq!(tracker.allocation_count(), 3);
+        drop(v);
+        assert_eq!(tracker.live_bytes(), 0);
+
+        let boxed: MyArc<[u16]> = MyArc::from(Box::<[u16]>::from(&[1, 2][..]));
+        assert_eq!(*boxed, [1, 2]);
+        let empty: MyArc<str> = MyArc::from("");
+        assert_eq!(empty.get_strong_count(), 1);
+    }
+
+    #[test]
+    fn test_coerce() {
+        let tracker = Tracking::new();
+        let debug: MyArc<dyn core::fmt::Debug, _> =
+            crate::coerce!(MyArc::new_in(String::from("dbg"), &tracker) => dyn core::fmt::Debug);
+        let weak = debug.downgrade();
+        assert_eq!(format!("{:?}", &*debug), "\"dbg\"");
+        drop(debug);
+        assert!(weak.upgrade().is_none());
+        drop(weak);
+        assert_eq!(tracker.live_bytes(), 0);
+    }
+
+    #[test]
+    fn test_send_sync_bounds() {
+        // Misuse is covered by tests/compile_fail
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<MyArc<u32>>();
+        assert_send_sync::<MyArc<str>>();
+        assert_send_sync::<MyWeak<core::sync::atomic::AtomicU32>>();
+        assert_send_sync::<MyArc<dyn Fn() + Send + Sync>>();
+        assert_send_sync::<MyArc<u32, &Tracking>>();
+    }
+
+    #[test]
+    fn test_weak_and_upgrade_overflow_panic() {
+        use std::panic::{AssertUnwindSafe, catch_unwind};
+
+        let tracker = Tracking::new();
+        let arc = MyArc::new_in(String::from("forgotten"), &tracker);
+        let weak = arc.downgrade();
+        let arc = arc.with_counts(MAX_REFCOUNT, MAX_REFCOUNT);
+
+        // These can refuse before touching the count
+        assert!(catch_unwind(AssertUnwindSafe(|| weak.upgrade())).is_err());
+        assert!(catch_unwind(AssertUnwindSafe(|| arc.downgrade())).is_err());
+        assert!(catch_unwind(AssertUnwindSafe(|| weak.clone())).is_err());
+        assert_eq!(arc.get_strong_count(), MAX_REFCOUNT);
+        assert_eq!(arc.get_weak_count(), MAX_REFCOUNT);
+
+        // One below still works, and clone may reach the limit
+        let arc = arc.with_counts(MAX_REFCOUNT - 1, MAX_REFCOUNT - 1);
+        let (again, weak_again) = (arc.clone(), weak.clone());
+        assert_eq!(arc.get_strong_count(), MAX_REFCOUNT);
+        assert_eq!(arc.get_weak_count(), MAX_REFCOUNT);
+        drop((again, weak_again));
+
+        let arc = arc.with_counts(1, 2);
+        drop((arc, weak));
+        assert_eq!(tracker.live_bytes(), 0);
+    }
+
+    #[test]
+    #[cfg(unix)]
+    #[cfg_attr(miri, ignore = "spawns a process")]
+    fn test_clone_aborts_past_max_refcount() {
+        use std::process::{Command, Stdio};
+
+        const CHILD: &str = "MY_STD_ARC_OVERFLOW_CHILD";
+        const NAME: &str = "my_arc::test::test_clone_aborts_past_max_refcount";
+
+        // The abort takes the whole process down, so the clone happens in a
+        // copy of this test binary running only this test
+        if std::env::var_os(CHILD).is_some() {
+            let arc = MyArc::new(0).with_counts(MAX_REFCOUNT, 1);
+            let _ = arc.clone();
+            unreachable!("clone past MAX_REFCOUNT returned");
+        }
+        let status = Command::new(std::env::current_exe().unwrap())
+            .args(["--exact", NAME, "--test-threads=1"])
+            .env(CHILD, "1")
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status()
+            .unwrap();
+        // Killed by SIGABRT, not a failed test (which exits with 101)
+        assert_eq!(status.code(), None, "{status}");
+    }
 }
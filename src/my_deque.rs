@@ -1,12 +1,12 @@
 // Purpose: Custom double-ended queue (deque) implementation with low-level raw buffer management.
 
-use std::{
-    alloc::{self, alloc, Layout},
-    collections::VecDeque,
-    fmt::Debug,
-    marker::PhantomData,
-    mem::{ManuallyDrop, MaybeUninit},
-    ptr::{self, NonNull},
+use alloc::vec::Vec;
+use core::{cmp::Ordering, fmt::Debug, marker::PhantomData, mem::ManuallyDrop, ptr, slice};
+
+use crate::{
+    allocator::{Allocator, Global},
+    raw_vec::{RawVec, TryReserveError},
+    sort,
 };
 
 // =====================
@@ -14,8 +14,8 @@ use std::{
 // =====================
 
 /// A double-ended queue (deque) with manual memory management.
-struct MyDeque<T> {
-    buf: RawVec<T>,
+pub struct MyDeque<T, A: Allocator = Global> {
+    buf: RawVec<T, A>,
     head: usize,
     tail: usize,
     len: usize,
@@ -24,10 +24,10 @@ struct MyDeque<T> {
 /// Immutable reference iterator for MyDeque<T>.
 /// Yields &T in logical order, handles wrap-around.
 pub struct MyDequeIter<'a, T> {
-    head: *const T,
-    tail: *const T,
+    buf: *const T,
+    idx: usize,
+    cap: usize,
     len: usize,
-    buf_cap: usize,
     _marker: PhantomData<&'a T>,
 }
 
@@ -43,74 +43,206 @@ pub struct MutMyDequeIter<'a, T> {
 
 /// Consuming iterator for MyDeque<T>.
 /// Yields T by value, consuming the deque.
-pub struct MyDequeIntoIter<T> {
+pub struct MyDequeIntoIter<T, A: Allocator = Global> {
     ptr: *const T,
     idx: usize,
     cap: usize,
     len: usize,
-    _buf: RawVec<T>,
+    _buf: RawVec<T, A>,
 }
 
-/// Raw buffer for manual memory management.
-struct RawVec<T> {
-    ptr: NonNull<MaybeUninit<T>>,
-    cap: usize,
-}
+// The iterators point into the buffer the same way &[T] / &mut [T] / RawVec would
+unsafe impl<T: Sync> Send for MyDequeIter<'_, T> {}
+unsafe impl<T: Sync> Sync for MyDequeIter<'_, T> {}
+unsafe impl<T: Send> Send for MutMyDequeIter<'_, T> {}
+unsafe impl<T: Sync> Sync for MutMyDequeIter<'_, T> {}
+unsafe impl<T: Send, A: Allocator + Send> Send for MyDequeIntoIter<T, A> {}
+unsafe impl<T: Sync, A: Allocator + Sync> Sync for MyDequeIntoIter<T, A> {}
 
 // =====================
 // Inherent impl blocks
 // =====================
 
-// MyDeque<T> inherent methods
+// MyDeque<T> constructors on the global heap
 impl<T> MyDeque<T> {
     pub fn new() -> Self {
+        Self::new_in(Global)
+    }
+
+    #[track_caller]
+    pub fn with_capacity(cap: usize) -> Self {
+        Self::with_capacity_in(cap, Global)
+    }
+
+    /// Like `with_capacity` but reports allocation failure instead of aborting.
+    #[track_caller]
+    pub fn try_with_capacity(cap: usize) -> Result<Self, TryReserveError> {
+        Self::try_with_capacity_in(cap, Global)
+    }
+}
+
+// MyDeque<T, A> inherent methods
+impl<T, A: Allocator> MyDeque<T, A> {
+    /// Creates an empty deque that will allocate from `alloc`.
+    pub fn new_in(alloc: A) -> Self {
         Self {
-            buf: RawVec::new(),
+            buf: RawVec::new_in(alloc),
             head: 0,
             tail: 0,
             len: 0,
         }
     }
 
-    pub fn with_capacity(cap: usize) -> Self {
+    #[track_caller]
+    pub fn with_capacity_in(cap: usize, alloc: A) -> Self {
         Self {
-            buf: RawVec::with_capacity(cap),
+            buf: RawVec::with_capacity_in(cap, alloc),
             head: 0,
             tail: 0,
             len: 0,
         }
     }
 
+    #[track_caller]
+    pub fn try_with_capacity_in(cap: usize, alloc: A) -> Result<Self, TryReserveError> {
+        Ok(Self {
+            buf: RawVec::try_with_capacity_in(cap, alloc)?,
+            head: 0,
+            tail: 0,
+            len: 0,
+        })
+    }
+
+    /// The allocator backing this deque.
+    pub fn allocator(&self) -> &A {
+        &self.buf.alloc
+    }
+
+    #[track_caller]
     pub fn push_back(&mut self, value: T) {
         if self.len == self.buf.cap {
-            let (new_head, new_tail) = self.buf.grow(self.head, self.len);
-            self.head = new_head;
-            self.tail = new_tail;
+            self.grow();
         }
 
         self.buf.write(self.tail, value);
 
-        self.tail = (self.tail + 1) % self.buf.cap;
+        self.tail = self.wrap_add(self.tail, 1);
         self.len += 1;
     }
 
+    #[track_caller]
     pub fn push_front(&mut self, value: T) {
         if self.len == self.buf.cap {
-            let (new_head, new_tail) = self.buf.grow(self.head, self.len);
-            self.head = new_head;
-            self.tail = new_tail;
+            self.grow();
         }
 
-        self.head = (self.head + self.buf.cap - 1) % self.buf.cap; // move head back
+        self.head = self.wrap_sub(self.head, 1); // move head back
         self.buf.write(self.head, value);
         self.len += 1;
     }
 
+    /// Reserves room for at least `additional` more elements.
+    #[track_caller]
+    pub fn reserve(&mut self, additional: usize) {
+        let old_cap = self.buf.cap;
+        self.buf.reserve(self.len, additional);
+        self.handle_capacity_increase(old_cap);
+    }
+
+    /// Reserves room for exactly `additional` more elements.
+    #[track_caller]
+    pub fn reserve_exact(&mut self, additional: usize) {
+        let old_cap = self.buf.cap;
+        self.buf.reserve_exact(self.len, additional);
+        self.handle_capacity_increase(old_cap);
+    }
+
+    /// Shrinks the capacity as close to `len` as possible.
+    pub fn shrink_to_fit(&mut self) {
+        self.shrink_to(0);
+    }
+
+    /// Shrinks the capacity to `max(len, min_capacity)`.
+    ///
+    /// Elements are first moved so they all sit below the new capacity,
+    /// then the buffer is reallocated.
+    pub fn shrink_to(&mut self, min_capacity: usize) {
+        let target = min_capacity.max(self.len);
+        if target >= self.buf.cap || RawVec::<T, A>::IS_ZST {
+            return;
+        }
+
+        let ptr = self.buf.ptr.as_ptr();
+        unsafe {
+            if self.head + self.len <= target {
+                // Already inside the new buffer
+            } else if self.head + self.len <= self.buf.cap {
+                // Contiguous but too far right: slide the block to the start
+                ptr::copy(ptr.add(self.head), ptr, self.len);
+                self.head = 0;
+            } else {
+                // Wrapped: the back part stays at 0, the head part moves to
+                // the end of the new buffer.
+                let head_len = self.buf.cap - self.head;
+                let new_head = target - head_len;
+                ptr::copy(ptr.add(self.head), ptr.add(new_head), head_len);
+                self.head = new_head;
+            }
+        }
+
+        self.buf.shrink_to(target);
+        self.tail = if target == 0 {
+            0
+        } else {
+            (self.head + self.len) % target
+        };
+    }
+
+    /// Tries to reserve room for at least `additional` more elements.
+    #[track_caller]
+    pub fn try_reserve(&mut self, additional: usize) -> Result<(), TryReserveError> {
+        let old_cap = self.buf.cap;
+        self.buf.try_reserve(self.len, additional)?;
+        self.handle_capacity_increase(old_cap);
+        Ok(())
+    }
+
+    /// Tries to reserve room for exactly `additional` more elements.
+    #[track_caller]
+    pub fn try_reserve_exact(&mut self, additional: usize) -> Result<(), TryReserveError> {
+        let old_cap = self.buf.cap;
+        self.buf.try_reserve_exact(self.len, additional)?;
+        self.handle_capacity_increase(old_cap);
+        Ok(())
+    }
+
+    /// Pushes to the back, returning an error instead of aborting if the
+    /// deque has to grow and the allocation fails.
+    #[track_caller]
+    pub fn try_push_back(&mut self, value: T) -> Result<(), TryReserveError> {
+        if self.len == self.buf.cap {
+            self.try_reserve(1)?;
+        }
+        self.push_back(value);
+        Ok(())
+    }
+
+    /// Pushes to the front, returning an error instead of aborting if the
+    /// deque has to grow and the allocation fails.
+    #[track_caller]
+    pub fn try_push_front(&mut self, value: T) -> Result<(), TryReserveError> {
+        if self.len == self.buf.cap {
+            self.try_reserve(1)?;
+        }
+        self.push_front(value);
+        Ok(())
+    }
+
     pub fn pop_back(&mut self) -> Option<T> {
         if self.len == 0 {
             None
         } else {
-            self.tail = (self.tail - 1 + self.buf.cap) % self.buf.cap;
+            self.tail = self.wrap_sub(self.tail, 1);
             self.len -= 1;
             Some(self.buf.read(self.tail))
         }
@@ -121,7 +253,7 @@ impl<T> MyDeque<T> {
             None
         } else {
             let val = Some(self.buf.read(self.head));
-            self.head = (self.head + 1) % self.buf.cap;
+            self.head = self.wrap_add(self.head, 1);
             self.len -= 1;
             val
         }
@@ -131,7 +263,7 @@ impl<T> MyDeque<T> {
         if self.len == 0 {
             None
         } else {
-            let tail_idx = (self.tail + self.buf.cap - 1) % self.buf.cap;
+            let tail_idx = self.wrap_sub(self.tail, 1);
             Some(self.buf.read_ref(tail_idx))
         }
     }
@@ -165,7 +297,7 @@ impl<T> MyDeque<T> {
         if index >= self.len {
             None
         } else {
-            let index_ref = (self.head + index) % self.buf.cap;
+            let index_ref = self.to_physical_idx(index);
             Some(self.buf.read_ref(index_ref))
         }
     }
@@ -174,14 +306,14 @@ impl<T> MyDeque<T> {
         if index >= self.len {
             None
         } else {
-            let index_ref = (self.head + index) % self.buf.cap;
+            let index_ref = self.to_physical_idx(index);
             Some(self.buf.read_mut(index_ref))
         }
     }
 
     pub fn clear(&mut self) {
         for i in 0..self.len {
-            let index = (self.head + i) % self.buf.cap;
+            let index = self.to_physical_idx(i);
             self.buf.drop_index(index);
         }
         self.len = 0;
@@ -194,7 +326,7 @@ impl<T> MyDeque<T> {
         T: PartialEq,
     {
         for i in 0..self.len {
-            let index = (self.head + i) % self.buf.cap;
+            let index = self.to_physical_idx(i);
             if *value == *self.buf.read_ref(index) {
                 return true;
             }
@@ -202,6 +334,131 @@ impl<T> MyDeque<T> {
         false
     }
 
+    /// Rearranges the buffer so the elements sit in one run, front to back,
+    /// and returns them as a slice. Doesn't allocate.
+    ///
+    /// A wrapped deque `[C, D, _, A, B]` first has its front segment copied
+    /// down to `[C, D, A, B, _]`, then the run is rotated to `[A, B, C, D, _]`.
+    pub fn make_contiguous(&mut self) -> &mut [T] {
+        let ptr = self.buf.ptr.as_ptr().cast::<T>();
+        if RawVec::<T, A>::IS_ZST {
+            return unsafe { slice::from_raw_parts_mut(ptr, self.len) };
+        }
+
+        if self.head <= self.buf.cap - self.len {
+            return unsafe { slice::from_raw_parts_mut(ptr.add(self.head), self.len) };
+        }
+
+        let head_len = self.buf.cap - self.head;
+        let tail_len = self.len - head_len;
+        unsafe {
+            ptr::copy(ptr.add(self.head), ptr.add(tail_len), head_len);
+            slice::from_raw_parts_mut(ptr, self.len).rotate_left(tail_len);
+        }
+        self.head = 0;
+        self.tail = self.len % self.buf.cap;
+
+        unsafe { slice::from_raw_parts_mut(ptr, self.len) }
+    }
+
+    /// Stable sort, done in place after `make_contiguous`. The merge
+    /// scratch buffer comes from this deque's allocator.
+    pub fn sort(&mut self)
+    where
+        T: Ord,
+    {
+        self.sort_by(T::cmp);
+    }
+
+    pub fn sort_by<F>(&mut self, mut compare: F)
+    where
+        F: FnMut(&T, &T) -> Ordering,
+    {
+        self.make_contiguous();
+        let v = unsafe {
+            let ptr = self.buf.ptr.as_ptr().cast::<T>();
+            slice::from_raw_parts_mut(ptr.add(self.head), self.len)
+        };
+        sort::merge_sort(
+            v,
+            &mut |a, b| compare(a, b) == Ordering::Less,
+            &self.buf.alloc,
+        );
+    }
+
+    pub fn sort_by_key<K, F>(&mut self, mut f: F)
+    where
+        F: FnMut(&T) -> K,
+        K: Ord,
+    {
+        self.sort_by(|a, b| f(a).cmp(&f(b)));
+    }
+
+    /// Unstable sort (pattern-defeating quicksort), done in place after
+    /// `make_contiguous`.
+    pub fn sort_unstable(&mut self)
+    where
+        T: Ord,
+    {
+        self.sort_unstable_by(T::cmp);
+    }
+
+    pub fn sort_unstable_by<F>(&mut self, mut compare: F)
+    where
+        F: FnMut(&T, &T) -> Ordering,
+    {
+        sort::quicksort(self.make_contiguous(), &mut |a, b| {
+            compare(a, b) == Ordering::Less
+        });
+    }
+
+    pub fn sort_unstable_by_key<K, F>(&mut self, mut f: F)
+    where
+        F: FnMut(&T) -> K,
+        K: Ord,
+    {
+        self.sort_unstable_by(|a, b| f(a).cmp(&f(b)));
+    }
+
+    /// Moves the element that belongs at `index` in sorted order there,
+    /// returning the elements before and after it. Leaves the deque
+    /// contiguous.
+    ///
+    /// # Panics
+    ///
+    /// If `index >= len`.
+    pub fn select_nth_unstable(&mut self, index: usize) -> (&mut [T], &mut T, &mut [T])
+    where
+        T: Ord,
+    {
+        self.select_nth_unstable_by(index, T::cmp)
+    }
+
+    pub fn select_nth_unstable_by<F>(
+        &mut self,
+        index: usize,
+        mut compare: F,
+    ) -> (&mut [T], &mut T, &mut [T])
+    where
+        F: FnMut(&T, &T) -> Ordering,
+    {
+        sort::select_nth_unstable(self.make_contiguous(), index, &mut |a, b| {
+            compare(a, b) == Ordering::Less
+        })
+    }
+
+    pub fn select_nth_unstable_by_key<K, F>(
+        &mut self,
+        index: usize,
+        mut f: F,
+    ) -> (&mut [T], &mut T, &mut [T])
+    where
+        F: FnMut(&T) -> K,
+        K: Ord,
+    {
+        self.select_nth_unstable_by(index, |a, b| f(a).cmp(&f(b)))
+    }
+
     /*
        append (Moves all the elements of other into self, leaving other empty.)
        retain (Retains only the elements specified by the predicate.)
@@ -218,121 +475,91 @@ impl<T> MyDeque<T> {
          */
 }
 
-// RawVec<T> inherent methods
-impl<T> RawVec<T> {
-    fn new() -> Self {
-        let cap = 2;
-        // gives us a block of memory of size cap.
-        let layout = Layout::array::<MaybeUninit<T>>(cap).unwrap();
-
-        let ptr = unsafe {
-            let raw_ptr = alloc(layout) as *mut MaybeUninit<T>;
-            NonNull::new(raw_ptr).unwrap_or_else(|| alloc::handle_alloc_error(layout))
-        };
-
-        Self { ptr, cap }
+// MyDeque<T> buffer management
+impl<T, A: Allocator> MyDeque<T, A> {
+    /// Physical buffer index of the `logical`-th element from the front.
+    fn to_physical_idx(&self, logical: usize) -> usize {
+        self.wrap_add(self.head, logical)
     }
 
-    pub fn with_capacity(cap: usize) -> Self {
-        let layout = Layout::array::<MaybeUninit<T>>(cap).unwrap();
+    // The wrapping_* calls only ever overflow for zero-sized T, where
+    // cap == usize::MAX and every index points at the same address anyway.
+    fn wrap_add(&self, idx: usize, addend: usize) -> usize {
+        wrap_index(idx.wrapping_add(addend), self.buf.cap)
+    }
 
-        let ptr = unsafe {
-            let raw_ptr = alloc(layout) as *mut MaybeUninit<T>;
-            NonNull::new(raw_ptr).unwrap_or_else(|| alloc::handle_alloc_error(layout))
-        };
+    fn wrap_sub(&self, idx: usize, subtrahend: usize) -> usize {
+        wrap_index(
+            idx.wrapping_sub(subtrahend).wrapping_add(self.buf.cap),
+            self.buf.cap,
+        )
+    }
 
-        Self { ptr, cap }
+    #[track_caller]
+    fn grow(&mut self) {
+        let old_cap = self.buf.cap;
+        self.buf.grow_amortized(self.len, 1);
+        self.handle_capacity_increase(old_cap);
     }
 
-    /// Grows the buffer for a circular deque.
-    ///
-    /// This is necessary because the logical order of elements in
-    /// a circular buffer can wrap around the physical end of the buffer.
-    /// A simple realloc would not reorder the elements correctly.
-    ///
-    /// So, we:
-    /// 1. Allocate a new buffer with double capacity.
-    /// 2. Copy existing elements in logical order from old buffer to new buffer.
-    /// 3. Update the pointer to the new buffer.
-    /// 4. Reset head to 0 and tail to len.
-    ///
-    /// # Parameters
-    /// - head: current index of the front element in the circular buffer.
-    /// - len: number of valid elements in the buffer.
+    /// Restores the ring layout after the buffer has been reallocated in place.
     ///
-    /// # Returns
-    /// Tuple (new_head, new_tail) — these will be (0, len) after growth.
+    /// `RawVec` grows by realloc, which keeps every element at the same index.
+    /// That is fine unless the deque wrapped around the old end, in which case
+    /// one of the two segments has to move so the elements are in logical
+    /// order again from `head`.
     ///
     /// # Example
     ///
-    /// Suppose we have capacity = 4 and buffer holds:
-    /// [50, 20, 30, 40]
-    ///  ^           ^
-    /// tail=3      head=1 (logical order from head to tail)
+    /// Old capacity = 4, head = 2, len = 4:
+    /// [C, D, A, B]            logical order A, B, C, D
     ///
-    /// The logical order of elements is: 20 (index 1), 30 (2), 40 (3), 50 (0)
+    /// After realloc to 8 the back segment is short, so it is copied after B:
+    /// [_, _, A, B, C, D, _, _]
+    ///        ^head       ^tail
     ///
-    /// After grow, capacity = 8, and we copy in logical order starting at index 0:
-    /// [20, 30, 40, 50, _, _, _, _]
-    ///  ^head           ^tail
-    /// 0                4 (tail points one past last element)
-    fn grow(&mut self, head: usize, len: usize) -> (usize, usize) {
-        let new_cap = self.cap * 2;
-        let new_layout = Layout::array::<MaybeUninit<T>>(new_cap).unwrap();
-
-        // Allocate new buffer
-        let new_ptr = unsafe {
-            let raw_ptr = alloc::alloc(new_layout) as *mut MaybeUninit<T>;
-            NonNull::new(raw_ptr).unwrap_or_else(|| alloc::handle_alloc_error(new_layout))
-        };
-
-        unsafe {
-            // Copy elements from old buffer to new buffer in logical order
-            for i in 0..len {
-                // Calculate source index, wrapping around old capacity
-                let src_idx = (head + i) % self.cap;
-                // Pointers for source and destination
-                let src = self.ptr.as_ptr().add(src_idx);
-                let dst = new_ptr.as_ptr().add(i);
-                // Copy the element without dropping
-                dst.write(src.read());
-            }
-
-            // Deallocate old buffer
-            let old_layout = Layout::array::<MaybeUninit<T>>(self.cap).unwrap();
-            alloc::dealloc(self.ptr.as_ptr() as *mut u8, old_layout);
+    /// If instead the head segment is the shorter one, it is copied to the
+    /// very end of the new buffer and `head` moves with it.
+    fn handle_capacity_increase(&mut self, old_cap: usize) {
+        let new_cap = self.buf.cap;
+        if new_cap == old_cap {
+            return;
         }
 
-        // Update pointer and capacity
-        self.ptr = new_ptr;
-        self.cap = new_cap;
-
-        // Reset head to 0 and tail to len, reflecting new linear layout
-        (0, len)
-    }
+        let ptr = self.buf.ptr.as_ptr();
+        if self.head + self.len > old_cap {
+            let head_len = old_cap - self.head;
+            let tail_len = self.len - head_len;
 
-    fn write(&mut self, index: usize, value: T) {
-        unsafe {
-            (*self.ptr.as_ptr().add(index)).write(value);
+            unsafe {
+                if tail_len < head_len && tail_len <= new_cap - old_cap {
+                    // move the wrapped back segment after the old end
+                    ptr::copy_nonoverlapping(ptr, ptr.add(old_cap), tail_len);
+                } else {
+                    // move the head segment to the end of the new buffer
+                    let new_head = new_cap - head_len;
+                    ptr::copy(ptr.add(self.head), ptr.add(new_head), head_len);
+                    self.head = new_head;
+                }
+            }
         }
-    }
 
-    fn read(&self, index: usize) -> T {
-        unsafe { ptr::read((*self.ptr.as_ptr().add(index)).as_ptr()) }
+        self.tail = (self.head + self.len) % new_cap;
     }
+}
 
-    fn read_mut(&mut self, index: usize) -> &mut T {
-        unsafe { &mut *self.ptr.as_ptr().add(index).cast::<T>() }
+/// Folds an index in `0..2 * cap` back into `0..cap`.
+fn wrap_index(logical: usize, cap: usize) -> usize {
+    if logical >= cap {
+        logical - cap
+    } else {
+        logical
     }
+}
 
-    fn read_ref(&self, index: usize) -> &T {
-        unsafe { &*self.ptr.as_ptr().add(index).cast::<T>() }
-    }
-
-    fn drop_index(&mut self, index: usize) {
-        unsafe {
-            ptr::drop_in_place(self.ptr.as_ptr().add(index).cast::<T>());
-        }
+impl<T> Default for MyDeque<T> {
+    fn default() -> Self {
+        Self::new()
     }
 }
 
@@ -348,13 +575,9 @@ impl<'a, T> Iterator for MyDequeIter<'a, T> {
         if self.len == 0 {
             None
         } else {
-            let item = unsafe { &*self.head };
-            // Advance head, wrapping if needed
-            self.head = if (self.head as usize + 1) % self.buf_cap == self.tail as usize {
-                self.tail // End of iteration
-            } else {
-                unsafe { self.head.add(1) }
-            };
+            let item = unsafe { &*self.buf.add(self.idx) };
+            // Advance, wrapping around the physical end of the buffer
+            self.idx = (self.idx + 1) % self.cap;
             self.len -= 1;
             Some(item)
         }
@@ -362,20 +585,17 @@ impl<'a, T> Iterator for MyDequeIter<'a, T> {
 }
 
 // IntoIterator for &MyDeque<T>
-impl<'a, T> IntoIterator for &'a MyDeque<T> {
+impl<'a, T, A: Allocator> IntoIterator for &'a MyDeque<T, A> {
     type Item = &'a T;
 
     type IntoIter = MyDequeIter<'a, T>;
 
     fn into_iter(self) -> Self::IntoIter {
-        let head = unsafe { self.buf.ptr.add(self.head).as_ptr() } as *const T;
-        let tail = unsafe { self.buf.ptr.add(self.tail).as_ptr() } as *const T;
-
         MyDequeIter {
-            head,
-            tail,
+            buf: self.buf.ptr.as_ptr() as *const T,
+            idx: self.head,
+            cap: self.buf.cap,
             len: self.len,
-            buf_cap: self.buf.cap,
             _marker: PhantomData,
         }
     }
@@ -398,15 +618,14 @@ impl<'a, T> Iterator for MutMyDequeIter<'a, T> {
 }
 
 // IntoIterator for &mut MyDeque<T>
-impl<'a, T> IntoIterator for &'a mut MyDeque<T> {
+impl<'a, T, A: Allocator> IntoIterator for &'a mut MyDeque<T, A> {
     type Item = &'a mut T;
 
     type IntoIter = MutMyDequeIter<'a, T>;
 
     fn into_iter(self) -> Self::IntoIter {
-        let buf = unsafe { self.buf.ptr.add(self.head).as_ptr() as *mut T };
         MutMyDequeIter {
-            buf,
+            buf: self.buf.ptr.as_ptr() as *mut T,
             idx: self.head,
             cap: self.buf.cap,
             remaining: self.len(),
@@ -416,7 +635,7 @@ impl<'a, T> IntoIterator for &'a mut MyDeque<T> {
 }
 
 // Iterator for MyDequeIntoIter<T>
-impl<T> Iterator for MyDequeIntoIter<T> {
+impl<T, A: Allocator> Iterator for MyDequeIntoIter<T, A> {
     type Item = T;
 
     fn next(&mut self) -> Option<Self::Item> {
@@ -432,10 +651,10 @@ impl<T> Iterator for MyDequeIntoIter<T> {
 }
 
 // IntoIterator for MyDeque<T>
-impl<T> IntoIterator for MyDeque<T> {
+impl<T, A: Allocator> IntoIterator for MyDeque<T, A> {
     type Item = T;
 
-    type IntoIter = MyDequeIntoIter<T>;
+    type IntoIter = MyDequeIntoIter<T, A>;
 
     fn into_iter(self) -> Self::IntoIter {
         // Prevent MyVec's Drop as we're dropping it with MyVecIntoIntoIter
@@ -458,19 +677,19 @@ impl<T> IntoIterator for MyDeque<T> {
     }
 }
 
-impl<T: Ord> Ord for MyDeque<T> {
-    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+impl<T: Ord, A: Allocator> Ord for MyDeque<T, A> {
+    fn cmp(&self, other: &Self) -> Ordering {
         let mut self_iter = self.into_iter();
         let mut other_iter = other.into_iter();
 
         loop {
             match (self_iter.next(), other_iter.next()) {
-                (None, None) => return std::cmp::Ordering::Equal,
-                (None, Some(_)) => return std::cmp::Ordering::Less,
-                (Some(_), None) => return std::cmp::Ordering::Greater,
+                (None, None) => return Ordering::Equal,
+                (None, Some(_)) => return Ordering::Less,
+                (Some(_), None) => return Ordering::Greater,
                 (Some(a), Some(b)) => {
                     let cmp = a.cmp(b);
-                    if cmp != std::cmp::Ordering::Equal {
+                    if cmp != Ordering::Equal {
                         return cmp;
                     }
                 }
@@ -479,18 +698,18 @@ impl<T: Ord> Ord for MyDeque<T> {
     }
 }
 
-impl<T: PartialOrd> PartialOrd for MyDeque<T> {
-    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+impl<T: PartialOrd, A: Allocator> PartialOrd for MyDeque<T, A> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
         let mut self_iter = self.into_iter();
         let mut other_iter = other.into_iter();
 
         loop {
             match (self_iter.next(), other_iter.next()) {
-                (None, None) => return Some(std::cmp::Ordering::Equal),
-                (None, Some(_)) => return Some(std::cmp::Ordering::Less),
-                (Some(_), None) => return Some(std::cmp::Ordering::Greater),
+                (None, None) => return Some(Ordering::Equal),
+                (None, Some(_)) => return Some(Ordering::Less),
+                (Some(_), None) => return Some(Ordering::Greater),
                 (Some(a), Some(b)) => match a.partial_cmp(b) {
-                    Some(std::cmp::Ordering::Equal) => continue,
+                    Some(Ordering::Equal) => continue,
                     Some(cmp) => return Some(cmp),
                     None => return None,
                 },
@@ -500,7 +719,7 @@ impl<T: PartialOrd> PartialOrd for MyDeque<T> {
 }
 
 // Drop for MyDequeIntoIter<T>
-impl<T> Drop for MyDequeIntoIter<T> {
+impl<T, A: Allocator> Drop for MyDequeIntoIter<T, A> {
     fn drop(&mut self) {
         while self.len != 0 {
             unsafe {
@@ -513,38 +732,24 @@ impl<T> Drop for MyDequeIntoIter<T> {
 }
 
 // Drop for MyDeque<T>
-impl<T> Drop for MyDeque<T> {
+impl<T, A: Allocator> Drop for MyDeque<T, A> {
     fn drop(&mut self) {
         for i in 0..self.len {
             unsafe {
-                let index = (self.head + i) % self.buf.cap;
+                let index = self.to_physical_idx(i);
                 ptr::drop_in_place((*self.buf.ptr.as_ptr().add(index)).as_mut_ptr());
             }
         }
     }
 }
 
-// Drop for RawVec<T>
-impl<T> Drop for RawVec<T> {
-    fn drop(&mut self) {
-        if self.cap == 0 {
-            return;
-        }
-
-        let layout = Layout::array::<MaybeUninit<T>>(self.cap).unwrap();
-
-        unsafe {
-            alloc::dealloc(self.ptr.as_ptr() as *mut u8, layout);
-        }
-    }
-}
-
 // Clone for MyDeque<T>
-impl<T: Clone> Clone for MyDeque<T> {
+impl<T: Clone, A: Allocator + Clone> Clone for MyDeque<T, A> {
+    #[track_caller]
     fn clone(&self) -> Self {
-        let mut new = MyDeque::new();
+        let mut new = MyDeque::with_capacity_in(self.len, self.buf.alloc.clone());
         for i in 0..self.len {
-            let index = (self.head + i) % self.buf.cap;
+            let index = self.to_physical_idx(i);
             let val = self.buf.read_ref(index);
             new.push_back(val.clone());
         }
@@ -553,7 +758,7 @@ impl<T: Clone> Clone for MyDeque<T> {
 }
 
 // PartialEq for MyDeque<T>
-impl<T: PartialEq> PartialEq for MyDeque<T> {
+impl<T: PartialEq, A: Allocator> PartialEq for MyDeque<T, A> {
     fn eq(&self, other: &Self) -> bool {
         if self.len != other.len {
             return false;
@@ -568,10 +773,11 @@ impl<T: PartialEq> PartialEq for MyDeque<T> {
 }
 
 // Eq for MyDeque<T>
-impl<T: Eq> Eq for MyDeque<T> {}
+impl<T: Eq, A: Allocator> Eq for MyDeque<T, A> {}
 
 // Extend for MyDeque<T>
-impl<T> Extend<T> for MyDeque<T> {
+impl<T, A: Allocator> Extend<T> for MyDeque<T, A> {
+    #[track_caller]
     fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
         for val in iter {
             self.push_back(val);
@@ -613,8 +819,8 @@ impl<T: Clone> From<&[T]> for MyDeque<T> {
 }
 
 // Debug for MyDeque<T>
-impl<T: Debug> Debug for MyDeque<T> {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+impl<T: Debug, A: Allocator> Debug for MyDeque<T, A> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         f.debug_struct("MyDeque")
             .field("buf", &self.buf)
             .field("head", &self.head)
@@ -624,16 +830,6 @@ impl<T: Debug> Debug for MyDeque<T> {
     }
 }
 
-// Debug for RawVec<T>
-impl<T: Debug> Debug for RawVec<T> {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        f.debug_struct("RawVec")
-            .field("ptr", &self.ptr)
-            .field("cap", &self.cap)
-            .finish()
-    }
-}
-
 // =====================
 // Tests
 // =====================
@@ -882,6 +1078,7 @@ mod tests {
 
     #[test]
     fn test_drop() {
+        use crate::tracking::Tracking;
         use std::sync::{Arc, Mutex};
         struct DropCounter(Arc<Mutex<usize>>);
         impl Drop for DropCounter {
@@ -891,14 +1088,16 @@ mod tests {
             }
         }
         let counter = Arc::new(Mutex::new(0));
+        let tracker = Tracking::new();
         {
-            let mut deque = MyDeque::new();
+            let mut deque = MyDeque::new_in(&tracker);
             for _ in 0..5 {
                 deque.push_back(DropCounter(counter.clone()));
             }
             // When deque goes out of scope, all DropCounter should be dropped
         }
         assert_eq!(*counter.lock().unwrap(), 5);
+        assert_eq!(tracker.live_bytes(), 0);
     }
 
     #[test]
@@ -969,8 +1168,235 @@ mod tests {
             // Only consume part of the iterator
             let mut iter = deque.into_iter();
             let _ = iter.next(); // consume one
-                                 // When iter is dropped, remaining elements should be dropped
+            // When iter is dropped, remaining elements should be dropped
         }
         assert_eq!(*counter.lock().unwrap(), 4);
     }
-}
\ No newline at end of file
+
+    fn wrapped_deque() -> MyDeque<i32> {
+        // Head ends up in the middle of the buffer so the contents wrap
+        let mut deque = MyDeque::with_capacity(4);
+        deque.push_back(3);
+        deque.push_back(4);
+        deque.push_front(2);
+        deque.push_front(1);
+        assert_eq!(deque.capacity(), 4);
+        assert_ne!(deque.head, 0);
+        deque
+    }
+
+    #[test]
+    fn test_grow_while_wrapped() {
+        let mut deque = wrapped_deque();
+        deque.push_back(5);
+        deque.push_front(0);
+
+        assert!(deque.capacity() > 4);
+        let collected: Vec<_> = (&deque).into_iter().copied().collect();
+        assert_eq!(collected, vec![0, 1, 2, 3, 4, 5]);
+    }
+
+    #[test]
+    fn test_iterators_while_wrapped() {
+        let mut deque = wrapped_deque();
+        assert_eq!((&deque).into_iter().count(), 4);
+
+        for val in &mut deque {
+            *val *= 10;
+        }
+        let collected: Vec<_> = deque.into_iter().collect();
+        assert_eq!(collected, vec![10, 20, 30, 40]);
+    }
+
+    #[test]
+    fn test_reserve() {
+        let mut deque = wrapped_deque();
+        deque.reserve(10);
+        assert!(deque.capacity() >= 14);
+
+        deque.reserve_exact(20);
+        assert!(deque.capacity() >= 24);
+
+        let collected: Vec<_> = (&deque).into_iter().copied().collect();
+        assert_eq!(collected, vec![1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn test_shrink_to_fit() {
+        let mut deque = MyDeque::with_capacity(16);
+        for i in 0..10 {
+            deque.push_back(i);
+        }
+        for _ in 0..6 {
+            deque.pop_front();
+        }
+        deque.push_back(10);
+        deque.push_front(5);
+
+        deque.shrink_to_fit();
+        assert_eq!(deque.capacity(), 6);
+        let collected: Vec<_> = (&deque).into_iter().copied().collect();
+        assert_eq!(collected, vec![5, 6, 7, 8, 9, 10]);
+
+        deque.clear();
+        deque.shrink_to_fit();
+        assert_eq!(deque.capacity(), 0);
+        deque.push_back(1);
+        assert_eq!(deque.pop_front(), Some(1));
+    }
+
+    #[test]
+    fn test_shrink_to_while_wrapped() {
+        let mut deque = wrapped_deque();
+        deque.reserve_exact(12);
+        deque.pop_back();
+
+        deque.shrink_to(5);
+        assert_eq!(deque.capacity(), 5);
+        let collected: Vec<_> = (&deque).into_iter().copied().collect();
+        assert_eq!(collected, vec![1, 2, 3]);
+    }
+
+    #[derive(Debug, Clone, PartialEq)]
+    struct Marker;
+
+    #[test]
+    fn test_zst_push_pop() {
+        let mut deque = MyDeque::new();
+        assert_eq!(deque.capacity(), usize::MAX);
+
+        for _ in 0..50 {
+            deque.push_front(Marker);
+            deque.push_back(Marker);
+        }
+        assert_eq!(deque.len(), 100);
+        assert_eq!(deque.capacity(), usize::MAX);
+        assert_eq!(deque.get(99), Some(&Marker));
+        assert_eq!(deque.peek_front(), Some(&Marker));
+        assert_eq!(deque.peek_back(), Some(&Marker));
+
+        assert_eq!(deque.pop_back(), Some(Marker));
+        assert_eq!(deque.pop_front(), Some(Marker));
+        assert_eq!(deque.len(), 98);
+
+        deque.reserve(10);
+        deque.shrink_to_fit();
+        assert_eq!(deque.capacity(), usize::MAX);
+        assert_eq!(deque.len(), 98);
+    }
+
+    #[test]
+    fn test_zst_iterators_and_drop() {
+        use std::cell::Cell;
+
+        thread_local!(static DROPS: Cell<usize> = const { Cell::new(0) });
+        struct DropZst;
+        impl Drop for DropZst {
+            fn drop(&mut self) {
+                DROPS.with(|d| d.set(d.get() + 1));
+            }
+        }
+
+        let mut deque = MyDeque::with_capacity(3);
+        for _ in 0..3 {
+            deque.push_front(DropZst);
+        }
+        assert_eq!((&deque).into_iter().count(), 3);
+        assert_eq!((&mut deque).into_iter().count(), 3);
+
+        let mut iter = deque.into_iter();
+        drop(iter.next());
+        drop(iter);
+        assert_eq!(DROPS.with(Cell::get), 3);
+
+        let mut deque = MyDeque::new();
+        deque.push_back(DropZst);
+        deque.push_front(DropZst);
+        deque.clear();
+        assert!(deque.is_empty());
+        deque.push_back(DropZst);
+        drop(deque);
+        assert_eq!(DROPS.with(Cell::get), 6);
+    }
+
+    #[test]
+    fn test_try_push_and_reserve() {
+        let mut deque = MyDeque::try_with_capacity(2).unwrap();
+        deque.try_push_back(2).unwrap();
+        deque.try_push_front(1).unwrap();
+        deque.try_push_back(3).unwrap();
+        deque.try_push_front(0).unwrap();
+
+        deque.try_reserve(8).unwrap();
+        assert!(deque.capacity() >= 12);
+        deque.try_reserve_exact(16).unwrap();
+        assert!(deque.capacity() >= 20);
+
+        let collected: Vec<_> = deque.into_iter().collect();
+        assert_eq!(collected, vec![0, 1, 2, 3]);
+    }
+
+    #[test]
+    fn test_try_reserve_errors() {
+        let mut deque = wrapped_deque();
+        assert_eq!(
+            deque.try_reserve(usize::MAX),
+            Err(TryReserveError::CapacityOverflow)
+        );
+        assert_eq!(
+            deque.try_reserve_exact(usize::MAX / 2),
+            Err(TryReserveError::CapacityOverflow)
+        );
+        assert!(MyDeque::<u64>::try_with_capacity(usize::MAX).is_err());
+
+        // Nothing moved on failure
+        assert_eq!(deque.capacity(), 4);
+        let collected: Vec<_> = deque.into_iter().collect();
+        assert_eq!(collected, vec![1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn test_make_contiguous() {
+        let mut deque = wrapped_deque();
+        assert_eq!(deque.make_contiguous(), &[1, 2, 3, 4]);
+        assert_eq!((deque.head, deque.tail), (0, 0));
+        assert_eq!(deque.capacity(), 4);
+
+        // Still a working ring afterwards
+        deque.pop_front();
+        deque.push_back(5);
+        let collected: Vec<_> = deque.into_iter().collect();
+        assert_eq!(collected, vec![2, 3, 4, 5]);
+
+        // Wrapped with free slots between the segments
+        let mut deque = MyDeque::with_capacity(8);
+        deque.extend([3, 4]);
+        deque.push_front(2);
+        deque.push_front(1);
+        deque.push_front(0);
+        assert_eq!(deque.make_contiguous(), &[0, 1, 2, 3, 4]);
+        assert_eq!((deque.head, deque.tail), (0, 5));
+    }
+
+    #[test]
+    fn test_sort_across_the_wrap() {
+        let mut deque = wrapped_deque();
+        deque.sort_by_key(|x| std::cmp::Reverse(*x));
+        assert_eq!(deque.make_contiguous(), &[4, 3, 2, 1]);
+
+        let mut deque = wrapped_deque();
+        deque.push_front(9);
+        deque.sort_unstable();
+        let (low, mid, high) = deque.select_nth_unstable(2);
+        assert_eq!((&*low, *mid, &*high), (&[1, 2][..], 3, &[4, 9][..]));
+    }
+
+    #[test]
+    fn test_send_sync_bounds() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<MyDeque<String>>();
+        assert_send_sync::<MyDequeIter<'_, String>>();
+        assert_send_sync::<MutMyDequeIter<'_, String>>();
+        assert_send_sync::<MyDequeIntoIter<String>>();
+    }
+}
@@ -1,14 +1,19 @@
-use std::cmp::Ordering;
-use std::fmt;
-use std::fmt::Debug;
-use std::hash::{Hash, Hasher};
-use std::marker::PhantomData;
-use std::ptr::NonNull;
-
-pub struct LinkedList<T> {
+use alloc::alloc::handle_alloc_error;
+use core::alloc::Layout;
+use core::cmp::Ordering;
+use core::fmt;
+use core::fmt::Debug;
+use core::hash::{Hash, Hasher};
+use core::marker::PhantomData;
+use core::ptr::{self, NonNull};
+
+use crate::allocator::{Allocator, Global};
+
+pub struct LinkedList<T, A: Allocator = Global> {
     front: Link<T>,
     back: Link<T>,
     len: usize,
+    alloc: A,
     _boo: PhantomData<T>,
 }
 
@@ -34,28 +39,66 @@ pub struct IterMut<'a, T> {
     _boo: PhantomData<&'a mut T>,
 }
 
-pub struct IntoIter<T> {
-    list: LinkedList<T>,
+pub struct IntoIter<T, A: Allocator = Global> {
+    list: LinkedList<T, A>,
 }
 
 impl<T> LinkedList<T> {
     pub fn new() -> Self {
+        Self::new_in(Global)
+    }
+}
+
+impl<T, A: Allocator> LinkedList<T, A> {
+    /// Creates an empty list whose nodes will be allocated from `alloc`.
+    pub fn new_in(alloc: A) -> Self {
         Self {
             front: None,
             back: None,
             len: 0,
+            alloc,
             _boo: PhantomData,
         }
     }
 
-    pub fn push_front(&mut self, elem: T) {
-        // SAFETY: it's a linked-list, what do you want?
+    /// The allocator the nodes live in.
+    pub fn allocator(&self) -> &A {
+        &self.alloc
+    }
+
+    #[track_caller]
+    fn alloc_node(&self, elem: T) -> NonNull<Node<T>> {
+        let layout = Layout::new::<Node<T>>();
+        let node = self
+            .alloc
+            .allocate(layout)
+            .unwrap_or_else(|_| handle_alloc_error(layout))
+            .cast::<Node<T>>();
         unsafe {
-            let new = NonNull::new_unchecked(Box::into_raw(Box::new(Node {
+            node.as_ptr().write(Node {
                 front: None,
                 back: None,
                 elem,
-            })));
+            });
+        }
+        node
+    }
+
+    // Moves the node out of its allocation and hands the memory back.
+    unsafe fn free_node(&self, node: NonNull<Node<T>>) -> Node<T> {
+        unsafe {
+            let value = ptr::read(node.as_ptr());
+            self.alloc
+                .deallocate(node.cast::<u8>(), Layout::new::<Node<T>>());
+            value
+        }
+    }
+
+    #[track_caller]
+    pub fn push_front(&mut self, elem: T) {
+        // SAFETY: it's a linked-list, what do you want?
+        unsafe {
+            let new = self.alloc_node(elem);
             if let Some(old) = self.front {
                 // Put the new front before the old one
                 (*old.as_ptr()).front = Some(new);
@@ -71,14 +114,11 @@ impl<T> LinkedList<T> {
         }
     }
 
+    #[track_caller]
     pub fn push_back(&mut self, elem: T) {
         // SAFETY: it's a linked-list, what do you want?
         unsafe {
-            let new = NonNull::new_unchecked(Box::into_raw(Box::new(Node {
-                back: None,
-                front: None,
-                elem,
-            })));
+            let new = self.alloc_node(elem);
             if let Some(old) = self.back {
                 // Put the new back before the old one
                 (*old.as_ptr()).back = Some(new);
@@ -98,9 +138,9 @@ impl<T> LinkedList<T> {
         unsafe {
             // Only have to do stuff if there is a front node to pop.
             self.front.map(|node| {
-                // Bring the Box back to life so we can move out its value and
-                // Drop it (Box continues to magically understand this for us).
-                let boxed_node = Box::from_raw(node.as_ptr());
+                // Move the node out of the allocator so we can take its value,
+                // the memory itself goes straight back.
+                let boxed_node = self.free_node(node);
                 let result = boxed_node.elem;
 
                 // Make the next node into the new front.
@@ -115,7 +155,6 @@ impl<T> LinkedList<T> {
 
                 self.len -= 1;
                 result
-                // Box gets implicitly freed here, knows there is no T.
             })
         }
     }
@@ -124,9 +163,9 @@ impl<T> LinkedList<T> {
         unsafe {
             // Only have to do stuff if there is a back node to pop.
             self.back.map(|node| {
-                // Bring the Box front to life so we can move out its value and
-                // Drop it (Box continues to magically understand this for us).
-                let boxed_node = Box::from_raw(node.as_ptr());
+                // Move the node out of the allocator so we can take its value,
+                // the memory itself goes straight back.
+                let boxed_node = self.free_node(node);
                 let result = boxed_node.elem;
 
                 // Make the next node into the new back.
@@ -141,7 +180,6 @@ impl<T> LinkedList<T> {
 
                 self.len -= 1;
                 result
-                // Box gets implicitly freed here, knows there is no T.
             })
         }
     }
@@ -166,7 +204,7 @@ impl<T> LinkedList<T> {
         self.len
     }
 
-    pub fn iter(&self) -> Iter<T> {
+    pub fn iter(&self) -> Iter<'_, T> {
         Iter {
             front: self.front,
             back: self.back,
@@ -175,7 +213,7 @@ impl<T> LinkedList<T> {
         }
     }
 
-    pub fn iter_mut(&mut self) -> IterMut<T> {
+    pub fn iter_mut(&mut self) -> IterMut<'_, T> {
         IterMut {
             front: self.front,
             back: self.back,
@@ -184,22 +222,18 @@ impl<T> LinkedList<T> {
         }
     }
 
-    pub fn into_iter(self) -> IntoIter<T> {
-        IntoIter { list: self }
-    }
-
     pub fn is_empty(&self) -> bool {
         self.len == 0
     }
 
     pub fn clear(&mut self) {
         // Oh look it's drop again
-        while let Some(_) = self.pop_front() {}
+        while self.pop_front().is_some() {}
     }
 }
 
-unsafe impl<T: Send> Send for LinkedList<T> {}
-unsafe impl<T: Sync> Sync for LinkedList<T> {}
+unsafe impl<T: Send, A: Allocator + Send> Send for LinkedList<T, A> {}
+unsafe impl<T: Sync, A: Allocator + Sync> Sync for LinkedList<T, A> {}
 
 unsafe impl<'a, T: Send> Send for Iter<'a, T> {}
 unsafe impl<'a, T: Sync> Sync for Iter<'a, T> {}
@@ -213,9 +247,10 @@ impl<T> Default for LinkedList<T> {
     }
 }
 
-impl<T: Clone> Clone for LinkedList<T> {
+impl<T: Clone, A: Allocator + Clone> Clone for LinkedList<T, A> {
+    #[track_caller]
     fn clone(&self) -> Self {
-        let mut new_list = Self::new();
+        let mut new_list = Self::new_in(self.alloc.clone());
         for item in self {
             new_list.push_back(item.clone());
         }
@@ -223,7 +258,8 @@ impl<T: Clone> Clone for LinkedList<T> {
     }
 }
 
-impl<T> Extend<T> for LinkedList<T> {
+impl<T, A: Allocator> Extend<T> for LinkedList<T, A> {
+    #[track_caller]
     fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
         for item in iter {
             self.push_back(item);
@@ -239,37 +275,33 @@ impl<T> FromIterator<T> for LinkedList<T> {
     }
 }
 
-impl<T: Debug> Debug for LinkedList<T> {
+impl<T: Debug, A: Allocator> Debug for LinkedList<T, A> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.debug_list().entries(self).finish()
     }
 }
 
-impl<T: PartialEq> PartialEq for LinkedList<T> {
+impl<T: PartialEq, A: Allocator> PartialEq for LinkedList<T, A> {
     fn eq(&self, other: &Self) -> bool {
         self.len() == other.len() && self.iter().eq(other)
     }
-
-    fn ne(&self, other: &Self) -> bool {
-        self.len() != other.len() || self.iter().ne(other)
-    }
 }
 
-impl<T: Eq> Eq for LinkedList<T> {}
+impl<T: Eq, A: Allocator> Eq for LinkedList<T, A> {}
 
-impl<T: PartialOrd> PartialOrd for LinkedList<T> {
+impl<T: PartialOrd, A: Allocator> PartialOrd for LinkedList<T, A> {
     fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
         self.iter().partial_cmp(other)
     }
 }
 
-impl<T: Ord> Ord for LinkedList<T> {
+impl<T: Ord, A: Allocator> Ord for LinkedList<T, A> {
     fn cmp(&self, other: &Self) -> Ordering {
         self.iter().cmp(other)
     }
 }
 
-impl<T: Hash> Hash for LinkedList<T> {
+impl<T: Hash, A: Allocator> Hash for LinkedList<T, A> {
     fn hash<H: Hasher>(&self, state: &mut H) {
         self.len().hash(state);
         for item in self {
@@ -278,14 +310,14 @@ impl<T: Hash> Hash for LinkedList<T> {
     }
 }
 
-impl<T> Drop for LinkedList<T> {
+impl<T, A: Allocator> Drop for LinkedList<T, A> {
     fn drop(&mut self) {
         // Pop until we have to stop
-        while let Some(_) = self.pop_front() {}
+        while self.pop_front().is_some() {}
     }
 }
 
-impl<'a, T> IntoIterator for &'a LinkedList<T> {
+impl<'a, T, A: Allocator> IntoIterator for &'a LinkedList<T, A> {
     type IntoIter = Iter<'a, T>;
     type Item = &'a T;
 
@@ -338,7 +370,7 @@ impl<'a, T> ExactSizeIterator for Iter<'a, T> {
     }
 }
 
-impl<'a, T> IntoIterator for &'a mut LinkedList<T> {
+impl<'a, T, A: Allocator> IntoIterator for &'a mut LinkedList<T, A> {
     type IntoIter = IterMut<'a, T>;
     type Item = &'a mut T;
 
@@ -391,16 +423,16 @@ impl<'a, T> ExactSizeIterator for IterMut<'a, T> {
     }
 }
 
-impl<T> IntoIterator for LinkedList<T> {
-    type IntoIter = IntoIter<T>;
+impl<T, A: Allocator> IntoIterator for LinkedList<T, A> {
+    type IntoIter = IntoIter<T, A>;
     type Item = T;
 
     fn into_iter(self) -> Self::IntoIter {
-        self.into_iter()
+        IntoIter { list: self }
     }
 }
 
-impl<T> Iterator for IntoIter<T> {
+impl<T, A: Allocator> Iterator for IntoIter<T, A> {
     type Item = T;
 
     fn next(&mut self) -> Option<Self::Item> {
@@ -412,19 +444,26 @@ impl<T> Iterator for IntoIter<T> {
     }
 }
 
-impl<T> DoubleEndedIterator for IntoIter<T> {
+impl<T, A: Allocator> DoubleEndedIterator for IntoIter<T, A> {
     fn next_back(&mut self) -> Option<Self::Item> {
         self.list.pop_back()
     }
 }
 
-impl<T> ExactSizeIterator for IntoIter<T> {
+impl<T, A: Allocator> ExactSizeIterator for IntoIter<T, A> {
     fn len(&self) -> usize {
         self.list.len
     }
 }
 
+// These tests are kept as they were written, some of them on purpose
 #[cfg(test)]
+#[allow(
+    clippy::manual_next_back,
+    clippy::neg_cmp_op_on_partial_ord,
+    clippy::useless_vec,
+    clippy::zero_divided_by_zero
+)]
 mod test {
     use super::LinkedList;
 
@@ -1,20 +1,64 @@
-use std::{cell::Cell, ops::Deref, ptr::NonNull};
-
-pub struct InnerRc<T> {
+use alloc::{
+    alloc::{dealloc, handle_alloc_error},
+    boxed::Box,
+    vec::Vec,
+};
+use core::{
+    alloc::Layout,
+    cell::Cell,
+    marker::PhantomData,
+    mem::{self, ManuallyDrop},
+    ops::Deref,
+    ptr::{self, NonNull},
+};
+
+use crate::{
+    allocator::{Allocator, Global},
+    hooks::{self, EventKind, PointerKind},
+};
+
+/*
+Counts:
+
+- `strong` is the number of MyRc. The value is dropped when it hits 0.
+- `weak` is the number of MyRcWeak, plus one shared by all the MyRc
+  together (same as MyArc). The block is freed when it hits 0, so the
+  last MyRc releasing that implicit weak frees it if no MyRcWeak is left.
+- `MyRcWeak::new()` points at the address usize::MAX instead of a block,
+  which no allocation can have, so it never allocates and never upgrades.
+- Increments are checked. A count wrapping to 0 after enough leaked
+  clones would free the value under the remaining pointers; panicking
+  instead leaves the count as it was.
+
+Unsized values:
+
+- InnerRc is repr(C) with the value last, so a block for a `str`, `[T]`
+  or `dyn Trait` is the two counts followed by the value's own bytes, in
+  one allocation. The pointer to it carries the value's length or vtable.
+- The block's layout is always recomputed from that pointer, never from
+  `Layout::new`.
+*/
+
+#[repr(C)]
+pub struct InnerRc<T: ?Sized> {
+    strong: Cell<usize>,
+    weak: Cell<usize>,
     value: T,
-    count: Cell<usize>,
 }
 
 impl<T> InnerRc<T> {
     fn new(value: T) -> Self {
         Self {
+            strong: Cell::new(1),
+            weak: Cell::new(1),
             value,
-            count: Cell::new(1),
         }
     }
+}
 
+impl<T: ?Sized> InnerRc<T> {
     pub fn get_count(&self) -> usize {
-        self.count.get()
+        self.strong.get()
     }
 
     pub fn get_ref(&self) -> &T {
@@ -22,88 +66,588 @@ impl<T> InnerRc<T> {
     }
 }
 
-struct MyRc<T> {
+// The counts are borrowed field by field, never through `&InnerRc`: the
+// value may not be written yet (`new_cyclic`) or may be mid-drop. The one
+// exception is the last MyRcWeak sizing the block, see its Drop.
+unsafe fn strong<'a, T: ?Sized>(ptr: NonNull<InnerRc<T>>) -> &'a Cell<usize> {
+    unsafe { &(*ptr.as_ptr()).strong }
+}
+
+unsafe fn weak<'a, T: ?Sized>(ptr: NonNull<InnerRc<T>>) -> &'a Cell<usize> {
+    unsafe { &(*ptr.as_ptr()).weak }
+}
+
+// Tells the debug hooks, if there are any
+fn emit<T: ?Sized>(kind: EventKind, ptr: NonNull<InnerRc<T>>, strong_count: usize) {
+    hooks::emit::<T>(kind, PointerKind::Rc, ptr.as_ptr().addr(), strong_count);
+}
+
+fn increment(count: &Cell<usize>) {
+    count.set(count.get().checked_add(1).expect("MyRc count overflow"));
+}
+
+/// Single-threaded: the counts are plain `Cell`s, so MyRc is neither
+/// `Send` nor `Sync` whatever `T` is. Use MyArc to share across threads.
+pub struct MyRc<T: ?Sized, A: Allocator = Global> {
+    ptr: NonNull<InnerRc<T>>,
+    alloc: A,
+    _marker: NotSendSync,
+}
+
+/// A non-owning pointer to a MyRc's value, for back-pointers that mustn't
+/// keep it alive. `upgrade` gives a MyRc back while the value still exists.
+pub struct MyRcWeak<T: ?Sized, A: Allocator = Global> {
     ptr: NonNull<InnerRc<T>>,
+    alloc: A,
+    _marker: NotSendSync,
 }
 
+// Opts out of Send and Sync on its own, so that doesn't hinge on `NonNull`
+type NotSendSync = PhantomData<*const ()>;
+
 impl<T> MyRc<T> {
-    fn new(value: T) -> Self {
-        let inner_ptr =
-            unsafe { NonNull::new_unchecked(Box::into_raw(Box::new(InnerRc::new(value)))) };
-        Self { ptr: inner_ptr }
+    #[track_caller]
+    pub fn new(value: T) -> Self {
+        Self::new_in(value, Global)
+    }
+
+    /// Builds a value that holds a weak pointer to itself. `data_fn` gets
+    /// that pointer before the value exists, so upgrading it in there
+    /// returns `None`.
+    #[track_caller]
+    pub fn new_cyclic<F>(data_fn: F) -> Self
+    where
+        F: FnOnce(&MyRcWeak<T>) -> T,
+    {
+        Self::new_cyclic_in(data_fn, Global)
+    }
+}
+
+impl<T, A: Allocator> MyRc<T, A> {
+    /// Like `new`, but the shared block is allocated from `alloc`.
+    #[track_caller]
+    pub fn new_in(value: T, alloc: A) -> Self {
+        let layout = Layout::new::<InnerRc<T>>();
+        let inner_ptr = alloc
+            .allocate(layout)
+            .unwrap_or_else(|_| handle_alloc_error(layout))
+            .cast::<InnerRc<T>>();
+        unsafe { inner_ptr.as_ptr().write(InnerRc::new(value)) };
+        emit(EventKind::Allocate, inner_ptr, 1);
+        Self {
+            ptr: inner_ptr,
+            alloc,
+            _marker: PhantomData,
+        }
     }
 
-    fn try_unwrap(self) -> Result<T, Self> {
+    /// Like `new_cyclic`, but the shared block is allocated from `alloc`.
+    #[track_caller]
+    pub fn new_cyclic_in<F>(data_fn: F, alloc: A) -> Self
+    where
+        A: Clone,
+        F: FnOnce(&MyRcWeak<T, A>) -> T,
+    {
+        let layout = Layout::new::<InnerRc<T>>();
+        let ptr = alloc
+            .allocate(layout)
+            .unwrap_or_else(|_| handle_alloc_error(layout))
+            .cast::<InnerRc<T>>();
+        // No strong pointer yet, the weak handed to `data_fn` is the only
+        // reference.
+        unsafe {
+            let inner = ptr.as_ptr();
+            (&raw mut (*inner).strong).write(Cell::new(0));
+            (&raw mut (*inner).weak).write(Cell::new(1));
+        }
+        emit(EventKind::Allocate, ptr, 0);
+        let weak = ManuallyDrop::new(MyRcWeak {
+            ptr,
+            alloc: alloc.clone(),
+            _marker: PhantomData,
+        });
+
+        // If `data_fn` panics this releases `weak`. Its own Drop would size
+        // the block through a value that was never written.
+        struct ReleaseOnUnwind<'a, T, A: Allocator>(&'a MyRcWeak<T, A>);
+
+        impl<T, A: Allocator> Drop for ReleaseOnUnwind<'_, T, A> {
+            fn drop(&mut self) {
+                unsafe {
+                    release_weak(self.0.ptr, &self.0.alloc, Layout::new::<InnerRc<T>>);
+                    drop(ptr::read(&self.0.alloc));
+                }
+            }
+        }
+
+        let guard = ReleaseOnUnwind(&weak);
+        let value = data_fn(&weak);
+        mem::forget(guard);
+
+        unsafe {
+            (&raw mut (*ptr.as_ptr()).value).write(value);
+            strong(ptr).set(1);
+        }
+        // Its weak count becomes the one shared by the strong pointers,
+        // only its allocator handle is dropped
+        drop(unsafe { ptr::read(&weak.alloc) });
+        Self {
+            ptr,
+            alloc,
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn try_unwrap(self) -> Result<T, Self> {
         if self.get_count() == 1 {
-            let inner = unsafe { Box::from_raw(self.ptr.as_ptr()) };
-            let value = inner.value;
-            std::mem::forget(self); // prevent drop
-            Ok(value)
+            let this = ManuallyDrop::new(self); // prevent drop
+            unsafe {
+                let value = ptr::read(&this.ptr.as_ref().value);
+                strong(this.ptr).set(0);
+                emit(EventKind::Drop, this.ptr, 0);
+                let alloc = ptr::read(&this.alloc);
+                // Release the strong pointers' weak, weaks left over free the block later
+                release_weak(this.ptr, &alloc, Layout::new::<InnerRc<T>>);
+                Ok(value)
+            }
         } else {
             Err(self)
         }
     }
 
-    fn get_count(&self) -> usize {
-        unsafe { (*self.ptr.as_ptr()).count.get() }
+    /// Like `try_unwrap`, but throws the pointer away when it's shared.
+    pub fn into_inner(self) -> Option<T> {
+        self.try_unwrap().ok()
+    }
+
+    /// The value, moved out if this is the only MyRc and cloned otherwise.
+    pub fn unwrap_or_clone(self) -> T
+    where
+        T: Clone,
+    {
+        self.try_unwrap().unwrap_or_else(|rc| (*rc).clone())
+    }
+
+    /// Clone-on-write access. If other MyRc share the value it's cloned
+    /// into a new block first. If only MyRcWeak point at it, it's moved to
+    /// a new block instead and those weak pointers stop upgrading.
+    #[track_caller]
+    pub fn make_mut(&mut self) -> &mut T
+    where
+        T: Clone,
+        A: Clone,
+    {
+        if self.strong_count() != 1 {
+            *self = Self::new_in((**self).clone(), self.alloc.clone());
+        } else if self.weak_count() != 0 {
+            unsafe {
+                let value = ptr::read(&self.ptr.as_ref().value);
+                strong(self.ptr).set(0);
+                emit(EventKind::Drop, self.ptr, 0);
+                let fresh = Self::new_in(value, self.alloc.clone());
+                // The old block only has weak pointers left, it's theirs to free
+                let old = ManuallyDrop::new(mem::replace(self, fresh));
+                let alloc = ptr::read(&old.alloc);
+                release_weak(old.ptr, &alloc, Layout::new::<InnerRc<T>>);
+            }
+        }
+        unsafe { &mut self.ptr.as_mut().value }
+    }
+
+    /// What [`coerce!`](crate::coerce) expands to.
+    ///
+    /// # Safety
+    ///
+    /// `f` must return the pointer it's given, only unsized to `U`. Anything
+    /// else, like a pointer to a field or to what a wrapper holds, would
+    /// get the block dropped as the wrong type.
+    #[doc(hidden)]
+    pub unsafe fn coerce_unchecked<U: ?Sized>(
+        self,
+        f: impl FnOnce(*const T) -> *const U,
+    ) -> MyRc<U, A> {
+        let this = ManuallyDrop::new(self);
+        let given = unsafe { &raw const (*this.ptr.as_ptr()).value };
+        let value = f(given);
+        debug_assert!(ptr::addr_eq(value, given));
+        // Step back from the value to its block, keeping the metadata the
+        // compiler's unsizing gave `value`
+        let offset = given.addr() - this.ptr.as_ptr().addr();
+        let inner = unsafe { value.byte_sub(offset) } as *mut InnerRc<U>;
+        MyRc {
+            ptr: unsafe { NonNull::new_unchecked(inner) },
+            alloc: unsafe { ptr::read(&this.alloc) },
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<T: ?Sized, A: Allocator> MyRc<T, A> {
+    pub fn allocator(&self) -> &A {
+        &self.alloc
     }
 
-    fn get_value_ref(&self) -> &T {
+    pub fn get_count(&self) -> usize {
+        self.strong_count()
+    }
+
+    /// Number of MyRc pointing at the value.
+    pub fn strong_count(&self) -> usize {
+        unsafe { strong(self.ptr).get() }
+    }
+
+    /// Number of MyRcWeak pointing at the value (not counting the implicit one).
+    pub fn weak_count(&self) -> usize {
+        unsafe { weak(self.ptr).get() - 1 }
+    }
+
+    // Overwrites both counts, `weak` including the implicit one, so tests can
+    // start next to usize::MAX instead of leaking that many clones. Put the
+    // real counts back before dropping.
+    #[cfg(test)]
+    fn with_counts(self, strong_count: usize, weak_count: usize) -> Self {
+        unsafe {
+            strong(self.ptr).set(strong_count);
+            weak(self.ptr).set(weak_count);
+        }
+        self
+    }
+
+    pub fn get_value_ref(&self) -> &T {
         unsafe { &(*self.ptr.as_ptr()).value }
     }
 
+    /// Only succeeds for the one and only pointer, weak ones included:
+    /// a MyRcWeak could otherwise upgrade and alias the `&mut`.
     pub fn get_mut_ref(&mut self) -> Option<&mut T> {
         unsafe {
-            let inner = self.ptr.as_ref();
-            if inner.count.get() == 1 {
-                Some(&mut (*self.ptr.as_mut()).value)
+            if strong(self.ptr).get() == 1 && weak(self.ptr).get() == 1 {
+                Some(&mut self.ptr.as_mut().value)
             } else {
                 None
             }
         }
     }
+
+    pub fn downgrade(&self) -> MyRcWeak<T, A>
+    where
+        A: Clone,
+    {
+        increment(unsafe { weak(self.ptr) });
+        MyRcWeak {
+            ptr: self.ptr,
+            alloc: self.alloc.clone(),
+            _marker: PhantomData,
+        }
+    }
 }
 
-impl<T> Clone for MyRc<T> {
-    fn clone(&self) -> Self {
+// Drops one weak count, freeing the block when it was the last. `layout`
+// is only asked for then, and mustn't look at the value: it's gone (or was
+// never written).
+unsafe fn release_weak<T: ?Sized, A: Allocator>(
+    ptr: NonNull<InnerRc<T>>,
+    alloc: &A,
+    layout: impl FnOnce() -> Layout,
+) {
+    unsafe {
+        let weak = weak(ptr);
+        weak.set(weak.get() - 1);
+        if weak.get() == 0 {
+            emit(EventKind::Deallocate, ptr, 0);
+            alloc.deallocate(ptr.cast(), layout());
+        }
+    }
+}
+
+// ==============
+// Unsized values
+// ==============
+
+// A block for a value with `value_layout`, counts set to one and the value
+// left for the caller to write. `to_inner` puts the value's metadata (length
+// or vtable) on the block's address.
+fn allocate_for_layout<T: ?Sized, A: Allocator>(
+    value_layout: Layout,
+    alloc: &A,
+    to_inner: impl FnOnce(*mut u8) -> *mut InnerRc<T>,
+) -> NonNull<InnerRc<T>> {
+    let layout = Layout::new::<InnerRc<()>>()
+        .extend(value_layout)
+        .expect("capacity overflow")
+        .0
+        .pad_to_align();
+    let mem = alloc
+        .allocate(layout)
+        .unwrap_or_else(|_| handle_alloc_error(layout));
+    unsafe {
+        let inner = to_inner(mem.as_ptr());
+        (&raw mut (*inner).strong).write(Cell::new(1));
+        (&raw mut (*inner).weak).write(Cell::new(1));
+        let inner = NonNull::new_unchecked(inner);
+        emit(EventKind::Allocate, inner, 1);
+        inner
+    }
+}
+
+// `ptr` moved to `addr`, keeping its metadata (what the unstable
+// `with_metadata_of` does). Only `From<Box<T>>` needs it: the box's metadata
+// has to go onto a pointer into another allocation, and nothing stable does
+// that for a `dyn Trait`. It assumes a wide pointer's first word is the data
+// pointer, which rustc does but doesn't promise.
+pub(crate) unsafe fn with_addr_of<T: ?Sized>(mut ptr: *mut T, addr: NonNull<u8>) -> *mut T {
+    unsafe { (&raw mut ptr).cast::<*mut u8>().write(addr.as_ptr()) };
+    ptr
+}
+
+/// Turns a `MyRc<T>` or `MyArc<T>` into one of an unsized type, keeping
+/// the same block: a `[T; N]` into a `[T]`, or a value into a `dyn Trait`.
+/// This is the coercion std's Rc gets implicitly from the unstable
+/// `CoerceUnsized`:
+///
+/// ```
+/// use core::fmt::Display;
+/// use my_std::{coerce, my_arc::MyArc, my_rc::MyRc};
+///
+/// let shown: MyRc<dyn Display> = coerce!(MyRc::new(7) => dyn Display);
+/// assert_eq!(shown.to_string(), "7");
+/// let slice: MyArc<[u8]> = coerce!(MyArc::new([1, 2, 3]) => [u8]);
+/// assert_eq!(slice.len(), 3);
+/// ```
+///
+/// It goes through the compiler's own pointer unsizing, so nothing else
+/// compiles. In particular not reaching through a wrapper, which would
+/// run a destructor the wrapper was there to control:
+///
+/// ```compile_fail
+/// use core::mem::ManuallyDrop;
+/// use my_std::{coerce, my_rc::MyRc};
+///
+/// let kept = MyRc::new(ManuallyDrop::new([String::new()]));
+/// let _ = coerce!(kept => [String]);
+/// ```
+#[macro_export]
+macro_rules! coerce {
+    ($pointer:expr => $target:ty) => {{
+        let pointer = $pointer;
+        // The closure only type-checks if `*const T` coerces to `*const $target`
+        unsafe { pointer.coerce_unchecked(|value| -> *const $target { value }) }
+    }};
+}
+
+impl<T> MyRc<[T]> {
+    // Moves `len` elements from `src` into a new block
+    unsafe fn copy_from_slice(src: *const T, len: usize) -> Self {
+        let value_layout = Layout::array::<T>(len).expect("capacity overflow");
+        let ptr = allocate_for_layout(value_layout, &Global, |mem| {
+            ptr::slice_from_raw_parts_mut(mem.cast::<T>(), len) as *mut InnerRc<[T]>
+        });
+        unsafe {
+            let dst = (&raw mut (*ptr.as_ptr()).value).cast::<T>();
+            ptr::copy_nonoverlapping(src, dst, len);
+        }
+        MyRc {
+            ptr,
+            alloc: Global,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl From<&str> for MyRc<str> {
+    fn from(s: &str) -> Self {
+        let bytes = unsafe { MyRc::<[u8]>::copy_from_slice(s.as_ptr(), s.len()) };
+        let bytes = ManuallyDrop::new(bytes);
+        // Same block, same length, and the bytes are valid UTF-8
+        MyRc {
+            ptr: unsafe { NonNull::new_unchecked(bytes.ptr.as_ptr() as *mut InnerRc<str>) },
+            alloc: Global,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<T> From<Vec<T>> for MyRc<[T]> {
+    fn from(mut vec: Vec<T>) -> Self {
         unsafe {
-            let inner = self.ptr.as_ref();
-            inner.count.set(inner.count.get() + 1);
+            let rc = Self::copy_from_slice(vec.as_ptr(), vec.len());
+            // The elements moved, only the buffer is left to free
+            vec.set_len(0);
+            rc
         }
-        Self { ptr: self.ptr }
     }
 }
 
-impl<T> Deref for MyRc<T> {
+impl<T: ?Sized> From<Box<T>> for MyRc<T> {
+    fn from(value: Box<T>) -> Self {
+        let value_layout = Layout::for_value(&*value);
+        let raw = Box::into_raw(value);
+        // The box's metadata on the new block, assuming the data pointer is
+        // a wide pointer's first word (see `with_addr_of`)
+        let ptr = allocate_for_layout(value_layout, &Global, |mem| unsafe {
+            with_addr_of(raw as *mut InnerRc<T>, NonNull::new_unchecked(mem))
+        });
+        unsafe {
+            let dst = (&raw mut (*ptr.as_ptr()).value).cast::<u8>();
+            ptr::copy_nonoverlapping(raw.cast::<u8>(), dst, value_layout.size());
+            // The value moved, only the box's memory is left to free
+            if value_layout.size() != 0 {
+                dealloc(raw.cast(), value_layout);
+            }
+        }
+        MyRc {
+            ptr,
+            alloc: Global,
+            _marker: PhantomData,
+        }
+    }
+}
+
+/// Collects into a `Vec` first: the block has to be sized before the
+/// first element goes in, and the iterator's length can't be trusted.
+impl<T> FromIterator<T> for MyRc<[T]> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        Self::from(iter.into_iter().collect::<Vec<T>>())
+    }
+}
+
+impl<T> MyRcWeak<T> {
+    /// A weak pointer to nothing. Doesn't allocate; `upgrade` always fails.
+    pub const fn new() -> Self {
+        Self::new_in(Global)
+    }
+}
+
+impl<T, A: Allocator> MyRcWeak<T, A> {
+    pub const fn new_in(alloc: A) -> Self {
+        MyRcWeak {
+            ptr: unsafe { NonNull::new_unchecked(ptr::without_provenance_mut(usize::MAX)) },
+            alloc,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<T: ?Sized, A: Allocator> MyRcWeak<T, A> {
+    fn is_dangling(&self) -> bool {
+        self.ptr.as_ptr().addr() == usize::MAX
+    }
+
+    // (strong, weak). The counts outlive the value, they stay readable until the last weak goes
+    fn counts(&self) -> Option<(&Cell<usize>, &Cell<usize>)> {
+        if self.is_dangling() {
+            None
+        } else {
+            Some(unsafe { (strong(self.ptr), weak(self.ptr)) })
+        }
+    }
+
+    pub fn upgrade(&self) -> Option<MyRc<T, A>>
+    where
+        A: Clone,
+    {
+        let (strong, _) = self.counts()?;
+        if strong.get() == 0 {
+            return None;
+        }
+        increment(strong);
+        emit(EventKind::Clone, self.ptr, strong.get());
+        Some(MyRc {
+            ptr: self.ptr,
+            alloc: self.alloc.clone(),
+            _marker: PhantomData,
+        })
+    }
+
+    /// Number of MyRc pointing at the value, 0 once it's gone.
+    pub fn strong_count(&self) -> usize {
+        self.counts().map_or(0, |(strong, _)| strong.get())
+    }
+
+    /// Number of MyRcWeak pointing at the value, 0 once it's gone.
+    pub fn weak_count(&self) -> usize {
+        match self.counts() {
+            Some((strong, weak)) if strong.get() > 0 => weak.get() - 1,
+            _ => 0,
+        }
+    }
+}
+
+impl<T: ?Sized, A: Allocator + Clone> Clone for MyRc<T, A> {
+    fn clone(&self) -> Self {
+        let strong = unsafe { strong(self.ptr) };
+        increment(strong);
+        emit(EventKind::Clone, self.ptr, strong.get());
+        Self {
+            ptr: self.ptr,
+            alloc: self.alloc.clone(),
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<T: ?Sized, A: Allocator + Clone> Clone for MyRcWeak<T, A> {
+    fn clone(&self) -> Self {
+        if let Some((_, weak)) = self.counts() {
+            increment(weak);
+        }
+        Self {
+            ptr: self.ptr,
+            alloc: self.alloc.clone(),
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<T> Default for MyRcWeak<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: ?Sized, A: Allocator> Deref for MyRc<T, A> {
     type Target = T;
 
     fn deref(&self) -> &Self::Target {
-        unsafe { &(*self.ptr.as_ref()).value }
+        unsafe { &self.ptr.as_ref().value }
     }
 }
 
-impl<T> Drop for MyRc<T> {
+impl<T: ?Sized, A: Allocator> Drop for MyRc<T, A> {
     fn drop(&mut self) {
         unsafe {
-            let inner = self.ptr.as_ref();
-
-            if inner.count.get() == 0 {
-                panic!("Double drop detected!");
+            let strong = strong(self.ptr);
+            strong.set(strong.get() - 1);
+            emit(EventKind::Drop, self.ptr, strong.get());
+            if strong.get() == 0 {
+                // Sized while the value is still alive
+                let layout = Layout::for_value(self.ptr.as_ref());
+                // Drop the value but keep the block alive for any MyRcWeak
+                ptr::drop_in_place(&raw mut (*self.ptr.as_ptr()).value);
+                release_weak(self.ptr, &self.alloc, || layout);
             }
+        }
+    }
+}
 
-            if inner.count.get() != 1 {
-                inner.count.set(inner.count.get() - 1);
-            } else {
-                drop(Box::from_raw(self.ptr.as_ptr()));
-                println!("Dropped MyRc");
-            }
+impl<T: ?Sized, A: Allocator> Drop for MyRcWeak<T, A> {
+    fn drop(&mut self) {
+        // Only the last MyRcWeak sizes the block, over the dropped value (see
+        // MyWeak's Drop for why that's `for_value` and what it relies on).
+        // `new_cyclic_in` frees its own unwritten block without it.
+        if !self.is_dangling() {
+            let ptr = self.ptr;
+            unsafe { release_weak(ptr, &self.alloc, || Layout::for_value(ptr.as_ref())) };
         }
     }
 }
 
 #[cfg(test)]
 pub mod test {
-    use super::MyRc;
+    use core::cell::{Cell, RefCell};
+
+    use super::{MyRc, MyRcWeak};
+    use crate::{my_vec::MyVec, tracking::Tracking};
 
     #[test]
     fn test_basics() {
@@ -128,16 +672,18 @@ pub mod test {
             }
         }
 
+        let tracker = Tracking::new();
         {
-            let a = MyRc::new(Tracker("a"));
+            let a = MyRc::new_in(Tracker("a"), &tracker);
 
             let b = a.clone();
-            let c = b.clone();
+            let _c = b.clone();
             assert_eq!(a.get_count(), 3);
             // all dropped here, should print once
         }
 
         // You should see "Dropped Tracker(a)" once in output
+        assert_eq!(tracker.live_bytes(), 0);
     }
 
     #[test]
@@ -152,7 +698,7 @@ pub mod test {
             *value.unwrap() = 100;
         }
 
-        let rc2 = rc.clone();
+        let _rc2 = rc.clone();
         assert_eq!(rc.get_count(), 2);
 
         // Should fail now
@@ -166,4 +712,261 @@ pub mod test {
         let rc = MyRc::new(String::from("hello"));
         assert_eq!(rc.len(), 5); // using Deref to String
     }
+
+    #[test]
+    fn test_weak_upgrade_and_counts() {
+        let rc = MyRc::new(42);
+        assert_eq!((rc.strong_count(), rc.weak_count()), (1, 0));
+
+        let weak = rc.downgrade();
+        let weak2 = weak.clone();
+        assert_eq!((rc.strong_count(), rc.weak_count()), (1, 2));
+        assert_eq!((weak.strong_count(), weak.weak_count()), (1, 2));
+
+        let upgraded = weak.upgrade().unwrap();
+        assert_eq!(*upgraded, 42);
+        assert_eq!(rc.strong_count(), 2);
+
+        drop(upgraded);
+        drop(rc);
+        assert!(weak.upgrade().is_none());
+        assert_eq!((weak.strong_count(), weak.weak_count()), (0, 0));
+        drop(weak2);
+    }
+
+    #[test]
+    fn test_weak_blocks_get_mut_ref() {
+        let mut rc = MyRc::new(1);
+        let weak = rc.downgrade();
+        assert!(rc.get_mut_ref().is_none());
+        drop(weak);
+        *rc.get_mut_ref().unwrap() += 1;
+        assert_eq!(*rc, 2);
+    }
+
+    #[test]
+    fn test_weak_new_does_not_allocate() {
+        let tracker = Tracking::new();
+        let weak: MyRcWeak<String, _> = MyRcWeak::new_in(&tracker);
+        let clone = weak.clone();
+        assert!(clone.upgrade().is_none());
+        assert_eq!((clone.strong_count(), clone.weak_count()), (0, 0));
+        drop((weak, clone));
+        assert_eq!(tracker.allocation_count(), 0);
+
+        assert!(MyRcWeak::<u8>::default().upgrade().is_none());
+    }
+
+    #[test]
+    fn test_no_leaks_through_weak_paths() {
+        let tracker = Tracking::new();
+
+        // Last MyRc goes first, the MyRcWeak frees the block
+        let weak = MyRc::new_in(String::from("weak outlives"), &tracker).downgrade();
+        assert!(weak.upgrade().is_none());
+        assert!(tracker.live_bytes() > 0);
+        drop(weak);
+        assert_eq!(tracker.live_bytes(), 0);
+
+        // try_unwrap leaves the block to the remaining weak
+        let rc = MyRc::new_in(String::from("unwrapped"), &tracker);
+        let weak = rc.downgrade();
+        assert_eq!(rc.try_unwrap().ok().as_deref(), Some("unwrapped"));
+        assert!(weak.upgrade().is_none());
+        drop(weak);
+        assert_eq!(tracker.live_bytes(), 0);
+    }
+
+    struct Node {
+        parent: MyRcWeak<Node>,
+        children: RefCell<MyVec<MyRc<Node>>>,
+        me: MyRcWeak<Node>,
+    }
+
+    #[test]
+    fn test_new_cyclic() {
+        let root = MyRc::new_cyclic(|me| {
+            assert!(me.upgrade().is_none());
+            Node {
+                parent: MyRcWeak::new(),
+                children: RefCell::new(MyVec::new()),
+                me: me.clone(),
+            }
+        });
+        assert_eq!((root.strong_count(), root.weak_count()), (1, 1));
+        assert!(core::ptr::eq(&*root.me.upgrade().unwrap(), &*root));
+
+        let child = MyRc::new_cyclic(|me| Node {
+            parent: root.me.clone(),
+            children: RefCell::new(MyVec::new()),
+            me: me.clone(),
+        });
+        root.children.borrow_mut().push(child);
+        let child = root.children.borrow()[0].me.clone();
+        assert!(child.upgrade().unwrap().parent.upgrade().is_some());
+
+        // The back-pointers don't keep anything alive
+        drop(root);
+        assert!(child.upgrade().is_none());
+    }
+
+    #[test]
+    fn test_new_cyclic_panic_frees() {
+        let tracker = Tracking::new();
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            MyRc::<String, _>::new_cyclic_in(|_| panic!("no value"), &tracker)
+        }));
+        assert!(result.is_err());
+        assert_eq!(tracker.live_bytes(), 0);
+    }
+
+    #[test]
+    fn test_make_mut() {
+        let tracker = Tracking::new();
+
+        // Unique: no copy
+        let mut rc = MyRc::new_in(String::from("a"), &tracker);
+        rc.make_mut().push('b');
+        assert_eq!(tracker.allocation_count(), 1);
+
+        // Shared: the clone is detached, the other MyRc keeps the old value
+        let other = rc.clone();
+        rc.make_mut().push('c');
+        assert_eq!((&**rc, &**other), ("abc", "ab"));
+        assert_eq!((rc.strong_count(), other.strong_count()), (1, 1));
+        drop(other);
+
+        // Only weaks: the value moves, the weaks are disassociated
+        let weak = rc.downgrade();
+        rc.make_mut().push('d');
+        assert!(weak.upgrade().is_none());
+        assert_eq!(rc.weak_count(), 0);
+        assert_eq!(*rc, "abcd");
+        drop((rc, weak));
+        assert_eq!(tracker.live_bytes(), 0);
+    }
+
+    #[test]
+    fn test_into_inner_and_unwrap_or_clone() {
+        let rc = MyRc::new(String::from("x"));
+        let other = rc.clone();
+        assert_eq!(rc.into_inner(), None);
+        assert_eq!(other.strong_count(), 1);
+
+        let again = other.clone();
+        assert_eq!(other.unwrap_or_clone(), "x");
+        assert_eq!(again.unwrap_or_clone(), "x");
+    }
+
+    #[test]
+    fn test_unsized_from() {
+        let s: MyRc<str> = MyRc::from("interned");
+        let s2 = s.clone();
+        assert_eq!((&*s2, s.strong_count()), ("interned", 2));
+
+        let v: MyRc<[String]> = MyRc::from(vec![String::from("a"), String::from("b")]);
+        assert_eq!(v.len(), 2);
+        assert_eq!(v[1], "b");
+
+        let collected: MyRc<[u64]> = (1..=4).collect();
+        assert_eq!(*collected, [1, 2, 3, 4]);
+
+        let empty: MyRc<[String]> = MyRc::from(Vec::new());
+        let weak = empty.downgrade();
+        assert!(empty.is_empty());
+        drop(empty);
+        assert!(weak.upgrade().is_none());
+
+        let boxed: MyRc<[u8]> = MyRc::from(Box::<[u8]>::from(&b"boxed"[..]));
+        assert_eq!(&*boxed, b"boxed");
+        let unit: MyRc<()> = MyRc::from(Box::new(()));
+        assert_eq!(unit.strong_count(), 1);
+    }
+
+    trait Handler {
+        fn handle(&self, x: u32) -> u32;
+    }
+
+    struct AddN<'a> {
+        n: u32,
+        drops: &'a Cell<u32>,
+    }
+
+    impl Handler for AddN<'_> {
+        fn handle(&self, x: u32) -> u32 {
+            x + self.n
+        }
+    }
+
+    impl Drop for AddN<'_> {
+        fn drop(&mut self) {
+            self.drops.set(self.drops.get() + 1);
+        }
+    }
+
+    #[test]
+    fn test_dyn_values() {
+        let drops = Cell::new(0);
+        {
+            let boxed: Box<dyn Handler + '_> = Box::new(AddN {
+                n: 1,
+                drops: &drops,
+            });
+            let from_box: MyRc<dyn Handler> = MyRc::from(boxed);
+
+            let coerced: MyRc<dyn Handler> = crate::coerce!(MyRc::new(AddN {
+                n: 10,
+                drops: &drops,
+            }) => dyn Handler);
+            let weak = coerced.downgrade();
+            let handlers = [from_box, coerced.clone(), coerced];
+            let total: u32 = handlers.iter().map(|h| h.handle(1)).sum();
+            assert_eq!(total, 2 + 11 + 11);
+            assert_eq!(weak.strong_count(), 2);
+            assert_eq!(drops.get(), 0);
+        }
+        assert_eq!(drops.get(), 2);
+    }
+
+    #[test]
+    fn test_coerce_array_to_slice() {
+        // Anything but unsizing is rejected at compile time, see tests/compile_fail
+        let drops = Cell::new(0);
+        let counted = |n| AddN { n, drops: &drops };
+        let array = MyRc::new([counted(1), counted(2)]);
+        let weak = array.downgrade();
+        let slice: MyRc<[AddN<'_>]> = crate::coerce!(array => [AddN<'_>]);
+        assert_eq!(slice.iter().map(|h| h.handle(0)).sum::<u32>(), 3);
+        drop(slice);
+        assert_eq!(drops.get(), 2);
+        assert!(weak.upgrade().is_none());
+    }
+
+    #[test]
+    fn test_count_overflow_panics() {
+        use std::panic::{AssertUnwindSafe, catch_unwind};
+
+        let tracker = Tracking::new();
+        let rc = MyRc::new_in(String::from("forgotten"), &tracker);
+        let weak = rc.downgrade();
+        let rc = rc.with_counts(usize::MAX, usize::MAX);
+
+        // Every increment refuses and leaves the count alone
+        assert!(catch_unwind(AssertUnwindSafe(|| rc.clone())).is_err());
+        assert!(catch_unwind(AssertUnwindSafe(|| weak.upgrade())).is_err());
+        assert!(catch_unwind(AssertUnwindSafe(|| rc.downgrade())).is_err());
+        assert!(catch_unwind(AssertUnwindSafe(|| weak.clone())).is_err());
+        assert_eq!(rc.strong_count(), usize::MAX);
+        assert_eq!(rc.weak_count(), usize::MAX - 1);
+
+        // One below still works
+        let rc = rc.with_counts(usize::MAX - 1, usize::MAX - 1);
+        let (again, weak_again) = (rc.clone(), weak.clone());
+        assert_eq!(rc.strong_count(), usize::MAX);
+        drop((again, weak_again));
+
+        let rc = rc.with_counts(1, 2);
+        drop((rc, weak));
+        assert_eq!(tracker.live_bytes(), 0);
+    }
 }
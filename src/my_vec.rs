@@ -1,18 +1,29 @@
-use std::{
-    alloc::{self, Layout},
-    collections::btree_map::RangeMut,
+use alloc::vec::Vec;
+use core::{
+    borrow::{Borrow, BorrowMut},
+    cmp::Ordering,
+    hash::{Hash, Hasher},
+    iter::FusedIterator,
     marker::PhantomData,
     mem::{ManuallyDrop, MaybeUninit},
-    ops::{Add, Index, IndexMut, Range, RangeInclusive},
-    path::Iter,
+    ops::{Deref, DerefMut, Index, IndexMut, RangeBounds},
     ptr::{self, NonNull},
-    slice::{from_raw_parts, from_raw_parts_mut},
+    slice::{self, SliceIndex},
 };
 
+use crate::{
+    allocator::{Allocator, Global},
+    drain,
+    raw_vec::{RawVec, TryReserveError},
+    sort,
+};
+
+pub use crate::drain::{Drain, ExtractIf, Splice};
+
 /*
-RawVec<T>:
+RawVec<T> (see raw_vec.rs):
 
-- Low-level memory allocator & manager.
+- Low-level memory allocator & manager, shared with MyDeque.
 - Handles allocation, reallocation, and deallocation.
 - Deals with capacity, pointer math, and unsafe internals.
 
@@ -24,77 +35,196 @@ MyVec<T>:
 */
 
 #[derive(Debug)]
-struct MyVec<T> {
-    data: RawVec<T>,
+pub struct MyVec<T, A: Allocator = Global> {
+    data: RawVec<T, A>,
     len: usize,
 }
 
 impl<T> MyVec<T> {
-    fn new() -> Self {
+    pub fn new() -> Self {
         MyVec {
             data: RawVec::new(),
             len: 0,
         }
     }
 
-    fn push(&mut self, value: T) {
+    /// Creates an empty vector with room for at least `capacity` elements,
+    /// so the first `capacity` pushes never reallocate.
+    #[track_caller]
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self::with_capacity_in(capacity, Global)
+    }
+
+    /// Creates an empty vector with room for `capacity` elements, reporting
+    /// allocation failure instead of aborting.
+    #[track_caller]
+    pub fn try_with_capacity(capacity: usize) -> Result<Self, TryReserveError> {
+        Self::try_with_capacity_in(capacity, Global)
+    }
+}
+
+impl<T, A: Allocator> MyVec<T, A> {
+    /// Creates an empty vector that will allocate from `alloc`.
+    pub fn new_in(alloc: A) -> Self {
+        MyVec {
+            data: RawVec::new_in(alloc),
+            len: 0,
+        }
+    }
+
+    /// Creates an empty vector in `alloc` with room for `capacity` elements.
+    #[track_caller]
+    pub fn with_capacity_in(capacity: usize, alloc: A) -> Self {
+        MyVec {
+            data: RawVec::with_capacity_in(capacity, alloc),
+            len: 0,
+        }
+    }
+
+    #[track_caller]
+    pub fn try_with_capacity_in(capacity: usize, alloc: A) -> Result<Self, TryReserveError> {
+        Ok(MyVec {
+            data: RawVec::try_with_capacity_in(capacity, alloc)?,
+            len: 0,
+        })
+    }
+
+    /// The allocator backing this vector.
+    pub fn allocator(&self) -> &A {
+        &self.data.alloc
+    }
+
+    #[track_caller]
+    pub fn push(&mut self, value: T) {
         if self.len == self.data.cap {
-            self.data.grow();
+            self.data.grow_amortized(self.len, 1);
         }
 
         self.data.write(self.len, value);
         self.len += 1;
     }
 
-    fn pop(&mut self) -> Option<T> {
-        if self.len == 0 {
-            None
-        } else {
-            self.len -= 1;
-            Some(self.data.read_last(self.len))
+    /// Reserves room for at least `additional` more elements. May over-allocate
+    /// to keep pushes amortised O(1).
+    #[track_caller]
+    pub fn reserve(&mut self, additional: usize) {
+        self.data.reserve(self.len, additional);
+    }
+
+    /// Reserves room for exactly `additional` more elements, unless there already is.
+    #[track_caller]
+    pub fn reserve_exact(&mut self, additional: usize) {
+        self.data.reserve_exact(self.len, additional);
+    }
+
+    /// Gives back as much spare capacity as possible.
+    pub fn shrink_to_fit(&mut self) {
+        self.shrink_to(0);
+    }
+
+    /// Shrinks the capacity down to `max(min_capacity, len)`. Does nothing if
+    /// the capacity is already at or below that.
+    pub fn shrink_to(&mut self, min_capacity: usize) {
+        let target = min_capacity.max(self.len);
+        if self.data.cap > target {
+            self.data.shrink_to(target);
         }
     }
 
-    fn get(&self, index: usize) -> Option<&T> {
-        if index >= self.len {
-            None
-        } else {
-            unsafe {
-                let ptr = self.data.ptr.as_ptr().add(index);
-                Some(&*ptr.cast::<T>())
-            }
+    /// The uninitialised slots after the last element, for filling in place
+    /// before a [`set_len`](Self::set_len).
+    pub fn spare_capacity_mut(&mut self) -> &mut [MaybeUninit<T>] {
+        unsafe {
+            slice::from_raw_parts_mut(
+                self.data.ptr.as_ptr().add(self.len),
+                self.data.cap - self.len,
+            )
         }
     }
 
-    // use cast to conver the MaybeUninit into T
-    fn get_mut(&mut self, index: usize) -> Option<&mut T> {
-        if index >= self.len {
+    /// Sets the length without touching the elements.
+    ///
+    /// # Safety
+    ///
+    /// `new_len` must be at most the capacity, and the first `new_len`
+    /// elements must be initialised. Any elements cut off are leaked, not dropped.
+    pub unsafe fn set_len(&mut self, new_len: usize) {
+        debug_assert!(new_len <= self.data.cap);
+        self.len = new_len;
+    }
+
+    /// Tries to reserve room for at least `additional` more elements.
+    #[track_caller]
+    pub fn try_reserve(&mut self, additional: usize) -> Result<(), TryReserveError> {
+        self.data.try_reserve(self.len, additional)
+    }
+
+    /// Tries to reserve room for exactly `additional` more elements.
+    #[track_caller]
+    pub fn try_reserve_exact(&mut self, additional: usize) -> Result<(), TryReserveError> {
+        self.data.try_reserve_exact(self.len, additional)
+    }
+
+    /// Pushes `value`, returning an error instead of aborting if the
+    /// vector has to grow and the allocation fails.
+    #[track_caller]
+    pub fn try_push(&mut self, value: T) -> Result<(), TryReserveError> {
+        if self.len == self.data.cap {
+            self.data.try_reserve(self.len, 1)?;
+        }
+
+        self.data.write(self.len, value);
+        self.len += 1;
+        Ok(())
+    }
+
+    pub fn pop(&mut self) -> Option<T> {
+        if self.len == 0 {
             None
         } else {
-            Some(self.data.read_mut(index))
+            self.len -= 1;
+            Some(self.data.read(self.len))
         }
     }
 
-    fn get_len(&self) -> usize {
+    // Generic like the slice methods, so `get(1..3)` still works through
+    // the inherent method instead of being shadowed by it
+    pub fn get<I: SliceIndex<[T]>>(&self, index: I) -> Option<&I::Output> {
+        self.as_slice().get(index)
+    }
+
+    pub fn get_mut<I: SliceIndex<[T]>>(&mut self, index: I) -> Option<&mut I::Output> {
+        self.as_mut_slice().get_mut(index)
+    }
+
+    pub fn get_len(&self) -> usize {
         self.len
     }
 
-    fn get_capacity(&self) -> usize {
+    pub fn get_capacity(&self) -> usize {
         self.data.cap
     }
 
-    fn as_slice(&self) -> &[T] {
+    // The buffer pointer, length and capacity, without the allocator. The
+    // caller frees the buffer, with a RawVec over the same allocator.
+    pub(crate) fn into_raw_parts(self) -> (NonNull<MaybeUninit<T>>, usize, usize) {
+        let this = ManuallyDrop::new(self);
+        (this.data.ptr, this.len, this.data.cap)
+    }
+
+    pub fn as_slice(&self) -> &[T] {
         self.data.slice(self.len)
     }
 
-    fn as_mut_slice(&mut self) -> &mut [T] {
+    pub fn as_mut_slice(&mut self) -> &mut [T] {
         self.data.slice_mut(self.len)
     }
 
-    fn insert(&mut self, index: usize, value: T) {
+    #[track_caller]
+    pub fn insert(&mut self, index: usize, value: T) {
         assert!(index <= self.len);
         if self.len == self.data.cap {
-            self.data.grow();
+            self.data.grow_amortized(self.len, 1);
         }
 
         self.data.write_pos(index, value, self.len);
@@ -102,16 +232,228 @@ impl<T> MyVec<T> {
         self.len += 1;
     }
 
-    fn remove(&mut self, index: usize) -> T {
+    pub fn remove(&mut self, index: usize) -> T {
         assert!(index < self.len);
         let val = self.data.read(index);
         self.data.remove_pos(index, self.len);
         self.len -= 1;
         val
     }
+
+    /// Removes `range` from the vector, yielding the removed elements.
+    ///
+    /// The tail is moved back once, when the `Drain` is dropped. Elements
+    /// that weren't yielded are dropped at that point too.
+    pub fn drain<R: RangeBounds<usize>>(&mut self, range: R) -> Drain<'_, T> {
+        let range = drain::slice_range(range, self.len);
+        unsafe { Drain::new(self.data.ptr.cast(), &mut self.len, range) }
+    }
+
+    /// Replaces `range` with the items of `replace_with`, yielding the
+    /// removed elements.
+    ///
+    /// The replacements go in when the `Splice` is dropped. The tail moves
+    /// once, unless `replace_with` is longer than the range and has no exact
+    /// size hint, in which case the extra items are buffered first.
+    pub fn splice<R, I>(&mut self, range: R, replace_with: I) -> Splice<'_, I::IntoIter, A>
+    where
+        R: RangeBounds<usize>,
+        I: IntoIterator<Item = T>,
+    {
+        let range = drain::slice_range(range, self.len);
+        let MyVec { data, len } = self;
+        Splice {
+            drain: unsafe { Drain::new(data.ptr.cast(), len, range) },
+            buf: data,
+            inline_cap: 0,
+            replace_with: replace_with.into_iter(),
+        }
+    }
+
+    /// Removes and yields every element `pred` returns `true` for, keeping
+    /// the rest in order.
+    pub fn extract_if<F>(&mut self, pred: F) -> ExtractIf<'_, T, F>
+    where
+        F: FnMut(&mut T) -> bool,
+    {
+        unsafe { ExtractIf::new(self.data.ptr.cast(), &mut self.len, pred) }
+    }
+
+    /// Keeps only the elements `f` returns `true` for.
+    pub fn retain<F>(&mut self, mut f: F)
+    where
+        F: FnMut(&T) -> bool,
+    {
+        self.retain_mut(|elem| f(elem));
+    }
+
+    pub fn retain_mut<F>(&mut self, mut f: F)
+    where
+        F: FnMut(&mut T) -> bool,
+    {
+        self.extract_if(|elem| !f(elem)).for_each(drop);
+    }
+
+    /// Removes consecutive elements that map to the same key.
+    pub fn dedup_by_key<F, K>(&mut self, mut key: F)
+    where
+        F: FnMut(&mut T) -> K,
+        K: PartialEq,
+    {
+        self.dedup_by(|a, b| key(a) == key(b));
+    }
+
+    /// Removes consecutive elements `same_bucket(current, kept)` calls equal.
+    pub fn dedup_by<F>(&mut self, same_bucket: F)
+    where
+        F: FnMut(&mut T, &mut T) -> bool,
+    {
+        unsafe { drain::dedup_by(self.data.ptr.cast::<T>(), &mut self.len, same_bucket) }
+    }
+
+    /// Drops everything past the first `len` elements. The capacity is unchanged.
+    pub fn truncate(&mut self, len: usize) {
+        unsafe { drain::truncate(self.data.ptr.cast::<T>(), &mut self.len, len) }
+    }
+
+    /// Splits off `[at, len)` into a new vector in a clone of the allocator.
+    #[track_caller]
+    pub fn split_off(&mut self, at: usize) -> Self
+    where
+        A: Clone,
+    {
+        assert!(
+            at <= self.len,
+            "`at` split index (is {at}) should be <= len (is {})",
+            self.len
+        );
+        let count = self.len - at;
+        let mut other = MyVec::with_capacity_in(count, self.data.alloc.clone());
+        unsafe {
+            ptr::copy_nonoverlapping(
+                self.data.ptr.as_ptr().add(at),
+                other.data.ptr.as_ptr(),
+                count,
+            );
+        }
+        self.len = at;
+        other.len = count;
+        other
+    }
+
+    /// Moves every element of `other` onto the end of `self`, leaving it empty.
+    #[track_caller]
+    pub fn append(&mut self, other: &mut Self) {
+        let count = other.len;
+        self.data.reserve(self.len, count);
+        unsafe {
+            ptr::copy_nonoverlapping(
+                other.data.ptr.as_ptr(),
+                self.data.ptr.as_ptr().add(self.len),
+                count,
+            );
+        }
+        other.len = 0;
+        self.len += count;
+    }
+
+    /// Stable sort. An adaptive merge sort that takes its scratch buffer
+    /// from this vector's allocator.
+    pub fn sort(&mut self)
+    where
+        T: Ord,
+    {
+        self.sort_by(T::cmp);
+    }
+
+    pub fn sort_by<F>(&mut self, mut compare: F)
+    where
+        F: FnMut(&T, &T) -> Ordering,
+    {
+        let v = unsafe { slice::from_raw_parts_mut(self.data.ptr.as_ptr().cast::<T>(), self.len) };
+        sort::merge_sort(
+            v,
+            &mut |a, b| compare(a, b) == Ordering::Less,
+            &self.data.alloc,
+        );
+    }
+
+    pub fn sort_by_key<K, F>(&mut self, mut f: F)
+    where
+        F: FnMut(&T) -> K,
+        K: Ord,
+    {
+        self.sort_by(|a, b| f(a).cmp(&f(b)));
+    }
+
+    /// Unstable sort (pattern-defeating quicksort). Doesn't allocate.
+    pub fn sort_unstable(&mut self)
+    where
+        T: Ord,
+    {
+        self.sort_unstable_by(T::cmp);
+    }
+
+    pub fn sort_unstable_by<F>(&mut self, mut compare: F)
+    where
+        F: FnMut(&T, &T) -> Ordering,
+    {
+        sort::quicksort(self, &mut |a, b| compare(a, b) == Ordering::Less);
+    }
+
+    pub fn sort_unstable_by_key<K, F>(&mut self, mut f: F)
+    where
+        F: FnMut(&T) -> K,
+        K: Ord,
+    {
+        self.sort_unstable_by(|a, b| f(a).cmp(&f(b)));
+    }
+
+    /// Moves the element that belongs at `index` in sorted order there,
+    /// returning the smaller-or-equal elements before it and the
+    /// greater-or-equal ones after it.
+    ///
+    /// # Panics
+    ///
+    /// If `index >= len`.
+    pub fn select_nth_unstable(&mut self, index: usize) -> (&mut [T], &mut T, &mut [T])
+    where
+        T: Ord,
+    {
+        self.select_nth_unstable_by(index, T::cmp)
+    }
+
+    pub fn select_nth_unstable_by<F>(
+        &mut self,
+        index: usize,
+        mut compare: F,
+    ) -> (&mut [T], &mut T, &mut [T])
+    where
+        F: FnMut(&T, &T) -> Ordering,
+    {
+        sort::select_nth_unstable(self, index, &mut |a, b| compare(a, b) == Ordering::Less)
+    }
+
+    pub fn select_nth_unstable_by_key<K, F>(
+        &mut self,
+        index: usize,
+        mut f: F,
+    ) -> (&mut [T], &mut T, &mut [T])
+    where
+        F: FnMut(&T) -> K,
+        K: Ord,
+    {
+        self.select_nth_unstable_by(index, |a, b| f(a).cmp(&f(b)))
+    }
+}
+
+impl<T> Default for MyVec<T> {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
-impl<T> Drop for MyVec<T> {
+impl<T, A: Allocator> Drop for MyVec<T, A> {
     fn drop(&mut self) {
         // Drop each initialized element (in reverse for safety)
         for i in (0..self.len).rev() {
@@ -123,137 +465,434 @@ impl<T> Drop for MyVec<T> {
     }
 }
 
-impl<T> Clone for MyVec<T>
+impl<T, A> Clone for MyVec<T, A>
 where
     T: Clone,
+    A: Allocator + Clone,
 {
+    #[track_caller]
     fn clone(&self) -> Self {
-        let mut new = MyVec::new();
-        for i in 0..self.len {
-            let val = self.data.read(i); // move out temporarily
-            new.push(val.clone());
-            std::mem::forget(val); // prevent double-drop
+        // If a `clone` panics, `new` drops only the clones made so far
+        let mut new = MyVec::with_capacity_in(self.len, self.data.alloc.clone());
+        for item in self.as_slice() {
+            new.push(item.clone());
         }
         new
     }
 }
 
-impl<T> Index<usize> for MyVec<T> {
-    type Output = T;
+impl<T, A: Allocator> Deref for MyVec<T, A> {
+    type Target = [T];
+
+    fn deref(&self) -> &[T] {
+        self.as_slice()
+    }
+}
 
-    fn index(&self, index: usize) -> &Self::Output {
-        self.get(index).expect("index out of bounds")
+impl<T, A: Allocator> DerefMut for MyVec<T, A> {
+    fn deref_mut(&mut self) -> &mut [T] {
+        self.as_mut_slice()
     }
 }
 
-impl<T> IndexMut<usize> for MyVec<T> {
-    fn index_mut(&mut self, index: usize) -> &mut Self::Output {
-        self.get_mut(index).expect("index out of bounds")
+// Anything a slice can be indexed with: usize, every range type, ...
+impl<T, I: SliceIndex<[T]>, A: Allocator> Index<I> for MyVec<T, A> {
+    type Output = I::Output;
+
+    fn index(&self, index: I) -> &Self::Output {
+        Index::index(self.as_slice(), index)
     }
 }
 
-impl<T> Index<Range<usize>> for MyVec<T> {
-    type Output = [T];
+impl<T, I: SliceIndex<[T]>, A: Allocator> IndexMut<I> for MyVec<T, A> {
+    fn index_mut(&mut self, index: I) -> &mut Self::Output {
+        IndexMut::index_mut(self.as_mut_slice(), index)
+    }
+}
 
-    fn index(&self, range: Range<usize>) -> &Self::Output {
-        &self.as_slice()[range]
+impl<T, A: Allocator> AsRef<[T]> for MyVec<T, A> {
+    fn as_ref(&self) -> &[T] {
+        self
     }
 }
 
-impl<T> IndexMut<Range<usize>> for MyVec<T> {
-    fn index_mut(&mut self, range: Range<usize>) -> &mut Self::Output {
-        &mut self.as_mut_slice()[range]
+impl<T, A: Allocator> AsMut<[T]> for MyVec<T, A> {
+    fn as_mut(&mut self) -> &mut [T] {
+        self
     }
 }
 
-impl<T> Index<RangeInclusive<usize>> for MyVec<T> {
-    type Output = [T];
+impl<T, A: Allocator> AsRef<MyVec<T, A>> for MyVec<T, A> {
+    fn as_ref(&self) -> &MyVec<T, A> {
+        self
+    }
+}
 
-    fn index(&self, range: RangeInclusive<usize>) -> &Self::Output {
-        &self.as_slice()[range]
+impl<T, A: Allocator> AsMut<MyVec<T, A>> for MyVec<T, A> {
+    fn as_mut(&mut self) -> &mut MyVec<T, A> {
+        self
     }
 }
 
+impl<T, A: Allocator> Borrow<[T]> for MyVec<T, A> {
+    fn borrow(&self) -> &[T] {
+        self
+    }
+}
+
+impl<T, A: Allocator> BorrowMut<[T]> for MyVec<T, A> {
+    fn borrow_mut(&mut self) -> &mut [T] {
+        self
+    }
+}
+
+// Same set of comparisons std has for Vec, in both directions where it can.
+macro_rules! impl_slice_eq {
+    ([$($vars:tt)*] $lhs:ty, $rhs:ty) => {
+        impl<T, U, $($vars)*> PartialEq<$rhs> for $lhs
+        where
+            T: PartialEq<U>,
+        {
+            fn eq(&self, other: &$rhs) -> bool {
+                self[..] == other[..]
+            }
+        }
+    };
+}
+
+impl_slice_eq! { [A1: Allocator, A2: Allocator] MyVec<T, A1>, MyVec<U, A2> }
+impl_slice_eq! { [A: Allocator] MyVec<T, A>, [U] }
+impl_slice_eq! { [A: Allocator] MyVec<T, A>, &[U] }
+impl_slice_eq! { [A: Allocator] MyVec<T, A>, &mut [U] }
+impl_slice_eq! { [A: Allocator, const N: usize] MyVec<T, A>, [U; N] }
+impl_slice_eq! { [A: Allocator, const N: usize] MyVec<T, A>, &[U; N] }
+impl_slice_eq! { [A: Allocator] MyVec<T, A>, Vec<U> }
+impl_slice_eq! { [A: Allocator] [T], MyVec<U, A> }
+impl_slice_eq! { [A: Allocator] &[T], MyVec<U, A> }
+impl_slice_eq! { [A: Allocator] &mut [T], MyVec<U, A> }
+impl_slice_eq! { [A: Allocator] Vec<T>, MyVec<U, A> }
+
+impl<T: Eq, A: Allocator> Eq for MyVec<T, A> {}
+
+impl<T: PartialOrd, A1: Allocator, A2: Allocator> PartialOrd<MyVec<T, A2>> for MyVec<T, A1> {
+    fn partial_cmp(&self, other: &MyVec<T, A2>) -> Option<Ordering> {
+        PartialOrd::partial_cmp(&**self, &**other)
+    }
+}
+
+impl<T: Ord, A: Allocator> Ord for MyVec<T, A> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        Ord::cmp(&**self, &**other)
+    }
+}
+
+// Hashes like the slice does, so it matches `Vec<T>` and `[T]` for Borrow lookups.
+impl<T: Hash, A: Allocator> Hash for MyVec<T, A> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        Hash::hash(&**self, state)
+    }
+}
+
+impl<T, A: Allocator> Extend<T> for MyVec<T, A> {
+    #[track_caller]
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        let iter = iter.into_iter();
+        let (lower, _) = iter.size_hint();
+        self.data.reserve(self.len, lower);
+        for item in iter {
+            self.push(item);
+        }
+    }
+}
+
+impl<'a, T: Copy + 'a, A: Allocator> Extend<&'a T> for MyVec<T, A> {
+    #[track_caller]
+    fn extend<I: IntoIterator<Item = &'a T>>(&mut self, iter: I) {
+        self.extend(iter.into_iter().copied());
+    }
+}
+
+impl<T> FromIterator<T> for MyVec<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut vec = MyVec::new();
+        vec.extend(iter);
+        vec
+    }
+}
+
+impl<T> From<Vec<T>> for MyVec<T> {
+    fn from(mut vec: Vec<T>) -> Self {
+        let len = vec.len();
+        let mut new = MyVec::with_capacity(len);
+        unsafe {
+            ptr::copy_nonoverlapping(vec.as_ptr(), new.data.ptr.as_ptr().cast::<T>(), len);
+            // The elements are ours now, `vec` only frees its buffer
+            vec.set_len(0);
+        }
+        new.len = len;
+        new
+    }
+}
+
+impl<T, A: Allocator> From<MyVec<T, A>> for Vec<T> {
+    fn from(vec: MyVec<T, A>) -> Self {
+        let mut new = Vec::with_capacity(vec.len);
+        unsafe {
+            ptr::copy_nonoverlapping(vec.data.ptr.as_ptr().cast::<T>(), new.as_mut_ptr(), vec.len);
+            new.set_len(vec.len);
+        }
+        // Only the buffer is left to free
+        let mut vec = ManuallyDrop::new(vec);
+        unsafe { ptr::drop_in_place(&mut vec.data) };
+        new
+    }
+}
+
+impl<T, const N: usize> From<[T; N]> for MyVec<T> {
+    fn from(array: [T; N]) -> Self {
+        let array = ManuallyDrop::new(array);
+        let mut new = MyVec::with_capacity(N);
+        unsafe {
+            ptr::copy_nonoverlapping(array.as_ptr(), new.data.ptr.as_ptr().cast::<T>(), N);
+        }
+        new.len = N;
+        new
+    }
+}
+
+impl<T: Clone> From<&[T]> for MyVec<T> {
+    fn from(slice: &[T]) -> Self {
+        let mut new = MyVec::with_capacity(slice.len());
+        new.extend(slice.iter().cloned());
+        new
+    }
+}
+
+/// Backs `my_vec![elem; n]`.
+#[doc(hidden)]
+pub fn from_elem<T: Clone>(elem: T, n: usize) -> MyVec<T> {
+    let mut vec = MyVec::with_capacity(n);
+    vec.extend(core::iter::repeat_n(elem, n));
+    vec
+}
+
+/// Creates a [`MyVec`] the same way `vec!` creates a `Vec`.
+///
+/// ```
+/// use my_std::my_vec;
+///
+/// let empty: my_std::my_vec::MyVec<i32> = my_vec![];
+/// assert!(empty.is_empty());
+/// assert_eq!(my_vec![1, 2, 3], [1, 2, 3]);
+/// assert_eq!(my_vec![0u8; 4], [0, 0, 0, 0]);
+/// ```
+#[macro_export]
+macro_rules! my_vec {
+    () => {
+        $crate::my_vec::MyVec::new()
+    };
+    ($elem:expr; $n:expr) => {
+        $crate::my_vec::from_elem($elem, $n)
+    };
+    ($($x:expr),+ $(,)?) => {
+        $crate::my_vec::MyVec::from([$($x),+])
+    };
+}
+
+// The iterators count the remaining elements rather than comparing a start
+// and end pointer: for zero-sized T every element lives at the same address.
 pub struct MyVecIter<'a, T> {
     start: *const T,
-    end: *const T,
+    len: usize,
     _marker: PhantomData<&'a T>,
 }
 
+unsafe impl<T: Sync> Send for MyVecIter<'_, T> {}
+unsafe impl<T: Sync> Sync for MyVecIter<'_, T> {}
+
+impl<'a, T> MyVecIter<'a, T> {
+    // Shared with the other contiguous vectors
+    pub(crate) fn new(slice: &'a [T]) -> Self {
+        MyVecIter {
+            start: slice.as_ptr(),
+            len: slice.len(),
+            _marker: PhantomData,
+        }
+    }
+}
+
 impl<'a, T> Iterator for MyVecIter<'a, T> {
     type Item = &'a T;
 
     fn next(&mut self) -> Option<Self::Item> {
-        if self.start == self.end {
+        if self.len == 0 {
             None
         } else {
             let item = unsafe { &*self.start };
             self.start = unsafe { self.start.add(1) };
+            self.len -= 1;
             Some(item)
         }
     }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+
+    // Skips straight to the element instead of walking there
+    fn nth(&mut self, n: usize) -> Option<Self::Item> {
+        let skip = n.min(self.len);
+        self.start = unsafe { self.start.add(skip) };
+        self.len -= skip;
+        self.next()
+    }
+
+    fn count(self) -> usize {
+        self.len
+    }
+}
+
+impl<T> DoubleEndedIterator for MyVecIter<'_, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.len == 0 {
+            None
+        } else {
+            self.len -= 1;
+            Some(unsafe { &*self.start.add(self.len) })
+        }
+    }
+}
+
+impl<T> ExactSizeIterator for MyVecIter<'_, T> {}
+
+impl<T> FusedIterator for MyVecIter<'_, T> {}
+
+impl<T> Clone for MyVecIter<'_, T> {
+    fn clone(&self) -> Self {
+        MyVecIter {
+            start: self.start,
+            len: self.len,
+            _marker: PhantomData,
+        }
+    }
 }
 
-impl<'a, T> IntoIterator for &'a MyVec<T> {
+impl<'a, T, A: Allocator> IntoIterator for &'a MyVec<T, A> {
     type Item = &'a T;
     type IntoIter = MyVecIter<'a, T>;
 
     fn into_iter(self) -> Self::IntoIter {
-        let start = self.data.ptr.as_ptr() as *const T;
-        let end = unsafe { start.add(self.len) };
-
         MyVecIter {
-            start,
-            end,
-            _marker: std::marker::PhantomData,
+            start: self.data.ptr.as_ptr() as *const T,
+            len: self.len,
+            _marker: PhantomData,
         }
     }
 }
 
 pub struct MutMyVecIter<'a, T> {
     start: *mut T,
-    end: *mut T,
-    _marker: PhantomData<&'a T>,
+    len: usize,
+    _marker: PhantomData<&'a mut T>,
+}
+
+unsafe impl<T: Send> Send for MutMyVecIter<'_, T> {}
+unsafe impl<T: Sync> Sync for MutMyVecIter<'_, T> {}
+
+impl<'a, T> MutMyVecIter<'a, T> {
+    pub(crate) fn new(slice: &'a mut [T]) -> Self {
+        MutMyVecIter {
+            start: slice.as_mut_ptr(),
+            len: slice.len(),
+            _marker: PhantomData,
+        }
+    }
 }
 
 impl<'a, T> Iterator for MutMyVecIter<'a, T> {
     type Item = &'a mut T;
 
     fn next(&mut self) -> Option<Self::Item> {
-        if self.start == self.end {
+        if self.len == 0 {
             None
         } else {
             let item = unsafe { self.start.as_mut() };
             self.start = unsafe { self.start.add(1) };
+            self.len -= 1;
             item
         }
     }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+
+    fn nth(&mut self, n: usize) -> Option<Self::Item> {
+        let skip = n.min(self.len);
+        self.start = unsafe { self.start.add(skip) };
+        self.len -= skip;
+        self.next()
+    }
+
+    fn count(self) -> usize {
+        self.len
+    }
+}
+
+impl<T> DoubleEndedIterator for MutMyVecIter<'_, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.len == 0 {
+            None
+        } else {
+            self.len -= 1;
+            unsafe { self.start.add(self.len).as_mut() }
+        }
+    }
 }
 
-impl<'a, T> IntoIterator for &'a mut MyVec<T> {
+impl<T> ExactSizeIterator for MutMyVecIter<'_, T> {}
+
+impl<T> FusedIterator for MutMyVecIter<'_, T> {}
+
+impl<'a, T, A: Allocator> IntoIterator for &'a mut MyVec<T, A> {
     type Item = &'a mut T;
 
     type IntoIter = MutMyVecIter<'a, T>;
 
     fn into_iter(self) -> Self::IntoIter {
-        let start = self.data.ptr.as_ptr() as *mut T;
-        let end = unsafe { start.add(self.len) };
         MutMyVecIter {
-            start,
-            end,
+            start: self.data.ptr.as_ptr() as *mut T,
+            len: self.len,
             _marker: PhantomData,
         }
     }
 }
 
-pub struct MyVecIntoIntoIter<T> {
+// Yields `[index, len)`: `index` moves up from the front, `len` down from the back.
+pub struct MyVecIntoIntoIter<T, A: Allocator = Global> {
     ptr: *const T,
     index: usize,
     len: usize,
-    _buf: RawVec<T>,
+    _buf: RawVec<T, A>,
 }
 
-impl<T> Iterator for MyVecIntoIntoIter<T> {
+unsafe impl<T: Send, A: Allocator + Send> Send for MyVecIntoIntoIter<T, A> {}
+unsafe impl<T: Sync, A: Allocator + Sync> Sync for MyVecIntoIntoIter<T, A> {}
+
+impl<T, A: Allocator> MyVecIntoIntoIter<T, A> {
+    /// The elements that haven't been yielded yet.
+    pub fn as_slice(&self) -> &[T] {
+        unsafe { slice::from_raw_parts(self.ptr.add(self.index), self.len - self.index) }
+    }
+
+    pub fn as_mut_slice(&mut self) -> &mut [T] {
+        unsafe {
+            slice::from_raw_parts_mut(self.ptr.add(self.index) as *mut T, self.len - self.index)
+        }
+    }
+}
+
+impl<T, A: Allocator> Iterator for MyVecIntoIntoIter<T, A> {
     type Item = T;
 
     fn next(&mut self) -> Option<Self::Item> {
@@ -261,16 +900,51 @@ impl<T> Iterator for MyVecIntoIntoIter<T> {
             None
         } else {
             let item = self._buf.read(self.index);
-            self.index = self.index.add(1);
+            self.index += 1;
 
             Some(item)
         }
     }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.len - self.index;
+        (remaining, Some(remaining))
+    }
+
+    // Drops the skipped elements in place rather than reading each one out
+    fn nth(&mut self, n: usize) -> Option<Self::Item> {
+        let skip = n.min(self.len - self.index);
+        let skipped =
+            ptr::slice_from_raw_parts_mut(unsafe { self.ptr.add(self.index) } as *mut T, skip);
+        // Step past them first, so a panicking drop can't drop them again
+        self.index += skip;
+        unsafe { ptr::drop_in_place(skipped) };
+        self.next()
+    }
+
+    fn count(self) -> usize {
+        self.len - self.index
+    }
+}
+
+impl<T, A: Allocator> DoubleEndedIterator for MyVecIntoIntoIter<T, A> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.index == self.len {
+            None
+        } else {
+            self.len -= 1;
+            Some(self._buf.read(self.len))
+        }
+    }
 }
 
-impl<T> IntoIterator for MyVec<T> {
+impl<T, A: Allocator> ExactSizeIterator for MyVecIntoIntoIter<T, A> {}
+
+impl<T, A: Allocator> FusedIterator for MyVecIntoIntoIter<T, A> {}
+
+impl<T, A: Allocator> IntoIterator for MyVec<T, A> {
     type Item = T;
-    type IntoIter = MyVecIntoIntoIter<T>;
+    type IntoIter = MyVecIntoIntoIter<T, A>;
 
     fn into_iter(self) -> Self::IntoIter {
         // Prevent MyVec's Drop as we're dropping it with MyVecIntoIntoIter
@@ -293,115 +967,13 @@ impl<T> IntoIterator for MyVec<T> {
     }
 }
 
-impl<T> Drop for MyVecIntoIntoIter<T> {
-    fn drop(&mut self) {
-        for i in self.index..self.len {
-            unsafe {
-                ptr::drop_in_place(self.ptr.add(i) as *mut T);
-            }
-        }
-    }
-}
-
-#[derive(Debug)]
-struct RawVec<T> {
-    ptr: NonNull<MaybeUninit<T>>,
-    cap: usize,
-}
-
-impl<T> RawVec<T> {
-    fn new() -> Self {
-        let cap = 2;
-        let layout = Layout::array::<MaybeUninit<T>>(cap).unwrap();
-        let ptr = unsafe {
-            let raw_ptr = alloc::alloc(layout) as *mut MaybeUninit<T>;
-            NonNull::new(raw_ptr).unwrap_or_else(|| alloc::handle_alloc_error(layout))
-        };
-
-        Self { ptr, cap }
-    }
-
-    fn grow(&mut self) {
-        let new_cap = self.cap * 2;
-        let new_layout = Layout::array::<MaybeUninit<T>>(new_cap).unwrap();
-        let old_layout = Layout::array::<MaybeUninit<T>>(self.cap).unwrap();
-
-        unsafe {
-            let new_ptr =
-                alloc::realloc(self.ptr.as_ptr() as *mut u8, old_layout, new_layout.size())
-                    as *mut MaybeUninit<T>;
-
-            self.ptr =
-                NonNull::new(new_ptr).unwrap_or_else(|| alloc::handle_alloc_error(new_layout));
-        }
-
-        self.cap = new_cap;
-    }
-
-    fn write(&mut self, index: usize, value: T) {
-        unsafe {
-            (*self.ptr.as_ptr().add(index)).write(value);
-        }
-    }
-
-    fn write_pos(&mut self, index: usize, value: T, len: usize) {
-        unsafe {
-            // so this means copy all the values up the inde
-            ptr::copy(
-                self.ptr.as_ptr().add(index),
-                self.ptr.as_ptr().add(index + 1),
-                len - index,
-            );
-            self.write(index, value);
-        }
-    }
-
-    // Shifts the array left
-    // remove_pos(2,4)
-    // [39,40,209,30]
-    // [39,40,30,_]
-    fn remove_pos(&mut self, index: usize, len: usize) {
-        unsafe {
-            ptr::copy(
-                self.ptr.as_ptr().add(index + 1),
-                self.ptr.as_ptr().add(index),
-                len - index - 1,
-            );
-        }
-    }
-
-    fn read_mut(&mut self, index: usize) -> &mut T {
-        unsafe { &mut *self.ptr.as_ptr().add(index).cast::<T>() }
-    }
-
-    fn read_last(&self, len: usize) -> T {
-        unsafe { ptr::read((*self.ptr.as_ptr().add(len)).as_ptr()) }
-    }
-
-    fn read(&self, index: usize) -> T {
-        unsafe { ptr::read((*self.ptr.as_ptr().add(index)).as_ptr()) }
-    }
-
-    fn slice(&self, len: usize) -> &[T] {
-        unsafe { from_raw_parts(self.ptr.as_ptr() as *const T, len) }
-    }
-
-    fn slice_mut(&mut self, len: usize) -> &mut [T] {
-        unsafe { from_raw_parts_mut(self.ptr.as_ptr() as *mut T, len) }
-    }
-}
-
-impl<T> Drop for RawVec<T> {
+impl<T, A: Allocator> Drop for MyVecIntoIntoIter<T, A> {
     fn drop(&mut self) {
-        if self.cap == 0 {
-            return;
-        }
-
-        let layout = Layout::array::<MaybeUninit<T>>(self.cap).unwrap();
-
-        unsafe {
-            alloc::dealloc(self.ptr.as_ptr() as *mut u8, layout);
-        }
+        let remaining = ptr::slice_from_raw_parts_mut(
+            unsafe { self.ptr.add(self.index) } as *mut T,
+            self.len - self.index,
+        );
+        unsafe { ptr::drop_in_place(remaining) }
     }
 }
 
@@ -463,6 +1035,45 @@ mod vec_tests {
         assert_eq!(vec2.get(1), Some(&10));
     }
 
+    #[test]
+    fn test_clone_panicking_clone() {
+        struct Bomb<'a> {
+            drops: &'a std::cell::Cell<usize>,
+            explode: bool,
+        }
+
+        impl Clone for Bomb<'_> {
+            fn clone(&self) -> Self {
+                assert!(!self.explode, "clone failed");
+                Bomb {
+                    drops: self.drops,
+                    explode: false,
+                }
+            }
+        }
+
+        impl Drop for Bomb<'_> {
+            fn drop(&mut self) {
+                self.drops.set(self.drops.get() + 1);
+            }
+        }
+
+        let drops = std::cell::Cell::new(0);
+        let mut vec = MyVec::new();
+        for explode in [false, false, true] {
+            vec.push(Bomb {
+                drops: &drops,
+                explode,
+            });
+        }
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| vec.clone()));
+        assert!(result.is_err());
+        // Only the two finished clones, the originals are untouched
+        assert_eq!(drops.get(), 2);
+        drop(vec);
+        assert_eq!(drops.get(), 5);
+    }
+
     #[test]
     fn test_get_out_of_bounds() {
         let mut vec = MyVec::new();
@@ -528,6 +1139,17 @@ mod vec_tests {
         assert_eq!(vec.get(0).unwrap(), &10);
     }
 
+    #[test]
+    fn test_get_range() {
+        let mut vec: MyVec<i32> = (1..=4).collect();
+
+        assert_eq!(vec.get(1..3), Some(&[2, 3][..]));
+        assert_eq!(vec.get(2..), Some(&[3, 4][..]));
+        assert_eq!(vec.get(3..5), None);
+        vec.get_mut(..2).unwrap().reverse();
+        assert_eq!(vec.as_slice(), [2, 1, 3, 4]);
+    }
+
     #[test]
     fn test_slice() {
         let mut vec = MyVec::new();
@@ -690,4 +1312,596 @@ mod vec_tests {
         vec.push(1);
         vec.remove(1); // Invalid: index >= len
     }
+
+    #[derive(Debug, Clone, PartialEq)]
+    struct Marker;
+
+    #[test]
+    fn test_zst_push_pop() {
+        let mut vec = MyVec::new();
+        assert_eq!(vec.get_capacity(), usize::MAX);
+
+        for _ in 0..100 {
+            vec.push(Marker);
+        }
+        assert_eq!(vec.get_len(), 100);
+        assert_eq!(vec.get_capacity(), usize::MAX);
+        assert_eq!(vec.get(99), Some(&Marker));
+        assert_eq!(vec.get(100), None);
+
+        vec.insert(50, Marker);
+        assert_eq!(vec.remove(0), Marker);
+        assert_eq!(vec.pop(), Some(Marker));
+        assert_eq!(vec.get_len(), 99);
+    }
+
+    #[test]
+    fn test_zst_iterators() {
+        let mut vec = MyVec::new();
+        for _ in 0..10 {
+            vec.push(());
+        }
+
+        assert_eq!((&vec).into_iter().count(), 10);
+        assert_eq!((&mut vec).into_iter().count(), 10);
+        assert_eq!(vec.as_slice().len(), 10);
+        assert_eq!(vec.clone().into_iter().count(), 10);
+    }
+
+    #[test]
+    fn test_zst_drops() {
+        use std::cell::Cell;
+
+        thread_local!(static DROPS: Cell<usize> = const { Cell::new(0) });
+        struct DropZst;
+        impl Drop for DropZst {
+            fn drop(&mut self) {
+                DROPS.with(|d| d.set(d.get() + 1));
+            }
+        }
+
+        let mut vec = MyVec::new();
+        for _ in 0..5 {
+            vec.push(DropZst);
+        }
+        drop(vec.pop());
+        drop(vec);
+        assert_eq!(DROPS.with(Cell::get), 5);
+
+        let mut vec = MyVec::new();
+        for _ in 0..5 {
+            vec.push(DropZst);
+        }
+        let mut iter = vec.into_iter();
+        iter.next();
+        drop(iter);
+        assert_eq!(DROPS.with(Cell::get), 10);
+    }
+
+    #[test]
+    fn test_try_push_and_reserve() {
+        let mut vec = MyVec::try_with_capacity(2).unwrap();
+        assert_eq!(vec.get_capacity(), 2);
+
+        vec.try_push(1).unwrap();
+        vec.try_push(2).unwrap();
+        vec.try_push(3).unwrap();
+        assert_eq!(vec.as_slice(), &[1, 2, 3]);
+
+        vec.try_reserve(10).unwrap();
+        assert!(vec.get_capacity() >= 13);
+        vec.try_reserve_exact(20).unwrap();
+        assert!(vec.get_capacity() >= 23);
+    }
+
+    #[test]
+    fn test_try_reserve_errors() {
+        let mut vec: MyVec<u64> = MyVec::new();
+        assert_eq!(
+            vec.try_reserve(usize::MAX),
+            Err(TryReserveError::CapacityOverflow)
+        );
+        assert_eq!(
+            vec.try_reserve_exact(usize::MAX / 2),
+            Err(TryReserveError::CapacityOverflow)
+        );
+        assert!(MyVec::<u64>::try_with_capacity(usize::MAX).is_err());
+
+        // The vector is still usable afterwards
+        vec.try_push(1).unwrap();
+        assert_eq!(vec.as_slice(), &[1]);
+
+        let mut zst = MyVec::new();
+        zst.try_push(()).unwrap();
+        assert_eq!(
+            zst.try_reserve(usize::MAX),
+            Err(TryReserveError::CapacityOverflow)
+        );
+    }
+
+    fn vec_of<T: Clone>(items: &[T]) -> MyVec<T> {
+        let mut vec = MyVec::new();
+        for item in items {
+            vec.push(item.clone());
+        }
+        vec
+    }
+
+    // Counts drops through a shared cell, and can be told to panic when dropped.
+    struct Counted<'a> {
+        value: i32,
+        drops: &'a std::cell::Cell<usize>,
+        panic_on_drop: bool,
+    }
+
+    impl Drop for Counted<'_> {
+        fn drop(&mut self) {
+            self.drops.set(self.drops.get() + 1);
+            if self.panic_on_drop {
+                panic!("drop of {}", self.value);
+            }
+        }
+    }
+
+    fn counted(drops: &std::cell::Cell<usize>, n: i32) -> MyVec<Counted<'_>> {
+        let mut vec = MyVec::new();
+        for value in 0..n {
+            vec.push(Counted {
+                value,
+                drops,
+                panic_on_drop: false,
+            });
+        }
+        vec
+    }
+
+    fn values(vec: &MyVec<Counted<'_>>) -> Vec<i32> {
+        vec.as_slice().iter().map(|c| c.value).collect()
+    }
+
+    #[test]
+    fn test_drain() {
+        let mut vec = vec_of(&[1, 2, 3, 4, 5, 6]);
+        let drained: Vec<_> = vec.drain(1..3).collect();
+        assert_eq!(drained, [2, 3]);
+        assert_eq!(vec.as_slice(), &[1, 4, 5, 6]);
+
+        let mut drain = vec.drain(1..);
+        assert_eq!(drain.as_slice(), &[4, 5, 6]);
+        assert_eq!(drain.next_back(), Some(6));
+        assert_eq!(drain.len(), 2);
+        drop(drain);
+        assert_eq!(vec.as_slice(), &[1]);
+
+        assert_eq!(vec.drain(..).collect::<Vec<_>>(), [1]);
+        assert_eq!(vec.get_len(), 0);
+        assert_eq!(vec.drain(..).next(), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "out of range")]
+    fn test_drain_out_of_range() {
+        let mut vec = vec_of(&[1, 2]);
+        vec.drain(1..=2);
+    }
+
+    #[test]
+    fn test_drain_drops_unyielded_and_forget_leaks() {
+        let drops = std::cell::Cell::new(0);
+        let mut vec = counted(&drops, 6);
+        {
+            let mut drain = vec.drain(1..4);
+            drop(drain.next());
+        }
+        assert_eq!(drops.get(), 3);
+        assert_eq!(values(&vec), [0, 4, 5]);
+
+        // Forgetting the drain must not expose the moved-out slots
+        std::mem::forget(vec.drain(1..));
+        assert_eq!(values(&vec), [0]);
+        drop(vec);
+        assert_eq!(drops.get(), 4);
+    }
+
+    #[test]
+    fn test_drain_panicking_drop() {
+        let drops = std::cell::Cell::new(0);
+        let mut vec = counted(&drops, 6);
+        vec[2].panic_on_drop = true;
+
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            vec.drain(1..4);
+        }));
+        assert!(result.is_err());
+        assert_eq!(drops.get(), 3);
+        assert_eq!(values(&vec), [0, 4, 5]);
+        drop(vec);
+        assert_eq!(drops.get(), 6);
+    }
+
+    #[test]
+    fn test_splice() {
+        // Shorter, exact-size replacement
+        let mut vec = vec_of(&[1, 2, 3, 4, 5]);
+        let removed: Vec<_> = vec.splice(1..4, [10]).collect();
+        assert_eq!(removed, [2, 3, 4]);
+        assert_eq!(vec.as_slice(), &[1, 10, 5]);
+
+        // Longer, exact-size replacement
+        vec.splice(1..2, [20, 21, 22, 23]);
+        assert_eq!(vec.as_slice(), &[1, 20, 21, 22, 23, 5]);
+
+        // Longer, without an exact size hint
+        vec.splice(..1, (0..4).filter(|x| x % 2 == 0));
+        assert_eq!(vec.as_slice(), &[0, 2, 20, 21, 22, 23, 5]);
+
+        // At the end, and with nothing removed
+        vec.splice(7.., [8, 9]);
+        vec.splice(2..2, [1]);
+        assert_eq!(vec.as_slice(), &[0, 2, 1, 20, 21, 22, 23, 5, 8, 9]);
+
+        // Same length, and to empty
+        vec.splice(3..7, [3, 4, 5, 6]);
+        assert_eq!(vec.as_slice(), &[0, 2, 1, 3, 4, 5, 6, 5, 8, 9]);
+        vec.splice(.., []);
+        assert_eq!(vec.get_len(), 0);
+    }
+
+    #[test]
+    fn test_splice_panicking_iterator() {
+        let mut vec = vec_of(&[String::from("a"), String::from("b"), String::from("c")]);
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            let replacement = (0..5).map(|i| {
+                assert!(i < 2, "replacement ran dry");
+                i.to_string()
+            });
+            vec.splice(1..2, replacement);
+        }));
+        assert!(result.is_err());
+        // The tail is back in place behind whatever was written before the panic
+        assert_eq!(vec.as_slice(), &["a", "0", "1", "c"]);
+    }
+
+    #[test]
+    fn test_extract_if() {
+        let mut vec = vec_of(&[1, 2, 3, 4, 5, 6, 7, 8]);
+        let evens: Vec<_> = vec.extract_if(|x| *x % 2 == 0).collect();
+        assert_eq!(evens, [2, 4, 6, 8]);
+        assert_eq!(vec.as_slice(), &[1, 3, 5, 7]);
+
+        // Stopping early keeps whatever wasn't visited
+        let mut iter = vec.extract_if(|x| *x > 1);
+        assert_eq!(iter.next(), Some(3));
+        drop(iter);
+        assert_eq!(vec.as_slice(), &[1, 5, 7]);
+
+        std::mem::forget(vec.extract_if(|_| true));
+        assert_eq!(vec.get_len(), 0);
+    }
+
+    #[test]
+    fn test_extract_if_panicking_predicate() {
+        let drops = std::cell::Cell::new(0);
+        let mut vec = counted(&drops, 8);
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            vec.extract_if(|c| {
+                assert_ne!(c.value, 5);
+                c.value % 2 == 1
+            })
+            .for_each(drop);
+        }));
+        assert!(result.is_err());
+        assert_eq!(drops.get(), 2);
+        assert_eq!(values(&vec), [0, 2, 4, 5, 6, 7]);
+        drop(vec);
+        assert_eq!(drops.get(), 8);
+    }
+
+    #[test]
+    fn test_retain() {
+        let mut vec = vec_of(&[1, 2, 3, 4, 5, 6]);
+        vec.retain(|x| *x % 3 != 0);
+        assert_eq!(vec.as_slice(), &[1, 2, 4, 5]);
+
+        vec.retain_mut(|x| {
+            *x *= 10;
+            *x > 10
+        });
+        assert_eq!(vec.as_slice(), &[20, 40, 50]);
+
+        let mut zst = vec_of(&[(); 10]);
+        let mut keep = false;
+        zst.retain(|_| {
+            keep = !keep;
+            keep
+        });
+        assert_eq!(zst.get_len(), 5);
+    }
+
+    #[test]
+    fn test_retain_panicking_drop() {
+        let drops = std::cell::Cell::new(0);
+        let mut vec = counted(&drops, 6);
+        vec[3].panic_on_drop = true;
+
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            vec.retain(|c| c.value % 3 != 0);
+        }));
+        assert!(result.is_err());
+        assert_eq!(drops.get(), 2);
+        assert_eq!(values(&vec), [1, 2, 4, 5]);
+        drop(vec);
+        assert_eq!(drops.get(), 6);
+    }
+
+    #[test]
+    fn test_dedup_by_key() {
+        let mut vec = vec_of(&[10, 11, 20, 21, 22, 30, 10, 12]);
+        vec.dedup_by_key(|x| *x / 10);
+        assert_eq!(vec.as_slice(), &[10, 20, 30, 10]);
+
+        let mut words = vec_of(&["a", "A", "b", "B", "b", "c"]);
+        words.dedup_by(|a, b| a.eq_ignore_ascii_case(b));
+        assert_eq!(words.as_slice(), &["a", "b", "c"]);
+    }
+
+    #[test]
+    fn test_dedup_panicking_key() {
+        let drops = std::cell::Cell::new(0);
+        let mut vec = counted(&drops, 8);
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            vec.dedup_by_key(|c| {
+                assert_ne!(c.value, 5);
+                c.value / 2
+            });
+        }));
+        assert!(result.is_err());
+        // 1 and 3 were removed before 5 panicked, the rest is untouched
+        assert_eq!(drops.get(), 2);
+        assert_eq!(values(&vec), [0, 2, 4, 5, 6, 7]);
+        drop(vec);
+        assert_eq!(drops.get(), 8);
+    }
+
+    #[test]
+    fn test_truncate() {
+        let drops = std::cell::Cell::new(0);
+        let mut vec = counted(&drops, 5);
+        let cap = vec.get_capacity();
+        vec.truncate(10);
+        assert_eq!(drops.get(), 0);
+        vec.truncate(2);
+        assert_eq!(drops.get(), 3);
+        assert_eq!(values(&vec), [0, 1]);
+        assert_eq!(vec.get_capacity(), cap);
+        vec.truncate(0);
+        assert_eq!(drops.get(), 5);
+    }
+
+    #[test]
+    fn test_split_off_and_append() {
+        let mut vec = vec_of(&[1, 2, 3, 4, 5]);
+        let mut tail = vec.split_off(2);
+        assert_eq!(vec.as_slice(), &[1, 2]);
+        assert_eq!(tail.as_slice(), &[3, 4, 5]);
+        assert!(vec.split_off(2).as_slice().is_empty());
+
+        vec.append(&mut tail);
+        assert_eq!(vec.as_slice(), &[1, 2, 3, 4, 5]);
+        assert_eq!(tail.get_len(), 0);
+
+        let mut zst = vec_of(&[(); 4]);
+        let mut other = zst.split_off(1);
+        assert_eq!((zst.get_len(), other.get_len()), (1, 3));
+        zst.append(&mut other);
+        assert_eq!(zst.get_len(), 4);
+    }
+
+    #[test]
+    fn test_deref_and_slice_index() {
+        let mut vec = my_vec![3, 1, 2];
+        assert_eq!(vec.len(), 3);
+        assert_eq!(vec.first(), Some(&3));
+        vec.sort_unstable();
+        assert_eq!(vec[..], [1, 2, 3]);
+        assert_eq!(vec[1..], [2, 3]);
+        assert_eq!(vec[..=1], [1, 2]);
+        vec[2] = 30;
+        vec[..2].copy_from_slice(&[10, 20]);
+        assert_eq!(vec, [10, 20, 30]);
+    }
+
+    #[test]
+    #[should_panic(expected = "index out of bounds")]
+    fn test_index_out_of_bounds() {
+        let vec = my_vec![1];
+        let _ = vec[1];
+    }
+
+    #[test]
+    fn test_comparisons_and_hash() {
+        use std::collections::HashSet;
+
+        let a = my_vec![1, 2, 3];
+        let b: MyVec<i32> = MyVec::from(vec![1, 2, 4]);
+        assert!(a < b);
+        assert_eq!(a.cmp(&a.clone()), Ordering::Equal);
+        assert_eq!(a, vec![1, 2, 3]);
+        assert_eq!(vec![1, 2, 3], a);
+        assert_eq!(&a[..], a);
+        assert_ne!(a, b);
+
+        // Borrow<[T]> lets a set of MyVecs be searched with a plain slice
+        let set: HashSet<MyVec<i32>> = [a.clone(), b].into_iter().collect();
+        assert!(set.contains(&[1, 2, 3][..]));
+    }
+
+    #[test]
+    fn test_conversions() {
+        let strings = vec![String::from("a"), String::from("b")];
+        let vec = MyVec::from(strings);
+        let back: Vec<String> = vec.into();
+        assert_eq!(back, ["a", "b"]);
+
+        let from_slice = MyVec::from(&[1, 2][..]);
+        let from_array = MyVec::from([1, 2]);
+        assert_eq!(from_slice, from_array);
+
+        let collected: MyVec<_> = (0..5).collect();
+        assert_eq!(collected, [0, 1, 2, 3, 4]);
+
+        let mut extended = my_vec![0];
+        extended.extend(&[1, 2]);
+        extended.extend(vec![3]);
+        assert_eq!(extended, [0, 1, 2, 3]);
+    }
+
+    #[test]
+    fn test_macro() {
+        let empty: MyVec<u8> = my_vec![];
+        assert!(empty.is_empty());
+        assert_eq!(my_vec![String::from("x"); 3], ["x", "x", "x"]);
+        assert_eq!(my_vec![1, 2, 3,], [1, 2, 3]);
+        assert!(my_vec![String::new(); 0].is_empty());
+    }
+
+    #[test]
+    fn test_iter_double_ended_and_exact() {
+        let mut vec = my_vec![1, 2, 3, 4, 5];
+
+        let mut iter = (&vec).into_iter();
+        assert_eq!(iter.len(), 5);
+        assert_eq!(iter.next_back(), Some(&5));
+        assert_eq!(iter.nth(1), Some(&2));
+        assert_eq!(iter.size_hint(), (2, Some(2)));
+        assert_eq!(iter.clone().rev().collect::<Vec<_>>(), [&4, &3]);
+        assert_eq!(iter.nth(5), None);
+        assert_eq!(iter.next(), None);
+
+        let mut iter = (&mut vec).into_iter();
+        *iter.next_back().unwrap() = 50;
+        *iter.nth(2).unwrap() = 30;
+        assert_eq!(iter.len(), 1);
+        assert_eq!(vec, [1, 2, 30, 4, 50]);
+
+        let zipped: Vec<_> = (&vec).into_iter().zip((&vec).into_iter().rev()).collect();
+        assert_eq!(zipped[0], (&1, &50));
+    }
+
+    #[test]
+    fn test_into_iter_double_ended_and_as_slice() {
+        let vec = my_vec![String::from("a"), String::from("b"), String::from("c")];
+        let mut iter = vec.into_iter();
+        assert_eq!(iter.as_slice(), ["a", "b", "c"]);
+        assert_eq!(iter.next_back().as_deref(), Some("c"));
+        iter.as_mut_slice()[0].push('!');
+        assert_eq!(iter.len(), 2);
+        assert_eq!(iter.next().as_deref(), Some("a!"));
+        assert_eq!(iter.as_slice(), ["b"]);
+        assert_eq!(iter.next_back().as_deref(), Some("b"));
+        assert_eq!(iter.next(), None);
+        assert!(iter.as_slice().is_empty());
+    }
+
+    #[test]
+    fn test_into_iter_nth_drops_skipped() {
+        let drops = std::cell::Cell::new(0);
+        let mut iter = counted(&drops, 6).into_iter();
+        let third = iter.nth(2).unwrap();
+        assert_eq!(third.value, 2);
+        assert_eq!(drops.get(), 2);
+        drop(third);
+        assert_eq!(iter.next_back().map(|c| c.value), Some(5));
+        assert_eq!(drops.get(), 4);
+        assert!(iter.nth(10).is_none());
+        assert_eq!(drops.get(), 6);
+    }
+
+    #[test]
+    fn test_zst_iter_from_both_ends() {
+        let vec = my_vec![(); 4];
+        let mut iter = (&vec).into_iter();
+        assert_eq!(iter.next_back(), Some(&()));
+        assert_eq!(iter.nth(1), Some(&()));
+        assert_eq!(iter.len(), 1);
+
+        let mut iter = vec.into_iter();
+        assert_eq!(iter.nth(2), Some(()));
+        assert_eq!(iter.next_back(), Some(()));
+        assert_eq!(iter.next(), None);
+    }
+
+    #[test]
+    fn test_with_capacity_and_reserve() {
+        let mut vec = MyVec::with_capacity(10);
+        assert_eq!(vec.get_capacity(), 10);
+        let start = vec.as_ptr();
+        for i in 0..10 {
+            vec.push(i);
+        }
+        assert_eq!(vec.as_ptr(), start);
+
+        vec.reserve(1);
+        assert!(vec.get_capacity() >= 11);
+        let cap = vec.get_capacity();
+        vec.reserve(1);
+        assert_eq!(vec.get_capacity(), cap);
+
+        vec.reserve_exact(cap - 10 + 5);
+        assert_eq!(vec.get_capacity(), cap + 5);
+        assert!(MyVec::<u8>::new().get_capacity() == 0);
+    }
+
+    #[test]
+    fn test_shrink() {
+        let mut vec = MyVec::with_capacity(100);
+        vec.extend(0..10);
+
+        vec.shrink_to(50);
+        assert_eq!(vec.get_capacity(), 50);
+        vec.shrink_to(80);
+        assert_eq!(vec.get_capacity(), 50);
+        vec.shrink_to(2);
+        assert_eq!(vec.get_capacity(), 10);
+
+        vec.truncate(3);
+        vec.shrink_to_fit();
+        assert_eq!(vec.get_capacity(), 3);
+        assert_eq!(vec, [0, 1, 2]);
+
+        vec.truncate(0);
+        vec.shrink_to_fit();
+        assert_eq!(vec.get_capacity(), 0);
+        vec.push(7);
+        assert_eq!(vec, [7]);
+
+        let mut zst = my_vec![(); 3];
+        zst.shrink_to_fit();
+        assert_eq!(zst.get_capacity(), usize::MAX);
+    }
+
+    #[test]
+    fn test_spare_capacity_and_set_len() {
+        let mut vec: MyVec<u32> = MyVec::with_capacity(8);
+        vec.push(1);
+
+        let spare = vec.spare_capacity_mut();
+        assert_eq!(spare.len(), 7);
+        for (i, slot) in spare.iter_mut().take(3).enumerate() {
+            slot.write(i as u32 + 10);
+        }
+        unsafe { vec.set_len(4) };
+        assert_eq!(vec, [1, 10, 11, 12]);
+        assert_eq!(vec.spare_capacity_mut().len(), 4);
+    }
+
+    #[test]
+    fn test_send_sync_bounds() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<MyVec<String>>();
+        assert_send_sync::<MyVec<String, crate::tracking::Tracking>>();
+        assert_send_sync::<MyVecIter<'_, String>>();
+        assert_send_sync::<MutMyVecIter<'_, String>>();
+        assert_send_sync::<MyVecIntoIntoIter<String>>();
+    }
 }
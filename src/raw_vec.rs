@@ -0,0 +1,469 @@
+// Purpose: The one growable buffer every contiguous collection in the crate is built on.
+
+use alloc::alloc::handle_alloc_error;
+use core::{
+    alloc::Layout,
+    cmp,
+    fmt::{self, Debug},
+    mem::MaybeUninit,
+    ptr::{self, NonNull},
+    slice::{from_raw_parts, from_raw_parts_mut},
+};
+
+use crate::allocator::{Allocator, Global};
+
+/*
+RawVec<T>:
+
+- Owns `cap` slots of uninitialised memory and nothing else.
+- Never reads, writes or drops a T on its own; the collection on top
+  tracks which slots are initialised and tells us through `len`.
+- A capacity of 0 means no allocation: `ptr` is dangling and Drop is a no-op.
+- Zero-sized types never allocate either: every slot lives at the same
+  dangling address, so the capacity is `usize::MAX` from the start.
+- Memory comes from the `A: Allocator` it owns; collections on top just
+  pass their allocator through.
+*/
+
+pub(crate) struct RawVec<T, A: Allocator = Global> {
+    pub(crate) ptr: NonNull<MaybeUninit<T>>,
+    pub(crate) cap: usize,
+    pub(crate) alloc: A,
+}
+
+// The buffer owns its T's like a Box<[T]> would, the raw pointer just hides that.
+unsafe impl<T: Send, A: Allocator + Send> Send for RawVec<T, A> {}
+unsafe impl<T: Sync, A: Allocator + Sync> Sync for RawVec<T, A> {}
+
+impl<T> RawVec<T> {
+    /// Creates an empty buffer on the global heap without allocating.
+    pub(crate) const fn new() -> Self {
+        Self::new_in(Global)
+    }
+}
+
+impl<T, A: Allocator> RawVec<T, A> {
+    // Same heuristic as std: tiny elements get a bigger first allocation,
+    // huge elements only get one slot.
+    const MIN_NON_ZERO_CAP: usize = if size_of::<T>() == 1 {
+        8
+    } else if size_of::<T>() <= 1024 {
+        4
+    } else {
+        1
+    };
+
+    pub(crate) const IS_ZST: bool = size_of::<T>() == 0;
+
+    /// Creates an empty buffer in `alloc` without allocating.
+    pub(crate) const fn new_in(alloc: A) -> Self {
+        Self {
+            ptr: NonNull::dangling(),
+            cap: if Self::IS_ZST { usize::MAX } else { 0 },
+            alloc,
+        }
+    }
+
+    /// Creates a buffer in `alloc` with room for exactly `cap` elements.
+    #[track_caller]
+    pub(crate) fn with_capacity_in(cap: usize, alloc: A) -> Self {
+        handle_error(Self::try_with_capacity_in(cap, alloc))
+    }
+
+    #[track_caller]
+    pub(crate) fn try_with_capacity_in(cap: usize, alloc: A) -> Result<Self, TryReserveError> {
+        let mut buf = Self::new_in(alloc);
+        if cap != 0 && !Self::IS_ZST {
+            buf.set_buffer(cap)?;
+        }
+        Ok(buf)
+    }
+
+    /// Makes sure there is room for at least `len + additional` elements,
+    /// growing geometrically so repeated pushes stay amortised O(1).
+    #[track_caller]
+    pub(crate) fn reserve(&mut self, len: usize, additional: usize) {
+        handle_error(self.try_reserve(len, additional));
+    }
+
+    #[track_caller]
+    pub(crate) fn try_reserve(
+        &mut self,
+        len: usize,
+        additional: usize,
+    ) -> Result<(), TryReserveError> {
+        if self.needs_to_grow(len, additional) {
+            self.try_grow_amortized(len, additional)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Makes sure there is room for exactly `len + additional` elements.
+    #[track_caller]
+    pub(crate) fn reserve_exact(&mut self, len: usize, additional: usize) {
+        handle_error(self.try_reserve_exact(len, additional));
+    }
+
+    #[track_caller]
+    pub(crate) fn try_reserve_exact(
+        &mut self,
+        len: usize,
+        additional: usize,
+    ) -> Result<(), TryReserveError> {
+        if !self.needs_to_grow(len, additional) {
+            return Ok(());
+        }
+
+        // A ZST buffer is already usize::MAX long, needing more is an overflow
+        if Self::IS_ZST {
+            return Err(TryReserveError::CapacityOverflow);
+        }
+
+        let required = len
+            .checked_add(additional)
+            .ok_or(TryReserveError::CapacityOverflow)?;
+        self.set_buffer(required)
+    }
+
+    /// Grows to at least `len + additional` slots, doubling when that is bigger.
+    #[track_caller]
+    pub(crate) fn grow_amortized(&mut self, len: usize, additional: usize) {
+        handle_error(self.try_grow_amortized(len, additional));
+    }
+
+    #[track_caller]
+    fn try_grow_amortized(&mut self, len: usize, additional: usize) -> Result<(), TryReserveError> {
+        if Self::IS_ZST {
+            return Err(TryReserveError::CapacityOverflow);
+        }
+
+        let required = len
+            .checked_add(additional)
+            .ok_or(TryReserveError::CapacityOverflow)?;
+        let new_cap = cmp::max(self.cap.saturating_mul(2), required);
+        let new_cap = cmp::max(Self::MIN_NON_ZERO_CAP, new_cap);
+        self.set_buffer(new_cap)
+    }
+
+    /// Shrinks the allocation down to `cap` slots (never below it).
+    ///
+    /// The caller must already have moved every live element below `cap`.
+    pub(crate) fn shrink_to(&mut self, cap: usize) {
+        assert!(cap <= self.cap, "Tried to shrink to a larger capacity");
+
+        if Self::IS_ZST {
+            return;
+        }
+
+        if cap == 0 {
+            self.free();
+            self.ptr = NonNull::dangling();
+            self.cap = 0;
+        } else if cap < self.cap {
+            handle_error(self.set_buffer(cap));
+        }
+    }
+
+    fn needs_to_grow(&self, len: usize, additional: usize) -> bool {
+        additional > self.cap.wrapping_sub(len)
+    }
+
+    // Moves the buffer to an allocation of exactly `new_cap` slots, keeping
+    // the first min(old, new) slots' bytes. `new_cap` must be non-zero.
+    // On failure the old buffer is left untouched.
+    #[track_caller]
+    fn set_buffer(&mut self, new_cap: usize) -> Result<(), TryReserveError> {
+        let new_layout =
+            Layout::array::<T>(new_cap).map_err(|_| TryReserveError::CapacityOverflow)?;
+
+        let result = unsafe {
+            if self.cap == 0 {
+                self.alloc.allocate(new_layout)
+            } else {
+                let old_layout = Layout::array::<T>(self.cap).unwrap();
+                let old_ptr = self.ptr.cast::<u8>();
+                if new_cap > self.cap {
+                    self.alloc.grow(old_ptr, old_layout, new_layout)
+                } else {
+                    self.alloc.shrink(old_ptr, old_layout, new_layout)
+                }
+            }
+        };
+
+        let raw_ptr = result.map_err(|_| TryReserveError::AllocError { layout: new_layout })?;
+        self.ptr = raw_ptr.cast::<MaybeUninit<T>>();
+        self.cap = new_cap;
+        Ok(())
+    }
+
+    fn free(&mut self) {
+        if self.cap == 0 || Self::IS_ZST {
+            return;
+        }
+
+        let layout = Layout::array::<T>(self.cap).unwrap();
+
+        unsafe {
+            self.alloc.deallocate(self.ptr.cast::<u8>(), layout);
+        }
+    }
+
+    pub(crate) fn write(&mut self, index: usize, value: T) {
+        unsafe {
+            (*self.ptr.as_ptr().add(index)).write(value);
+        }
+    }
+
+    /// Shifts `[index, len)` right by one and writes `value` into the gap.
+    pub(crate) fn write_pos(&mut self, index: usize, value: T, len: usize) {
+        unsafe {
+            ptr::copy(
+                self.ptr.as_ptr().add(index),
+                self.ptr.as_ptr().add(index + 1),
+                len - index,
+            );
+            self.write(index, value);
+        }
+    }
+
+    // Shifts the array left
+    // remove_pos(2,4)
+    // [39,40,209,30]
+    // [39,40,30,_]
+    pub(crate) fn remove_pos(&mut self, index: usize, len: usize) {
+        unsafe {
+            ptr::copy(
+                self.ptr.as_ptr().add(index + 1),
+                self.ptr.as_ptr().add(index),
+                len - index - 1,
+            );
+        }
+    }
+
+    pub(crate) fn read(&self, index: usize) -> T {
+        unsafe { ptr::read((*self.ptr.as_ptr().add(index)).as_ptr()) }
+    }
+
+    pub(crate) fn read_ref(&self, index: usize) -> &T {
+        unsafe { &*self.ptr.as_ptr().add(index).cast::<T>() }
+    }
+
+    pub(crate) fn read_mut(&mut self, index: usize) -> &mut T {
+        unsafe { &mut *self.ptr.as_ptr().add(index).cast::<T>() }
+    }
+
+    pub(crate) fn drop_index(&mut self, index: usize) {
+        unsafe {
+            ptr::drop_in_place(self.ptr.as_ptr().add(index).cast::<T>());
+        }
+    }
+
+    pub(crate) fn slice(&self, len: usize) -> &[T] {
+        unsafe { from_raw_parts(self.ptr.as_ptr() as *const T, len) }
+    }
+
+    pub(crate) fn slice_mut(&mut self, len: usize) -> &mut [T] {
+        unsafe { from_raw_parts_mut(self.ptr.as_ptr() as *mut T, len) }
+    }
+}
+
+impl<T, A: Allocator> Drop for RawVec<T, A> {
+    fn drop(&mut self) {
+        self.free();
+    }
+}
+
+impl<T, A: Allocator> Debug for RawVec<T, A> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RawVec")
+            .field("ptr", &self.ptr)
+            .field("cap", &self.cap)
+            .finish()
+    }
+}
+
+/// Why a fallible allocation (`try_reserve`, `try_push`, ...) failed.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TryReserveError {
+    /// The requested capacity does not fit in `isize::MAX` bytes,
+    /// or the element count itself overflowed `usize`.
+    CapacityOverflow,
+    /// The allocator returned null for this layout.
+    AllocError { layout: Layout },
+}
+
+impl fmt::Display for TryReserveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("memory allocation failed")?;
+        match self {
+            TryReserveError::CapacityOverflow => {
+                f.write_str(" because the computed capacity exceeded the collection's maximum")
+            }
+            TryReserveError::AllocError { .. } => {
+                f.write_str(" because the memory allocator returned an error")
+            }
+        }
+    }
+}
+
+impl core::error::Error for TryReserveError {}
+
+// The infallible API keeps std's behaviour: panic on overflow, abort on OOM.
+pub(crate) fn handle_error<R>(result: Result<R, TryReserveError>) -> R {
+    match result {
+        Ok(value) => value,
+        Err(TryReserveError::CapacityOverflow) => capacity_overflow(),
+        Err(TryReserveError::AllocError { layout }) => handle_alloc_error(layout),
+    }
+}
+
+#[cold]
+fn capacity_overflow() -> ! {
+    panic!("capacity overflow");
+}
+
+// How much a decoder may preallocate for a length it read from untrusted
+// input: the hint, capped at about a megabyte. Anything past that is only
+// allocated as the elements actually arrive.
+pub(crate) fn cautious_capacity<T>(hint: Option<usize>) -> usize {
+    const MAX_PREALLOC_BYTES: usize = 1024 * 1024;
+
+    let max = MAX_PREALLOC_BYTES / size_of::<T>().max(1);
+    hint.unwrap_or(0).min(max)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cautious_capacity() {
+        assert_eq!(cautious_capacity::<u64>(None), 0);
+        assert_eq!(cautious_capacity::<u64>(Some(10)), 10);
+        assert_eq!(cautious_capacity::<u64>(Some(usize::MAX)), 128 * 1024);
+        assert_eq!(cautious_capacity::<()>(Some(usize::MAX)), 1024 * 1024);
+    }
+
+    #[test]
+    fn test_send_sync_bounds() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<RawVec<String>>();
+    }
+
+    #[test]
+    fn test_new_does_not_allocate() {
+        let buf: RawVec<u64> = RawVec::new();
+        assert_eq!(buf.cap, 0);
+        assert_eq!(buf.ptr, NonNull::dangling());
+    }
+
+    #[test]
+    fn test_grow_amortized_doubles() {
+        let mut buf: RawVec<u64> = RawVec::new();
+        buf.grow_amortized(0, 1);
+        assert_eq!(buf.cap, 4);
+
+        buf.grow_amortized(4, 1);
+        assert_eq!(buf.cap, 8);
+
+        // A big request wins over doubling
+        buf.grow_amortized(8, 100);
+        assert_eq!(buf.cap, 108);
+    }
+
+    #[test]
+    fn test_reserve_keeps_contents() {
+        let mut buf: RawVec<String> = RawVec::with_capacity_in(1, Global);
+        buf.write(0, String::from("kept"));
+
+        buf.reserve(1, 10);
+        assert!(buf.cap >= 11);
+        assert_eq!(buf.read(0), "kept");
+    }
+
+    #[test]
+    fn test_reserve_exact() {
+        let mut buf: RawVec<u32> = RawVec::new();
+        buf.reserve_exact(0, 3);
+        assert_eq!(buf.cap, 3);
+
+        // Enough room already, nothing to do
+        buf.reserve_exact(1, 2);
+        assert_eq!(buf.cap, 3);
+    }
+
+    #[test]
+    fn test_shrink_to() {
+        let mut buf: RawVec<u32> = RawVec::with_capacity_in(10, Global);
+        buf.write(0, 7);
+
+        buf.shrink_to(1);
+        assert_eq!(buf.cap, 1);
+        assert_eq!(buf.read(0), 7);
+
+        buf.shrink_to(0);
+        assert_eq!(buf.cap, 0);
+    }
+
+    #[test]
+    fn test_zst_never_allocates() {
+        let mut buf: RawVec<()> = RawVec::with_capacity_in(10, Global);
+        assert_eq!(buf.cap, usize::MAX);
+        assert_eq!(buf.ptr, NonNull::dangling());
+
+        buf.reserve(100, 1000);
+        buf.reserve_exact(100, 1000);
+        buf.shrink_to(0);
+        assert_eq!(buf.cap, usize::MAX);
+        assert_eq!(buf.ptr, NonNull::dangling());
+    }
+
+    #[test]
+    #[should_panic(expected = "capacity overflow")]
+    fn test_zst_capacity_overflow() {
+        let mut buf: RawVec<()> = RawVec::new();
+        buf.reserve(usize::MAX, 1);
+    }
+
+    #[test]
+    fn test_try_reserve_overflow() {
+        let mut buf: RawVec<u64> = RawVec::with_capacity_in(1, Global);
+        assert_eq!(
+            buf.try_reserve(1, usize::MAX),
+            Err(TryReserveError::CapacityOverflow)
+        );
+        // Fits in usize but not in isize::MAX bytes
+        assert_eq!(
+            buf.try_reserve_exact(0, usize::MAX / 4),
+            Err(TryReserveError::CapacityOverflow)
+        );
+
+        let mut zst: RawVec<()> = RawVec::new();
+        assert_eq!(
+            zst.try_reserve(usize::MAX, 1),
+            Err(TryReserveError::CapacityOverflow)
+        );
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)] // Miri reports huge allocations instead of returning null
+    fn test_try_reserve_alloc_error_keeps_buffer() {
+        let mut buf: RawVec<u8> = RawVec::with_capacity_in(4, Global);
+        buf.write(0, 42);
+
+        let err = buf
+            .try_reserve_exact(1, isize::MAX as usize - 1)
+            .unwrap_err();
+        assert!(matches!(err, TryReserveError::AllocError { .. }));
+        assert_eq!(buf.cap, 4);
+        assert_eq!(buf.read(0), 42);
+    }
+
+    #[test]
+    #[should_panic(expected = "capacity overflow")]
+    fn test_capacity_overflow() {
+        let mut buf: RawVec<u64> = RawVec::new();
+        buf.reserve_exact(0, usize::MAX / 2);
+    }
+}
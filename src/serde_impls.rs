@@ -0,0 +1,217 @@
+// Purpose: serde support for the collections and smart pointers, behind the `serde` feature.
+
+use core::{fmt, marker::PhantomData};
+
+use serde::{
+    de::{Deserialize, Deserializer, Error, SeqAccess, Visitor},
+    ser::{Serialize, SerializeSeq, Serializer},
+};
+
+use crate::{
+    allocator::Allocator, array_vec::ArrayVec, inline_vec::InlineVec, my_arc::MyArc,
+    my_deque::MyDeque, my_linked_list::LinkedList, my_rc::MyRc, my_vec::MyVec,
+    raw_vec::cautious_capacity,
+};
+
+/*
+Format:
+
+- Every collection is a plain sequence, so it reads and writes the same
+  data as a Vec<T> / VecDeque<T> / std LinkedList<T> would.
+- MyRc<T> and MyArc<T> are transparent: they serialize the value they
+  point to. Like serde's own `rc` feature, sharing isn't preserved; each
+  pointer is written out in full and deserializes into its own allocation.
+- Deserializing preallocates from the sequence's size hint, capped so a
+  lying length can't make us reserve more than about a megabyte up front.
+*/
+
+// ================
+// Sequence formats
+// ================
+
+// The deque and list iterators don't report an exact size, so hand the
+// length to the serializer ourselves instead of relying on `collect_seq`.
+fn serialize_with_len<S, I>(serializer: S, len: usize, iter: I) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+    I: IntoIterator,
+    I::Item: Serialize,
+{
+    let mut seq = serializer.serialize_seq(Some(len))?;
+    for item in iter {
+        seq.serialize_element(&item)?;
+    }
+    seq.end()
+}
+
+impl<T: Serialize, A: Allocator> Serialize for MyVec<T, A> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self)
+    }
+}
+
+impl<T: Serialize, A: Allocator> Serialize for MyDeque<T, A> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serialize_with_len(serializer, self.len(), self)
+    }
+}
+
+impl<T: Serialize, A: Allocator> Serialize for LinkedList<T, A> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serialize_with_len(serializer, self.len(), self)
+    }
+}
+
+impl<T: Serialize, const N: usize, A: Allocator> Serialize for InlineVec<T, N, A> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self)
+    }
+}
+
+impl<T: Serialize, const N: usize> Serialize for ArrayVec<T, N> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self)
+    }
+}
+
+// One visitor for every growable collection: `new(capacity)` builds the
+// empty collection, `push` appends to it.
+struct SeqVisitor<C, T> {
+    expecting: &'static str,
+    new: fn(usize) -> C,
+    push: fn(&mut C, T),
+    _marker: PhantomData<T>,
+}
+
+impl<'de, C, T: Deserialize<'de>> Visitor<'de> for SeqVisitor<C, T> {
+    type Value = C;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.expecting)
+    }
+
+    fn visit_seq<S: SeqAccess<'de>>(self, mut seq: S) -> Result<C, S::Error> {
+        let mut collection = (self.new)(cautious_capacity::<T>(seq.size_hint()));
+        while let Some(value) = seq.next_element()? {
+            (self.push)(&mut collection, value);
+        }
+        Ok(collection)
+    }
+}
+
+fn deserialize_seq<'de, D, C, T>(
+    deserializer: D,
+    expecting: &'static str,
+    new: fn(usize) -> C,
+    push: fn(&mut C, T),
+) -> Result<C, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Deserialize<'de>,
+{
+    deserializer.deserialize_seq(SeqVisitor {
+        expecting,
+        new,
+        push,
+        _marker: PhantomData,
+    })
+}
+
+impl<'de, T: Deserialize<'de>> Deserialize<'de> for MyVec<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserialize_seq(
+            deserializer,
+            "a sequence",
+            MyVec::with_capacity,
+            MyVec::push,
+        )
+    }
+}
+
+impl<'de, T: Deserialize<'de>> Deserialize<'de> for MyDeque<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserialize_seq(
+            deserializer,
+            "a sequence",
+            MyDeque::with_capacity,
+            MyDeque::push_back,
+        )
+    }
+}
+
+impl<'de, T: Deserialize<'de>> Deserialize<'de> for LinkedList<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        // Nodes are allocated one at a time, there is nothing to preallocate
+        deserialize_seq(
+            deserializer,
+            "a sequence",
+            |_| LinkedList::new(),
+            LinkedList::push_back,
+        )
+    }
+}
+
+impl<'de, T: Deserialize<'de>, const N: usize> Deserialize<'de> for InlineVec<T, N> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserialize_seq(
+            deserializer,
+            "a sequence",
+            InlineVec::with_capacity,
+            InlineVec::push,
+        )
+    }
+}
+
+struct ArrayVecVisitor<T, const N: usize>(PhantomData<T>);
+
+impl<'de, T: Deserialize<'de>, const N: usize> Visitor<'de> for ArrayVecVisitor<T, N> {
+    type Value = ArrayVec<T, N>;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "a sequence of at most {N} elements")
+    }
+
+    fn visit_seq<S: SeqAccess<'de>>(self, mut seq: S) -> Result<Self::Value, S::Error> {
+        let mut vec = ArrayVec::new();
+        while let Some(value) = seq.next_element()? {
+            if vec.try_push(value).is_err() {
+                return Err(S::Error::invalid_length(N + 1, &self));
+            }
+        }
+        Ok(vec)
+    }
+}
+
+impl<'de, T: Deserialize<'de>, const N: usize> Deserialize<'de> for ArrayVec<T, N> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_seq(ArrayVecVisitor(PhantomData))
+    }
+}
+
+// ==============
+// Smart pointers
+// ==============
+
+impl<T: Serialize, A: Allocator> Serialize for MyRc<T, A> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        (**self).serialize(serializer)
+    }
+}
+
+impl<'de, T: Deserialize<'de>> Deserialize<'de> for MyRc<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        T::deserialize(deserializer).map(MyRc::new)
+    }
+}
+
+impl<T: Serialize, A: Allocator> Serialize for MyArc<T, A> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        (**self).serialize(serializer)
+    }
+}
+
+impl<'de, T: Deserialize<'de>> Deserialize<'de> for MyArc<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        T::deserialize(deserializer).map(MyArc::new)
+    }
+}
@@ -0,0 +1,872 @@
+// Purpose: The sorting algorithms behind MyVec's and MyDeque's sort methods.
+
+use core::{
+    cmp,
+    mem::{self, ManuallyDrop},
+    ptr,
+};
+
+use crate::{allocator::Allocator, my_vec::MyVec, raw_vec::RawVec};
+
+/*
+Panic safety:
+
+- A comparator can panic at any point. Whatever happens, the slice must
+  still hold every element exactly once when the panic leaves the sort.
+- The quicksort side only ever swaps, so that holds for free.
+- Insertion and merging lift elements out into a temporary or the scratch
+  buffer. Each of those moves is paired with a guard (`InsertionHole`,
+  `MergeHole`) whose Drop copies the lifted elements back into the gap.
+*/
+
+// Slices up to this long are insertion sorted outright.
+const MAX_INSERTION: usize = 20;
+
+/// Stable, adaptive merge sort.
+///
+/// Finds the runs that are already in order (reversing strictly descending
+/// ones), extends short runs with insertion sort, and merges neighbouring
+/// runs TimSort-style. The scratch buffer holds at most `len / 2` elements;
+/// it and the stack of pending runs both come from `alloc`.
+pub(crate) fn merge_sort<T, F, A>(v: &mut [T], is_less: &mut F, alloc: A)
+where
+    F: FnMut(&T, &T) -> bool,
+    A: Allocator,
+{
+    // Short runs are extended to at least this long before merging.
+    const MIN_RUN: usize = 10;
+
+    // Every zero-sized value looks the same, there is no order to restore
+    if mem::size_of::<T>() == 0 {
+        return;
+    }
+
+    let len = v.len();
+    if len <= MAX_INSERTION {
+        if len >= 2 {
+            insertion_sort_shift_left(v, 1, is_less);
+        }
+        return;
+    }
+
+    let buf = RawVec::<T, A>::with_capacity_in(len / 2, alloc);
+    let buf_ptr = buf.ptr.as_ptr().cast::<T>();
+
+    let mut runs: MyVec<Run, &A> = MyVec::new_in(&buf.alloc);
+    let mut end = 0;
+    while end < len {
+        let start = end;
+        let (streak, descending) = find_streak(&v[start..], is_less);
+        end = start + streak;
+        if descending {
+            v[start..end].reverse();
+        }
+
+        let min_end = cmp::min(start + MIN_RUN, len);
+        if end < min_end {
+            insertion_sort_shift_left(&mut v[start..min_end], end - start, is_less);
+            end = min_end;
+        }
+        runs.push(Run {
+            start,
+            len: end - start,
+        });
+
+        while let Some(r) = collapse(&runs, len) {
+            let (left, right) = (runs[r], runs[r + 1]);
+            let merged = &mut v[left.start..right.start + right.len];
+            unsafe { merge(merged, left.len, buf_ptr, is_less) };
+            runs[r] = Run {
+                start: left.start,
+                len: left.len + right.len,
+            };
+            runs.remove(r + 1);
+        }
+    }
+    debug_assert!(runs.len() == 1 && runs[0].len == len);
+}
+
+#[derive(Clone, Copy)]
+struct Run {
+    start: usize,
+    len: usize,
+}
+
+// Length of the run at the start of `v`, and whether it's strictly
+// descending. Only strict descents are reversed, which keeps it stable.
+fn find_streak<T, F>(v: &[T], is_less: &mut F) -> (usize, bool)
+where
+    F: FnMut(&T, &T) -> bool,
+{
+    let len = v.len();
+    if len < 2 {
+        return (len, false);
+    }
+
+    let mut end = 2;
+    let descending = is_less(&v[1], &v[0]);
+    if descending {
+        while end < len && is_less(&v[end], &v[end - 1]) {
+            end += 1;
+        }
+    } else {
+        while end < len && !is_less(&v[end], &v[end - 1]) {
+            end += 1;
+        }
+    }
+    (end, descending)
+}
+
+// Which pair of runs to merge next, if any, to keep the TimSort invariants
+// on the run lengths (so merges stay balanced and the stack stays small).
+// Once the last run reaches `stop` everything is merged down to one run.
+fn collapse(runs: &[Run], stop: usize) -> Option<usize> {
+    let n = runs.len();
+    if n >= 2
+        && (runs[n - 1].start + runs[n - 1].len == stop
+            || runs[n - 2].len <= runs[n - 1].len
+            || (n >= 3 && runs[n - 3].len <= runs[n - 2].len + runs[n - 1].len)
+            || (n >= 4 && runs[n - 4].len <= runs[n - 3].len + runs[n - 2].len))
+    {
+        if n >= 3 && runs[n - 3].len < runs[n - 1].len {
+            Some(n - 3)
+        } else {
+            Some(n - 2)
+        }
+    } else {
+        None
+    }
+}
+
+/// Merges the sorted `v[..mid]` and `v[mid..]`, copying the shorter one
+/// into `buf` and filling from the matching end.
+///
+/// # Safety
+///
+/// `buf` must have room for `min(mid, v.len() - mid)` elements.
+unsafe fn merge<T, F>(v: &mut [T], mid: usize, buf: *mut T, is_less: &mut F)
+where
+    F: FnMut(&T, &T) -> bool,
+{
+    let len = v.len();
+    let v = v.as_mut_ptr();
+
+    unsafe {
+        let v_mid = v.add(mid);
+        let v_end = v.add(len);
+
+        // Whatever is still in `[start, end)` of the buffer goes to `dest`
+        // when `hole` drops, whether the loop finished or `is_less` panicked.
+        let mut hole;
+
+        if mid <= len - mid {
+            // Left run is shorter: merge forwards
+            ptr::copy_nonoverlapping(v, buf, mid);
+            hole = MergeHole {
+                start: buf,
+                end: buf.add(mid),
+                dest: v,
+            };
+
+            let mut right = v_mid;
+            while hole.start < hole.end && right < v_end {
+                let to_copy = if is_less(&*right, &*hole.start) {
+                    let r = right;
+                    right = right.add(1);
+                    r
+                } else {
+                    let l = hole.start;
+                    hole.start = hole.start.add(1);
+                    l
+                };
+                ptr::copy_nonoverlapping(to_copy, hole.dest, 1);
+                hole.dest = hole.dest.add(1);
+            }
+        } else {
+            // Right run is shorter: merge backwards
+            ptr::copy_nonoverlapping(v_mid, buf, len - mid);
+            hole = MergeHole {
+                start: buf,
+                end: buf.add(len - mid),
+                dest: v_mid,
+            };
+
+            let mut out = v_end;
+            while v < hole.dest && hole.start < hole.end {
+                out = out.sub(1);
+                let to_copy = if is_less(&*hole.end.sub(1), &*hole.dest.sub(1)) {
+                    hole.dest = hole.dest.sub(1);
+                    hole.dest
+                } else {
+                    hole.end = hole.end.sub(1);
+                    hole.end
+                };
+                ptr::copy_nonoverlapping(to_copy, out, 1);
+            }
+        }
+    }
+}
+
+struct MergeHole<T> {
+    start: *mut T,
+    end: *mut T,
+    dest: *mut T,
+}
+
+impl<T> Drop for MergeHole<T> {
+    fn drop(&mut self) {
+        unsafe {
+            let len = self.end.offset_from(self.start) as usize;
+            ptr::copy_nonoverlapping(self.start, self.dest, len);
+        }
+    }
+}
+
+/// Sorts `v` assuming `v[..offset]` is already sorted.
+fn insertion_sort_shift_left<T, F>(v: &mut [T], offset: usize, is_less: &mut F)
+where
+    F: FnMut(&T, &T) -> bool,
+{
+    debug_assert!(offset >= 1 && offset <= v.len());
+    for i in offset..v.len() {
+        unsafe { insert_tail(&mut v[..=i], is_less) };
+    }
+}
+
+/// Moves the last element left into the sorted rest.
+///
+/// # Safety
+///
+/// `v.len() >= 2`.
+unsafe fn insert_tail<T, F>(v: &mut [T], is_less: &mut F)
+where
+    F: FnMut(&T, &T) -> bool,
+{
+    let arr = v.as_mut_ptr();
+    let i = v.len() - 1;
+
+    unsafe {
+        let i_ptr = arr.add(i);
+        if !is_less(&*i_ptr, &*i_ptr.sub(1)) {
+            return;
+        }
+
+        // Lift the element out; `hole` writes it back into wherever the gap
+        // ends up, including when `is_less` panics.
+        let tmp = ManuallyDrop::new(ptr::read(i_ptr));
+        let mut hole = InsertionHole {
+            src: &*tmp,
+            dest: i_ptr.sub(1),
+        };
+        ptr::copy_nonoverlapping(hole.dest, i_ptr, 1);
+
+        for j in (0..i - 1).rev() {
+            let j_ptr = arr.add(j);
+            if !is_less(&*tmp, &*j_ptr) {
+                break;
+            }
+            ptr::copy_nonoverlapping(j_ptr, hole.dest, 1);
+            hole.dest = j_ptr;
+        }
+    }
+}
+
+/// Moves the first element right into the rest, stopping at the first
+/// element that isn't smaller.
+///
+/// # Safety
+///
+/// `v.len() >= 2`.
+unsafe fn insert_head<T, F>(v: &mut [T], is_less: &mut F)
+where
+    F: FnMut(&T, &T) -> bool,
+{
+    let arr = v.as_mut_ptr();
+    let len = v.len();
+
+    unsafe {
+        if !is_less(&*arr.add(1), &*arr) {
+            return;
+        }
+
+        let tmp = ManuallyDrop::new(ptr::read(arr));
+        let mut hole = InsertionHole {
+            src: &*tmp,
+            dest: arr.add(1),
+        };
+        ptr::copy_nonoverlapping(arr.add(1), arr, 1);
+
+        for i in 2..len {
+            let i_ptr = arr.add(i);
+            if !is_less(&*i_ptr, &*tmp) {
+                break;
+            }
+            ptr::copy_nonoverlapping(i_ptr, hole.dest, 1);
+            hole.dest = i_ptr;
+        }
+    }
+}
+
+struct InsertionHole<T> {
+    src: *const T,
+    dest: *mut T,
+}
+
+impl<T> Drop for InsertionHole<T> {
+    fn drop(&mut self) {
+        unsafe { ptr::copy_nonoverlapping(self.src, self.dest, 1) }
+    }
+}
+
+/// Unstable pattern-defeating quicksort.
+///
+/// Quicksort with median-of-medians pivots that falls back to insertion
+/// sort for short slices, spots slices that are already (nearly) sorted,
+/// groups runs of equal elements, shuffles a few elements after a badly
+/// unbalanced partition, and switches to heapsort if that keeps happening,
+/// so the worst case stays O(n log n).
+pub(crate) fn quicksort<T, F>(v: &mut [T], is_less: &mut F)
+where
+    F: FnMut(&T, &T) -> bool,
+{
+    if mem::size_of::<T>() == 0 {
+        return;
+    }
+
+    // Allowed number of imbalanced partitions before falling back to heapsort
+    let limit = usize::BITS - v.len().leading_zeros();
+    recurse(v, is_less, None, limit);
+}
+
+// `pred` is the pivot of the partition to the left of `v`, if any: every
+// element of `v` is at least that big.
+fn recurse<'a, T, F>(mut v: &'a mut [T], is_less: &mut F, mut pred: Option<&'a T>, mut limit: u32)
+where
+    F: FnMut(&T, &T) -> bool,
+{
+    let mut was_balanced = true;
+    let mut was_partitioned = true;
+
+    loop {
+        let len = v.len();
+        if len <= MAX_INSERTION {
+            if len >= 2 {
+                insertion_sort_shift_left(v, 1, is_less);
+            }
+            return;
+        }
+
+        if limit == 0 {
+            heapsort(v, is_less);
+            return;
+        }
+
+        if !was_balanced {
+            break_patterns(v);
+            limit -= 1;
+        }
+
+        let (pivot, likely_sorted) = choose_pivot(v, is_less);
+
+        // The last partition was clean and the pivot sample was in order,
+        // so try to finish off with a bounded insertion sort.
+        if was_balanced && was_partitioned && likely_sorted && partial_insertion_sort(v, is_less) {
+            return;
+        }
+
+        // The pivot equals the predecessor: the elements equal to it go left
+        // and are done, which makes runs of duplicates cheap.
+        if let Some(p) = pred
+            && !is_less(p, &v[pivot])
+        {
+            let mid = partition_equal(v, pivot, is_less);
+            v = &mut v[mid..];
+            continue;
+        }
+
+        let (mid, was_p) = partition(v, pivot, is_less);
+        was_balanced = cmp::min(mid, len - mid) >= len / 8;
+        was_partitioned = was_p;
+
+        let (left, right) = v.split_at_mut(mid);
+        let (pivot, right) = right.split_at_mut(1);
+        let pivot = &pivot[0];
+
+        // Recurse into the shorter side to bound the stack depth
+        if left.len() < right.len() {
+            recurse(left, is_less, pred, limit);
+            v = right;
+            pred = Some(pivot);
+        } else {
+            recurse(right, is_less, Some(pivot), limit);
+            v = left;
+        }
+    }
+}
+
+/// Partitions around `v[pivot]`: smaller elements end up before the
+/// returned index, the pivot at it, and the rest after. Also reports
+/// whether nothing had to be swapped.
+fn partition<T, F>(v: &mut [T], pivot: usize, is_less: &mut F) -> (usize, bool)
+where
+    F: FnMut(&T, &T) -> bool,
+{
+    // The pivot is parked at the front and compared through a reference,
+    // the loop below only swaps the elements after it.
+    v.swap(0, pivot);
+    let (pivot_slot, rest) = v.split_at_mut(1);
+    let pivot = &pivot_slot[0];
+
+    let mut l = 0;
+    let mut r = rest.len();
+    while l < r && is_less(&rest[l], pivot) {
+        l += 1;
+    }
+    while l < r && !is_less(&rest[r - 1], pivot) {
+        r -= 1;
+    }
+    let was_partitioned = l >= r;
+
+    loop {
+        while l < r && is_less(&rest[l], pivot) {
+            l += 1;
+        }
+        while l < r && !is_less(&rest[r - 1], pivot) {
+            r -= 1;
+        }
+        if l >= r {
+            break;
+        }
+        r -= 1;
+        rest.swap(l, r);
+        l += 1;
+    }
+
+    // `rest[l - 1]` is the last smaller element; the pivot takes its place
+    v.swap(0, l);
+    (l, was_partitioned)
+}
+
+/// Moves the elements equal to `v[pivot]` to the front, assuming none are
+/// smaller, and returns how many there are.
+fn partition_equal<T, F>(v: &mut [T], pivot: usize, is_less: &mut F) -> usize
+where
+    F: FnMut(&T, &T) -> bool,
+{
+    v.swap(0, pivot);
+    let (pivot_slot, rest) = v.split_at_mut(1);
+    let pivot = &pivot_slot[0];
+
+    let mut l = 0;
+    let mut r = rest.len();
+    loop {
+        while l < r && !is_less(pivot, &rest[l]) {
+            l += 1;
+        }
+        while l < r && is_less(pivot, &rest[r - 1]) {
+            r -= 1;
+        }
+        if l >= r {
+            break;
+        }
+        r -= 1;
+        rest.swap(l, r);
+        l += 1;
+    }
+
+    // The pivot itself plus everything equal to it
+    l + 1
+}
+
+/// Picks a pivot from a median of three (or of three medians of three for
+/// longer slices). Reports whether the samples were already in order; if
+/// they were mostly in reverse order the slice is reversed first.
+fn choose_pivot<T, F>(v: &mut [T], is_less: &mut F) -> (usize, bool)
+where
+    F: FnMut(&T, &T) -> bool,
+{
+    const SHORTEST_MEDIAN_OF_MEDIANS: usize = 50;
+    const MAX_SWAPS: usize = 4 * 3;
+
+    let len = v.len();
+    let mut a = len / 4;
+    let mut b = len / 4 * 2;
+    let mut c = len / 4 * 3;
+    let mut swaps = 0;
+
+    if len >= 8 {
+        let mut sort2 = |a: &mut usize, b: &mut usize| {
+            if is_less(&v[*b], &v[*a]) {
+                mem::swap(a, b);
+                swaps += 1;
+            }
+        };
+        let mut sort3 = |a: &mut usize, b: &mut usize, c: &mut usize| {
+            sort2(a, b);
+            sort2(b, c);
+            sort2(a, b);
+        };
+
+        if len >= SHORTEST_MEDIAN_OF_MEDIANS {
+            let mut median_of_neighbours = |a: &mut usize| {
+                let (mut lo, mut hi) = (*a - 1, *a + 1);
+                sort3(&mut lo, a, &mut hi);
+            };
+            median_of_neighbours(&mut a);
+            median_of_neighbours(&mut b);
+            median_of_neighbours(&mut c);
+        }
+
+        sort3(&mut a, &mut b, &mut c);
+    }
+
+    if swaps < MAX_SWAPS {
+        (b, swaps == 0)
+    } else {
+        v.reverse();
+        (len - 1 - b, true)
+    }
+}
+
+/// Sorts a nearly sorted slice with a few insertion steps. Gives up (and
+/// returns false) once that looks like it won't be enough.
+fn partial_insertion_sort<T, F>(v: &mut [T], is_less: &mut F) -> bool
+where
+    F: FnMut(&T, &T) -> bool,
+{
+    const MAX_STEPS: usize = 5;
+    const SHORTEST_SHIFTING: usize = 50;
+
+    let len = v.len();
+    let mut i = 1;
+
+    for _ in 0..MAX_STEPS {
+        while i < len && !is_less(&v[i], &v[i - 1]) {
+            i += 1;
+        }
+        if i == len {
+            return true;
+        }
+        // Not worth shifting elements around on short slices
+        if len < SHORTEST_SHIFTING {
+            return false;
+        }
+
+        v.swap(i - 1, i);
+        if i >= 2 {
+            unsafe { insert_tail(&mut v[..i], is_less) };
+        }
+        if len - i >= 2 {
+            unsafe { insert_head(&mut v[i..], is_less) };
+        }
+    }
+
+    false
+}
+
+/// Scatters a few elements to break up patterns that keep producing
+/// unbalanced partitions.
+fn break_patterns<T>(v: &mut [T]) {
+    let len = v.len();
+    if len < 8 {
+        return;
+    }
+
+    // Xorshift: cheap, and deterministic so sorting stays reproducible
+    let mut random = len as u32;
+    let mut gen_u32 = || {
+        random ^= random << 13;
+        random ^= random >> 17;
+        random ^= random << 5;
+        random
+    };
+    let mut gen_usize = || {
+        if usize::BITS <= 32 {
+            gen_u32() as usize
+        } else {
+            (((gen_u32() as u64) << 32) | (gen_u32() as u64)) as usize
+        }
+    };
+
+    let modulus = len.next_power_of_two();
+    let pos = len / 4 * 2;
+    for i in 0..3 {
+        let mut other = gen_usize() & (modulus - 1);
+        if other >= len {
+            other -= len;
+        }
+        v.swap(pos - 1 + i, other);
+    }
+}
+
+/// Worst-case O(n log n) fallback. Only swaps, so panic safe as is.
+fn heapsort<T, F>(v: &mut [T], is_less: &mut F)
+where
+    F: FnMut(&T, &T) -> bool,
+{
+    let mut sift_down = |v: &mut [T], mut node: usize| {
+        loop {
+            let mut child = 2 * node + 1;
+            if child >= v.len() {
+                break;
+            }
+            if child + 1 < v.len() && is_less(&v[child], &v[child + 1]) {
+                child += 1;
+            }
+            if !is_less(&v[node], &v[child]) {
+                break;
+            }
+            v.swap(node, child);
+            node = child;
+        }
+    };
+
+    for i in (0..v.len() / 2).rev() {
+        sift_down(v, i);
+    }
+    for i in (1..v.len()).rev() {
+        v.swap(0, i);
+        sift_down(&mut v[..i], 0);
+    }
+}
+
+/// Reorders `v` so the element at `index` is where it would be if sorted,
+/// with nothing bigger before it and nothing smaller after it.
+pub(crate) fn select_nth_unstable<'a, T, F>(
+    v: &'a mut [T],
+    index: usize,
+    is_less: &mut F,
+) -> (&'a mut [T], &'a mut T, &'a mut [T])
+where
+    F: FnMut(&T, &T) -> bool,
+{
+    let len = v.len();
+    assert!(
+        index < len,
+        "partition_at_index index {index} greater than length of slice {len}"
+    );
+
+    if mem::size_of::<T>() == 0 {
+        // Nothing to reorder
+    } else if index == len - 1 {
+        let max = max_index(v, is_less);
+        v.swap(max, index);
+    } else if index == 0 {
+        let min = max_index(v, &mut |a, b| is_less(b, a));
+        v.swap(min, index);
+    } else {
+        select_loop(v, index, is_less, None);
+    }
+
+    let (left, rest) = v.split_at_mut(index);
+    let (nth, right) = rest.split_at_mut(1);
+    (left, &mut nth[0], right)
+}
+
+fn max_index<T, F>(v: &[T], is_less: &mut F) -> usize
+where
+    F: FnMut(&T, &T) -> bool,
+{
+    let mut max = 0;
+    for i in 1..v.len() {
+        if !is_less(&v[i], &v[max]) {
+            max = i;
+        }
+    }
+    max
+}
+
+// Quickselect on the same partitioning, with heapsort as the fallback.
+fn select_loop<'a, T, F>(
+    mut v: &'a mut [T],
+    mut index: usize,
+    is_less: &mut F,
+    mut pred: Option<&'a T>,
+) where
+    F: FnMut(&T, &T) -> bool,
+{
+    // Short slices are cheaper to just sort
+    const MAX_INSERTION: usize = 10;
+    let mut limit = 16;
+
+    loop {
+        if v.len() <= MAX_INSERTION {
+            if v.len() >= 2 {
+                insertion_sort_shift_left(v, 1, is_less);
+            }
+            return;
+        }
+
+        if limit == 0 {
+            heapsort(v, is_less);
+            return;
+        }
+        limit -= 1;
+
+        let (pivot, _) = choose_pivot(v, is_less);
+
+        if let Some(p) = pred
+            && !is_less(p, &v[pivot])
+        {
+            let mid = partition_equal(v, pivot, is_less);
+            // Everything before `mid` is equal, so any index in there is done
+            if mid > index {
+                return;
+            }
+            v = &mut v[mid..];
+            index -= mid;
+            pred = None;
+            continue;
+        }
+
+        let (mid, _) = partition(v, pivot, is_less);
+        let (left, right) = v.split_at_mut(mid);
+        let (pivot, right) = right.split_at_mut(1);
+        let pivot = &pivot[0];
+
+        if mid < index {
+            v = right;
+            index = index - mid - 1;
+            pred = Some(pivot);
+        } else if mid > index {
+            v = left;
+        } else {
+            return;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        cell::Cell,
+        panic::{self, AssertUnwindSafe},
+    };
+
+    use super::*;
+    use crate::allocator::Global;
+
+    // Deterministic pseudo-random values, enough to reach every code path.
+    fn xorshift(seed: u64, len: usize) -> Vec<u64> {
+        let mut state = seed | 1;
+        (0..len)
+            .map(|_| {
+                state ^= state << 13;
+                state ^= state >> 7;
+                state ^= state << 17;
+                state % 1000
+            })
+            .collect()
+    }
+
+    struct Tracked<'a> {
+        value: u64,
+        drops: &'a Cell<usize>,
+    }
+
+    type SortFn = fn(&mut [Tracked<'_>], &mut dyn FnMut(&Tracked<'_>, &Tracked<'_>) -> bool);
+
+    impl Drop for Tracked<'_> {
+        fn drop(&mut self) {
+            self.drops.set(self.drops.get() + 1);
+        }
+    }
+
+    // Sorts with a comparator that panics on its `panic_at`-th call and
+    // checks every element is still there exactly once afterwards.
+    fn assert_survives_panic(len: usize, panic_at: usize, sort: SortFn) {
+        let drops = Cell::new(0);
+        let values = xorshift(len as u64 + panic_at as u64, len);
+        let mut v: Vec<_> = values
+            .iter()
+            .map(|&value| Tracked {
+                value,
+                drops: &drops,
+            })
+            .collect();
+
+        let calls = Cell::new(0);
+        let result = panic::catch_unwind(AssertUnwindSafe(|| {
+            sort(&mut v, &mut |a, b| {
+                calls.set(calls.get() + 1);
+                if calls.get() == panic_at {
+                    panic!("comparator gave up");
+                }
+                a.value < b.value
+            })
+        }));
+
+        if calls.get() >= panic_at {
+            assert!(result.is_err());
+        }
+        assert_eq!(drops.get(), 0);
+        let mut after: Vec<_> = v.iter().map(|t| t.value).collect();
+        let mut before = values;
+        after.sort_unstable();
+        before.sort_unstable();
+        assert_eq!(after, before);
+        drop(v);
+        assert_eq!(drops.get(), len);
+    }
+
+    #[test]
+    fn merge_sort_is_panic_safe() {
+        for len in [5, 21, 64, 300] {
+            for panic_at in [1, 7, len / 2, len * 3] {
+                assert_survives_panic(len, panic_at, |v, is_less| {
+                    merge_sort(v, &mut |a, b| is_less(a, b), Global)
+                });
+            }
+        }
+    }
+
+    #[test]
+    fn quicksort_is_panic_safe() {
+        for len in [5, 21, 64, 300] {
+            for panic_at in [1, 7, len / 2, len * 3] {
+                assert_survives_panic(len, panic_at, |v, is_less| {
+                    quicksort(v, &mut |a, b| is_less(a, b))
+                });
+            }
+        }
+    }
+
+    #[test]
+    fn select_nth_is_panic_safe() {
+        for len in [5, 21, 64, 300] {
+            for panic_at in [1, 7, len / 2] {
+                assert_survives_panic(len, panic_at, |v, is_less| {
+                    select_nth_unstable(v, v.len() / 3, &mut |a, b| is_less(a, b));
+                });
+            }
+        }
+    }
+
+    #[test]
+    fn merge_sort_finds_runs() {
+        // Ascending, strictly descending and flat runs back to back
+        let mut v: Vec<u32> = (0..40).chain((0..40).rev()).chain([7; 40]).collect();
+        let mut expected = v.clone();
+        expected.sort();
+        merge_sort(&mut v, &mut |a, b| a < b, Global);
+        assert_eq!(v, expected);
+    }
+
+    #[test]
+    fn quicksort_falls_back_to_heapsort() {
+        // With no imbalance budget the first round goes straight to heapsort
+        let mut v = xorshift(3, 200);
+        let mut expected = v.clone();
+        expected.sort();
+        recurse(&mut v, &mut |a, b| a < b, None, 0);
+        assert_eq!(v, expected);
+    }
+
+    #[test]
+    fn sorts_zero_sized() {
+        let mut v = [(); 100];
+        merge_sort(&mut v, &mut |_, _| true, Global);
+        quicksort(&mut v, &mut |_, _| true);
+        select_nth_unstable(&mut v, 50, &mut |_, _| true);
+    }
+}
@@ -0,0 +1,23 @@
+// Purpose: the atomics behind the lock-free code, swapped for loom's under the `loom` feature.
+
+/*
+Lock-free code (MyArc's counts so far) imports from here instead of
+core::sync::atomic, so `--features loom` puts all of it under the model
+checker at once; tests/loom_*.rs drive it. Anything new that synchronises
+through atomics alone belongs here too.
+
+Tracking keeps core's atomics: it's a `const` static-friendly measuring
+tool, loom's atomics can't be built in a const, and modelling the
+allocator's bookkeeping would only multiply the interleavings to explore.
+*/
+
+#[cfg(not(feature = "loom"))]
+pub(crate) use core::{
+    hint,
+    sync::atomic::{AtomicUsize, Ordering, fence},
+};
+#[cfg(feature = "loom")]
+pub(crate) use loom::{
+    hint,
+    sync::atomic::{AtomicUsize, Ordering, fence},
+};
@@ -0,0 +1,287 @@
+// Purpose: Allocator wrapper that keeps usage statistics, for leak checks and diagnostics.
+
+use alloc::vec::Vec;
+use core::{
+    alloc::Layout,
+    cell::UnsafeCell,
+    cmp::Reverse,
+    fmt, hint,
+    ops::{Deref, DerefMut},
+    panic::Location,
+    ptr::NonNull,
+    sync::atomic::{AtomicBool, AtomicUsize, Ordering},
+};
+
+use crate::allocator::{AllocError, Allocator, Global};
+
+/// What a single line of code allocated through a [`Tracking`] allocator.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CallSite {
+    /// The line that asked for the memory. The collections' growth paths
+    /// (`push`, `reserve`, `insert`, `new_in`, ...) are `#[track_caller]`,
+    /// so this is the user's call rather than somewhere in this crate.
+    /// Growth from a `Drop` (a `Splice` writing its tail) stays in here.
+    pub location: &'static Location<'static>,
+    /// Successful `allocate` and `grow` calls made from here.
+    pub count: usize,
+    /// Bytes those calls added to the live total.
+    pub bytes: usize,
+}
+
+/// Wraps another allocator and records live bytes, peak bytes, the number
+/// of allocations and where they came from.
+///
+/// ```
+/// use my_std::prelude::*;
+/// use my_std::tracking::Tracking;
+///
+/// let tracker = Tracking::new();
+/// {
+///     let arc = MyArc::new_in(5, &tracker);
+///     let weak = arc.downgrade();
+///     drop(arc);
+///     assert!(weak.upgrade().is_none());
+///     assert!(tracker.live_bytes() > 0);
+/// }
+/// assert_eq!(tracker.live_bytes(), 0);
+/// ```
+pub struct Tracking<A: Allocator = Global> {
+    inner: A,
+    live: AtomicUsize,
+    peak: AtomicUsize,
+    allocations: AtomicUsize,
+    sites: SpinLock<Vec<CallSite>>,
+}
+
+impl Tracking {
+    /// Tracks allocations on the global heap. Being `const`, it can be a `static`.
+    pub const fn new() -> Self {
+        Self::new_in(Global)
+    }
+}
+
+impl<A: Allocator> Tracking<A> {
+    pub const fn new_in(inner: A) -> Self {
+        Self {
+            inner,
+            live: AtomicUsize::new(0),
+            peak: AtomicUsize::new(0),
+            allocations: AtomicUsize::new(0),
+            sites: SpinLock::new(Vec::new()),
+        }
+    }
+
+    /// The allocator doing the actual work.
+    pub fn inner(&self) -> &A {
+        &self.inner
+    }
+
+    /// Bytes handed out and not yet given back.
+    pub fn live_bytes(&self) -> usize {
+        self.live.load(Ordering::SeqCst)
+    }
+
+    /// The most bytes that were ever live at once.
+    pub fn peak_bytes(&self) -> usize {
+        self.peak.load(Ordering::SeqCst)
+    }
+
+    /// Successful `allocate` and `grow` calls.
+    pub fn allocation_count(&self) -> usize {
+        self.allocations.load(Ordering::SeqCst)
+    }
+
+    /// Where the allocations came from, busiest call site first.
+    pub fn call_sites(&self) -> Vec<CallSite> {
+        let mut sites = self.sites.lock().clone();
+        sites.sort_by_key(|site| Reverse(site.count));
+        sites
+    }
+
+    fn record(&self, location: &'static Location<'static>, bytes: usize) {
+        let live = self.live.fetch_add(bytes, Ordering::SeqCst) + bytes;
+        self.peak.fetch_max(live, Ordering::SeqCst);
+        self.allocations.fetch_add(1, Ordering::SeqCst);
+
+        let mut sites = self.sites.lock();
+        match sites.iter_mut().find(|site| site.location == location) {
+            Some(site) => {
+                site.count += 1;
+                site.bytes += bytes;
+            }
+            None => sites.push(CallSite {
+                location,
+                count: 1,
+                bytes,
+            }),
+        }
+    }
+
+    fn release(&self, bytes: usize) {
+        self.live.fetch_sub(bytes, Ordering::SeqCst);
+    }
+}
+
+unsafe impl<A: Allocator> Allocator for Tracking<A> {
+    #[track_caller]
+    fn allocate(&self, layout: Layout) -> Result<NonNull<u8>, AllocError> {
+        let location = Location::caller();
+        let ptr = self.inner.allocate(layout)?;
+        self.record(location, layout.size());
+        Ok(ptr)
+    }
+
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+        unsafe { self.inner.deallocate(ptr, layout) };
+        self.release(layout.size());
+    }
+
+    #[track_caller]
+    unsafe fn grow(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<u8>, AllocError> {
+        let location = Location::caller();
+        let ptr = unsafe { self.inner.grow(ptr, old_layout, new_layout)? };
+        self.record(location, new_layout.size() - old_layout.size());
+        Ok(ptr)
+    }
+
+    unsafe fn shrink(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<u8>, AllocError> {
+        let ptr = unsafe { self.inner.shrink(ptr, old_layout, new_layout)? };
+        self.release(old_layout.size() - new_layout.size());
+        Ok(ptr)
+    }
+}
+
+// A spin lock instead of std's Mutex, so tracking works without std too.
+// It's only held for a scan of the call-site list, and there's no
+// poisoning: a panic elsewhere can't take the statistics down with it.
+struct SpinLock<T> {
+    locked: AtomicBool,
+    value: UnsafeCell<T>,
+}
+
+unsafe impl<T: Send> Sync for SpinLock<T> {}
+
+impl<T> SpinLock<T> {
+    const fn new(value: T) -> Self {
+        SpinLock {
+            locked: AtomicBool::new(false),
+            value: UnsafeCell::new(value),
+        }
+    }
+
+    fn lock(&self) -> SpinGuard<'_, T> {
+        while self
+            .locked
+            .compare_exchange_weak(false, true, Ordering::Acquire, Ordering::Relaxed)
+            .is_err()
+        {
+            hint::spin_loop();
+        }
+        SpinGuard { lock: self }
+    }
+}
+
+struct SpinGuard<'a, T> {
+    lock: &'a SpinLock<T>,
+}
+
+impl<T> Deref for SpinGuard<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        unsafe { &*self.lock.value.get() }
+    }
+}
+
+impl<T> DerefMut for SpinGuard<'_, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        unsafe { &mut *self.lock.value.get() }
+    }
+}
+
+impl<T> Drop for SpinGuard<'_, T> {
+    fn drop(&mut self) {
+        self.lock.locked.store(false, Ordering::Release);
+    }
+}
+
+impl Default for Tracking {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<A: Allocator> fmt::Debug for Tracking<A> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Tracking")
+            .field("live_bytes", &self.live_bytes())
+            .field("peak_bytes", &self.peak_bytes())
+            .field("allocation_count", &self.allocation_count())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{my_linked_list::LinkedList, my_vec::MyVec};
+
+    #[test]
+    fn test_live_and_peak() {
+        let tracker = Tracking::new();
+        let small = Layout::array::<u8>(16).unwrap();
+        let big = Layout::array::<u8>(64).unwrap();
+
+        let a = tracker.allocate(small).unwrap();
+        let b = tracker.allocate(big).unwrap();
+        assert_eq!(tracker.live_bytes(), 80);
+
+        unsafe {
+            tracker.deallocate(b, big);
+            assert_eq!(tracker.live_bytes(), 16);
+
+            let a = tracker.grow(a, small, big).unwrap();
+            assert_eq!(tracker.live_bytes(), 64);
+            let a = tracker.shrink(a, big, small).unwrap();
+            assert_eq!(tracker.live_bytes(), 16);
+            tracker.deallocate(a, small);
+        }
+
+        assert_eq!(tracker.live_bytes(), 0);
+        assert_eq!(tracker.peak_bytes(), 80);
+        assert_eq!(tracker.allocation_count(), 3);
+    }
+
+    #[test]
+    fn test_call_sites_point_at_the_caller() {
+        let tracker = Tracking::new();
+        let mut vec = MyVec::new_in(&tracker);
+        let mut list = LinkedList::new_in(&tracker);
+        for i in 0..10 {
+            vec.push(i);
+            list.push_back(i);
+        }
+
+        let sites = tracker.call_sites();
+        assert!(sites.iter().all(|site| site.location.file() == file!()));
+        // Busiest first: ten nodes from `push_back`, then the vector's
+        // allocation and two regrowths from `push`
+        let counts: Vec<_> = sites.iter().map(|site| site.count).collect();
+        assert_eq!(counts, [10, 3]);
+        assert_eq!(sites[1].location.line() + 1, sites[0].location.line());
+
+        drop(vec);
+        drop(list);
+        assert_eq!(tracker.live_bytes(), 0);
+    }
+}
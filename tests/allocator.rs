@@ -0,0 +1,67 @@
+#![cfg(feature = "alloc")]
+
+use std::{alloc::Layout, cell::Cell, ptr::NonNull};
+
+use my_std::allocator::{AllocError, Allocator, Global};
+use my_std::prelude::*;
+
+/// Forwards to `Global` and keeps a tally of outstanding blocks.
+#[derive(Default)]
+struct Counting {
+    live: Cell<isize>,
+    total: Cell<usize>,
+}
+
+unsafe impl Allocator for Counting {
+    fn allocate(&self, layout: Layout) -> Result<NonNull<u8>, AllocError> {
+        self.live.set(self.live.get() + 1);
+        self.total.set(self.total.get() + 1);
+        Global.allocate(layout)
+    }
+
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+        self.live.set(self.live.get() - 1);
+        unsafe { Global.deallocate(ptr, layout) }
+    }
+}
+
+#[test]
+fn every_type_allocates_through_the_given_allocator() {
+    let counting = Counting::default();
+
+    {
+        let mut vec = MyVec::new_in(&counting);
+        for i in 0..100 {
+            vec.push(i);
+        }
+        assert_eq!(vec.as_slice().iter().sum::<i32>(), 4950);
+
+        let mut deque = MyDeque::with_capacity_in(2, &counting);
+        deque.push_back(1);
+        deque.push_front(0);
+        deque.push_back(2);
+        assert_eq!((&deque).into_iter().copied().collect::<Vec<_>>(), [0, 1, 2]);
+
+        let mut list = LinkedList::new_in(&counting);
+        list.push_back(String::from("b"));
+        list.push_front(String::from("a"));
+        assert_eq!(list.pop_back().as_deref(), Some("b"));
+
+        let rc = MyRc::new_in(String::from("rc"), &counting);
+        let rc2 = rc.clone();
+        assert_eq!(rc2.get_count(), 2);
+        drop(rc);
+        assert_eq!(rc2.try_unwrap().ok().as_deref(), Some("rc"));
+
+        let arc = MyArc::new_in(vec![1, 2, 3], &counting);
+        let weak = arc.downgrade();
+        assert_eq!(weak.upgrade().map(|a| a.len()), Some(3));
+        drop(arc);
+        assert!(weak.upgrade().is_none());
+
+        assert!(counting.live.get() > 0);
+    }
+
+    assert!(counting.total.get() >= 6);
+    assert_eq!(counting.live.get(), 0);
+}
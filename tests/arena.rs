@@ -0,0 +1,80 @@
+#![cfg(feature = "alloc")]
+
+use my_std::allocator::Allocator;
+use my_std::arena::Arena;
+use my_std::bump::Bump;
+use my_std::prelude::*;
+
+#[test]
+fn linked_list_entirely_in_a_bump() {
+    let mut bump = Bump::new();
+    {
+        // The list header, its nodes and the elements all live in the bump
+        let list = bump.alloc(LinkedList::new_in(&bump));
+        for i in 0..1000u64 {
+            list.push_back(i);
+            list.push_front(i);
+        }
+        assert_eq!(list.len(), 2000);
+        assert_eq!(list.iter().copied().sum::<u64>(), 2 * 999 * 1000 / 2);
+        assert_eq!(list.pop_front(), Some(999));
+        assert_eq!(list.pop_back(), Some(999));
+    }
+    assert!(bump.allocated_bytes() > 0);
+
+    // Freeing the request's worth of nodes at once, then reusing the memory
+    bump.reset();
+    let kept = bump.allocated_bytes();
+    let mut list = LinkedList::new_in(&bump);
+    list.extend(0..100u64);
+    assert_eq!(list.len(), 100);
+    assert_eq!(bump.allocated_bytes(), kept);
+}
+
+#[test]
+fn linked_list_of_arena_values() {
+    let mut arena = Arena::new();
+    for _ in 0..3 {
+        {
+            // Nodes come from the arena's bump, the strings are arena values
+            let mut list = LinkedList::new_in(&arena);
+            for word in ["per", "request", "scratch"] {
+                list.push_back(arena.alloc(word.to_uppercase()).as_str());
+            }
+            let joined: Vec<&str> = list.iter().copied().collect();
+            assert_eq!(joined, ["PER", "REQUEST", "SCRATCH"]);
+            assert_eq!(arena.len(), 3);
+        }
+        arena.reset();
+        assert!(arena.is_empty());
+    }
+}
+
+#[test]
+fn vec_and_deque_share_a_bump() {
+    let bump = Bump::new();
+    let mut vec = MyVec::new_in(&bump);
+    let mut deque = MyDeque::new_in(&bump);
+    for i in 0..50 {
+        vec.push(i);
+        deque.push_front(i);
+    }
+    assert_eq!(vec.as_slice().iter().sum::<i32>(), 1225);
+    assert_eq!(deque.pop_back(), Some(0));
+    assert_eq!(deque.len(), 49);
+}
+
+#[test]
+fn bump_respects_alignment() {
+    #[repr(align(64))]
+    struct Aligned(#[allow(dead_code)] u8);
+
+    let bump = Bump::new();
+    bump.alloc(1u8);
+    let aligned = bump.alloc(Aligned(2)) as *mut Aligned;
+    assert_eq!(aligned as usize % 64, 0);
+
+    let layout = std::alloc::Layout::from_size_align(10, 32).unwrap();
+    let ptr = bump.allocate(layout).unwrap();
+    assert_eq!(ptr.as_ptr() as usize % 32, 0);
+}
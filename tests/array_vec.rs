@@ -0,0 +1,42 @@
+use my_std::array_vec::{ArrayVec, CapacityError};
+
+// A fixed-size sample window, the kind of thing a hot loop keeps on the stack.
+fn push_sample(window: &mut ArrayVec<u32, 4>, sample: u32) {
+    if let Err(err) = window.try_push(sample) {
+        window.remove(0);
+        window.push(err.element());
+    }
+}
+
+#[test]
+fn bounded_window() {
+    let mut window = ArrayVec::new();
+    for sample in 1..=6 {
+        push_sample(&mut window, sample);
+    }
+    assert_eq!(window, [3, 4, 5, 6]);
+    assert_eq!(window.iter().sum::<u32>(), 18);
+}
+
+#[test]
+fn errors_are_values() {
+    let mut vec: ArrayVec<&str, 1> = ArrayVec::new();
+    vec.try_insert(0, "kept").unwrap();
+    let err: CapacityError<&str> = vec.try_insert(0, "rejected").unwrap_err();
+    assert_eq!(err.element(), "rejected");
+
+    let too_long: Result<ArrayVec<u8, 2>, _> = ArrayVec::try_from(&b"abc"[..]);
+    assert_eq!(too_long.unwrap_err(), CapacityError::new(()));
+}
+
+#[test]
+fn slice_and_drain_api() {
+    let mut vec: ArrayVec<i32, 8> = [5, 1, 4, 2, 3].into_iter().collect();
+    vec.sort_unstable();
+    assert_eq!(vec, [1, 2, 3, 4, 5]);
+
+    let evens: Vec<_> = vec.extract_if(|x| *x % 2 == 0).collect();
+    assert_eq!(evens, [2, 4]);
+    vec.drain(..1);
+    assert_eq!(vec.into_iter().collect::<Vec<_>>(), [3, 5]);
+}
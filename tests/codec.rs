@@ -0,0 +1,158 @@
+#![cfg(feature = "alloc")]
+
+#[cfg(feature = "std")]
+use std::{io, thread};
+
+use my_std::{
+    codec::{self, DecodeError, Encoder},
+    my_arc::MyArc,
+    my_deque::MyDeque,
+    my_linked_list::LinkedList,
+    my_rc::MyRc,
+    my_vec::MyVec,
+};
+
+#[test]
+#[cfg(feature = "std")]
+fn deque_snapshots_over_a_pipe() {
+    let (mut reader, mut writer) = io::pipe().unwrap();
+
+    let sender = thread::spawn(move || {
+        let mut deque = MyDeque::with_capacity(4);
+        for i in 0..10u32 {
+            deque.push_back(i);
+            if deque.len() > 4 {
+                deque.pop_front();
+            }
+            codec::write_frame(&mut writer, &deque).unwrap();
+        }
+    });
+
+    let mut snapshots = Vec::new();
+    loop {
+        match codec::read_frame::<MyDeque<u32>, _>(&mut reader) {
+            Ok(deque) => snapshots.push(deque.into_iter().collect::<Vec<_>>()),
+            Err(err) if err.kind() == io::ErrorKind::UnexpectedEof => break,
+            Err(err) => panic!("{err}"),
+        }
+    }
+    sender.join().unwrap();
+
+    assert_eq!(snapshots.len(), 10);
+    assert_eq!(snapshots[2], [0, 1, 2]);
+    assert_eq!(snapshots[9], [6, 7, 8, 9]);
+}
+
+#[test]
+#[cfg(feature = "std")]
+fn bogus_frame_length_does_not_allocate() {
+    // A frame claiming to be u64::MAX / 2 bytes long, then nothing
+    let mut prefix = Encoder::new();
+    prefix.write_varint(u64::MAX / 2);
+    let bytes = prefix.finish();
+
+    let err = codec::read_frame::<MyVec<u8>, _>(&mut &bytes[..]).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+}
+
+#[test]
+fn nested_collections_round_trip() {
+    let mut list = LinkedList::new();
+    list.push_back(MyVec::from([-1i64, 0, i64::MAX]));
+    list.push_back(MyVec::new());
+
+    let bytes = codec::encode_to_vec(&list);
+    let back: LinkedList<MyVec<i64>> = codec::decode_from_slice(&bytes).unwrap();
+    assert_eq!(back.len(), 2);
+    assert_eq!(back.iter().next().unwrap()[..], [-1, 0, i64::MAX]);
+}
+
+#[test]
+fn sharing_survives_across_values() {
+    let shared = MyArc::new(MyVec::from(*b"payload"));
+    let first = MyVec::from([shared.clone()]);
+    let second = MyDeque::from(vec![shared.clone(), MyArc::new(MyVec::new())]);
+
+    // Both values go through one encoder, so the second refers back to the first
+    let mut encoder = Encoder::new();
+    encoder.encode(&first);
+    encoder.encode(&second);
+    let bytes = encoder.finish();
+
+    let mut decoder = codec::Decoder::new(&bytes);
+    let first: MyVec<MyArc<MyVec<u8>>> = decoder.decode().unwrap();
+    let second: MyDeque<MyArc<MyVec<u8>>> = decoder.decode().unwrap();
+    decoder.finish().unwrap();
+
+    assert_eq!(first[0].get_strong_count(), 2);
+    assert_eq!(second.peek_front().unwrap()[..], *b"payload");
+    assert_eq!(second.peek_back().unwrap().len(), 0);
+}
+
+#[test]
+fn rc_of_rc_is_shared_at_both_levels() {
+    let inner = MyRc::new(42u16);
+    let outer = MyRc::new(MyVec::from([inner.clone(), inner]));
+    let bytes = codec::encode_to_vec(&MyVec::from([outer.clone(), outer]));
+    // length, tag, inner length, tag + value, back-reference, back-reference
+    assert_eq!(bytes.len(), 7);
+
+    let back: MyVec<MyRc<MyVec<MyRc<u16>>>> = codec::decode_from_slice(&bytes).unwrap();
+    assert_eq!(back[0].get_count(), 2);
+    assert_eq!(back[0][1].get_count(), 2);
+    assert_eq!(*back[1][0], 42);
+}
+
+#[test]
+fn truncated_input_is_an_error() {
+    let bytes = codec::encode_to_vec(&MyVec::from([1u32, 2, 3]));
+    for end in 0..bytes.len() {
+        assert_eq!(
+            codec::decode_from_slice::<MyVec<u32>>(&bytes[..end]).err(),
+            Some(DecodeError::UnexpectedEof)
+        );
+    }
+}
+
+#[test]
+fn lying_length_of_zero_byte_elements_is_an_error() {
+    // u64::MAX >> 1 elements, each taking no input at all
+    let prefix = [0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x7f];
+    let too_long = Some(DecodeError::SequenceTooLong((u64::MAX >> 1) as usize));
+    assert_eq!(
+        codec::decode_from_slice::<LinkedList<()>>(&prefix).err(),
+        too_long
+    );
+    assert_eq!(
+        codec::decode_from_slice::<MyVec<()>>(&prefix).err(),
+        too_long
+    );
+    // Elements of at least a byte are still held to the input left
+    assert_eq!(
+        codec::decode_from_slice::<MyVec<u8>>(&[3, 1, 2]).err(),
+        Some(DecodeError::UnexpectedEof)
+    );
+}
+
+#[test]
+fn zero_byte_elements_round_trip_up_to_the_cap() {
+    let vec: MyVec<()> = MyVec::from([(), (), ()]);
+    assert_eq!(codec::encode_to_vec(&vec)[..], [3]);
+    let back: MyVec<()> = codec::decode_from_slice(&codec::encode_to_vec(&vec)).unwrap();
+    assert_eq!(back.len(), 3);
+
+    let deque: MyDeque<()> = (0..codec::MAX_ZERO_BYTE_SEQ_LEN).map(|_| ()).collect();
+    let back: MyDeque<()> = codec::decode_from_slice(&codec::encode_to_vec(&deque)).unwrap();
+    assert_eq!(back.len(), codec::MAX_ZERO_BYTE_SEQ_LEN);
+
+    let list: LinkedList<()> = (0..5).map(|_| ()).collect();
+    let back: LinkedList<()> = codec::decode_from_slice(&codec::encode_to_vec(&list)).unwrap();
+    assert_eq!(back.len(), 5);
+}
+
+#[test]
+#[should_panic(expected = "zero-byte elements")]
+fn encoding_past_the_zero_byte_cap_panics() {
+    let vec: MyVec<()> = (0..=codec::MAX_ZERO_BYTE_SEQ_LEN).map(|_| ()).collect();
+    codec::encode_to_vec(&vec);
+}
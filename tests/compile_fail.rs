@@ -0,0 +1,11 @@
+// Misuse that has to be rejected at compile time: each file under
+// tests/compile_fail/ must fail to build, with the error in its .stderr.
+// After a compiler upgrade changes the wording, regenerate them with
+// `TRYBUILD=overwrite cargo test --test compile_fail`.
+#![cfg(feature = "alloc")]
+
+#[test]
+fn compile_fail() {
+    let cases = trybuild::TestCases::new();
+    cases.compile_fail("tests/compile_fail/*.rs");
+}
@@ -0,0 +1,11 @@
+// Two threads bumping the same Cell through a MyArc would be a data race.
+use std::{cell::Cell, thread};
+
+use my_std::my_arc::MyArc;
+
+fn main() {
+    let counter = MyArc::new(Cell::new(0u32));
+    let other = counter.clone();
+    thread::spawn(move || other.set(other.get() + 1));
+    counter.set(counter.get() + 1);
+}
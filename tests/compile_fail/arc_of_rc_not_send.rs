@@ -0,0 +1,12 @@
+// The MyRc's non-atomic count would be shared between threads.
+use std::thread;
+
+use my_std::{my_arc::MyArc, my_rc::MyRc};
+
+fn main() {
+    let shared = MyArc::new(MyRc::new(1u32));
+    let other = shared.clone();
+    thread::spawn(move || {
+        let _rc = (*other).clone();
+    });
+}
@@ -0,0 +1,9 @@
+use std::mem::ManuallyDrop;
+
+use my_std::{coerce, my_rc::MyRc};
+
+fn main() {
+    // Would drop the String that ManuallyDrop promised to keep
+    let kept = MyRc::new(ManuallyDrop::new(String::from("kept")));
+    let _ = coerce!(kept => String);
+}
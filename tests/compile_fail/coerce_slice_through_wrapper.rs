@@ -0,0 +1,9 @@
+use std::mem::ManuallyDrop;
+
+use my_std::{coerce, my_arc::MyArc};
+
+fn main() {
+    // Same size and address as the array inside, but not an unsizing of the wrapper
+    let kept = MyArc::new(ManuallyDrop::new([String::from("kept")]));
+    let _ = coerce!(kept => [String]);
+}
@@ -0,0 +1,6 @@
+use my_std::{coerce, my_rc::MyRc};
+
+fn main() {
+    let pair = MyRc::new((1u8, 2u8));
+    let _ = coerce!(pair => u8);
+}
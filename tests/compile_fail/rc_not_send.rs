@@ -0,0 +1,9 @@
+use std::thread;
+
+use my_std::my_rc::MyRc;
+
+fn main() {
+    let rc = MyRc::new(5u32);
+    let other = rc.clone();
+    thread::spawn(move || drop(other));
+}
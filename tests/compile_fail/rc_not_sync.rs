@@ -0,0 +1,10 @@
+use std::thread;
+
+use my_std::my_rc::MyRc;
+
+fn main() {
+    let rc = MyRc::new(5u32);
+    thread::scope(|s| {
+        s.spawn(|| rc.clone());
+    });
+}
@@ -0,0 +1,9 @@
+use std::thread;
+
+use my_std::my_rc::MyRc;
+
+fn main() {
+    let rc = MyRc::new(5u32);
+    let weak = rc.downgrade();
+    thread::spawn(move || weak.upgrade().is_some());
+}
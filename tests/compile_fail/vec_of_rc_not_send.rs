@@ -0,0 +1,11 @@
+// Moving the vector moves its MyRc's, whose count isn't atomic.
+use std::thread;
+
+use my_std::{my_rc::MyRc, my_vec::MyVec};
+
+fn main() {
+    let rc = MyRc::new(1u32);
+    let mut vec = MyVec::new();
+    vec.push(rc.clone());
+    thread::spawn(move || drop(vec));
+}
@@ -0,0 +1,14 @@
+// A MyWeak upgrades into a MyArc on the other side, so it needs the same bounds.
+use std::{cell::Cell, thread};
+
+use my_std::my_arc::MyArc;
+
+fn main() {
+    let counter = MyArc::new(Cell::new(0u32));
+    let weak = counter.downgrade();
+    thread::spawn(move || {
+        if let Some(counter) = weak.upgrade() {
+            counter.set(1);
+        }
+    });
+}
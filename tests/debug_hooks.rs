@@ -0,0 +1,64 @@
+#![cfg(feature = "debug-hooks")]
+
+use std::{cell::RefCell, collections::BTreeMap};
+
+use my_std::{
+    hooks::{self, Event, EventKind},
+    my_rc::MyRc,
+};
+
+thread_local! {
+    // Block address -> type, for every block allocated and not freed yet
+    static LIVE: RefCell<BTreeMap<usize, &'static str>> = const { RefCell::new(BTreeMap::new()) };
+}
+
+fn track_blocks(event: &Event) {
+    LIVE.with_borrow_mut(|live| match event.kind {
+        EventKind::Allocate => {
+            live.insert(event.addr, event.type_name);
+        }
+        EventKind::Deallocate => {
+            live.remove(&event.addr);
+        }
+        _ => {}
+    });
+}
+
+struct Node {
+    name: &'static str,
+    next: RefCell<Option<MyRc<Node>>>,
+}
+
+fn node(name: &'static str) -> MyRc<Node> {
+    MyRc::new(Node {
+        name,
+        next: RefCell::new(None),
+    })
+}
+
+#[test]
+fn leak_hunt_finds_the_cycle() {
+    let id = hooks::register(track_blocks).unwrap();
+
+    // A chain is freed, a loop keeps itself alive
+    let (a, b) = (node("a"), node("b"));
+    *a.next.borrow_mut() = Some(b.clone());
+    drop((a, b));
+
+    let (c, d) = (node("c"), node("d"));
+    *c.next.borrow_mut() = Some(d.clone());
+    *d.next.borrow_mut() = Some(c.clone());
+    let leaked = [c.name, d.name];
+    let way_in = c.downgrade();
+    drop((c, d));
+
+    hooks::unregister(id);
+    let live = LIVE.take();
+    assert_eq!(leaked, ["c", "d"]);
+    assert_eq!(live.len(), 2);
+    assert!(live.values().all(|name| name.ends_with("::Node")));
+
+    // Break the loop so the process doesn't actually leak
+    way_in.upgrade().unwrap().next.take();
+    assert!(way_in.upgrade().is_none());
+}
@@ -0,0 +1,51 @@
+#![cfg(feature = "alloc")]
+
+use my_std::{inline_vec::InlineVec, tracking::Tracking};
+
+#[test]
+fn small_vectors_never_allocate() {
+    let tracker = Tracking::new();
+    let mut vecs: Vec<InlineVec<u16, 8, _>> = Vec::new();
+    for len in 0..=8 {
+        let mut vec = InlineVec::new_in(&tracker);
+        vec.extend(0..len);
+        vecs.push(vec);
+    }
+    assert!(vecs.iter().all(|vec| !vec.spilled()));
+    assert_eq!(vecs[8], [0, 1, 2, 3, 4, 5, 6, 7]);
+    assert_eq!(tracker.allocation_count(), 0);
+}
+
+#[test]
+fn spill_and_come_back() {
+    let mut vec: InlineVec<String, 2> = InlineVec::new();
+    vec.push("a".into());
+    vec.push("b".into());
+    assert!(!vec.spilled());
+
+    vec.push("c".into());
+    assert!(vec.spilled());
+    vec.sort_by(|a, b| b.cmp(a));
+    assert_eq!(vec, ["c", "b", "a"]);
+
+    vec.retain(|s| s != "b");
+    vec.shrink_to_fit();
+    assert!(!vec.spilled());
+    assert_eq!(vec.into_iter().collect::<Vec<_>>(), ["c", "a"]);
+}
+
+#[test]
+fn behaves_like_a_vec() {
+    let mut vec: InlineVec<i32, 4> = InlineVec::from([5, 3, 1]);
+    vec.insert(1, 4);
+    vec.extend([2, 0]);
+    vec.sort_unstable();
+    assert_eq!(vec, [0, 1, 2, 3, 4, 5]);
+
+    let drained: Vec<_> = vec.drain(..2).collect();
+    assert_eq!(drained, [0, 1]);
+    let clone = vec.clone();
+    assert_eq!(clone, vec);
+    assert_eq!(format!("{clone:?}"), "[2, 3, 4, 5]");
+    assert_eq!(vec.iter().sum::<i32>(), 14);
+}
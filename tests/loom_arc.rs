@@ -0,0 +1,101 @@
+// Purpose: model-check MyArc's counts under every interleaving loom finds.
+// Run with `cargo test --features loom --test loom_arc --release`.
+#![cfg(feature = "loom")]
+
+use loom::{
+    cell::UnsafeCell,
+    sync::{
+        Arc,
+        atomic::{AtomicUsize, Ordering},
+    },
+    thread,
+};
+use my_std::my_arc::MyArc;
+
+// Counts its own drops so a test can tell "dropped once" from "leaked" or "twice"
+struct Tally(Arc<AtomicUsize>, UnsafeCell<usize>);
+
+impl Drop for Tally {
+    fn drop(&mut self) {
+        self.0.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+fn tally() -> (MyArc<Tally>, Arc<AtomicUsize>) {
+    let drops = Arc::new(AtomicUsize::new(0));
+    (MyArc::new(Tally(drops.clone(), UnsafeCell::new(0))), drops)
+}
+
+#[test]
+fn clone_and_drop_across_threads() {
+    loom::model(|| {
+        let (arc, drops) = tally();
+        let other = arc.clone();
+
+        let handle = thread::spawn(move || {
+            let again = other.clone();
+            drop(other);
+            drop(again);
+        });
+        drop(arc);
+        handle.join().unwrap();
+
+        assert_eq!(drops.load(Ordering::Relaxed), 1);
+    });
+}
+
+#[test]
+fn upgrade_races_last_drop() {
+    loom::model(|| {
+        let (arc, drops) = tally();
+        let weak = arc.downgrade();
+
+        let handle = thread::spawn(move || weak.upgrade().map(|arc| arc.get_strong_count()));
+        drop(arc);
+        // An upgrade that won keeps the value alive until it's dropped
+        if let Some(count) = handle.join().unwrap() {
+            assert!(count >= 1);
+        }
+
+        assert_eq!(drops.load(Ordering::Relaxed), 1);
+    });
+}
+
+#[test]
+fn try_unwrap_races_upgrade() {
+    loom::model(|| {
+        let (arc, drops) = tally();
+        let weak = arc.downgrade();
+
+        // Loom flags the read and the write if they can overlap, i.e. if the
+        // upgrade ever shares a value `try_unwrap` handed out
+        let handle = thread::spawn(move || {
+            if let Some(arc) = weak.upgrade() {
+                arc.1.with(|seen| unsafe { *seen });
+            }
+        });
+        if let Ok(value) = MyArc::try_unwrap(arc) {
+            value.1.with_mut(|seen| unsafe { *seen += 1 });
+        }
+        handle.join().unwrap();
+
+        assert_eq!(drops.load(Ordering::Relaxed), 1);
+    });
+}
+
+#[test]
+fn try_unwrap_races_drop() {
+    loom::model(|| {
+        let (arc, drops) = tally();
+        let other = arc.clone();
+
+        let handle = thread::spawn(move || MyArc::try_unwrap(other).ok());
+        let mine = MyArc::try_unwrap(arc).ok();
+        let theirs = handle.join().unwrap();
+
+        // At most one side gets the value; if it was the clone that lost, it got dropped
+        assert!(mine.is_none() || theirs.is_none());
+        drop((mine, theirs));
+        assert_eq!(drops.load(Ordering::Relaxed), 1);
+    });
+}
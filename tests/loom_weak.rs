@@ -0,0 +1,106 @@
+// Purpose: model-check MyWeak and get_mut_ref against the other MyArc operations.
+// Run with `cargo test --features loom --test loom_weak --release`.
+#![cfg(feature = "loom")]
+
+use loom::{cell::UnsafeCell, thread};
+use my_std::{my_arc::MyArc, tracking::Tracking};
+
+// Loom flags any access to the cell that can overlap a `with_mut`, so two
+// threads both believing they own the value show up as a failure.
+type Shared = MyArc<UnsafeCell<u32>, &'static Tracking>;
+
+fn shared(tracker: &'static Tracking) -> Shared {
+    MyArc::new_in(UnsafeCell::new(0), tracker)
+}
+
+#[test]
+fn weak_drop_races_arc_drop() {
+    static TRACKER: Tracking = Tracking::new();
+    loom::model(|| {
+        let arc = shared(&TRACKER);
+        let weak = arc.downgrade();
+        let other = weak.clone();
+
+        // Whichever of the three goes last frees the block, exactly once
+        let handle = thread::spawn(move || drop(weak));
+        drop(arc);
+        drop(other);
+        handle.join().unwrap();
+
+        assert_eq!(TRACKER.live_bytes(), 0);
+    });
+}
+
+#[test]
+fn upgrade_races_weak_drop() {
+    static TRACKER: Tracking = Tracking::new();
+    loom::model(|| {
+        let arc = shared(&TRACKER);
+        let weak = arc.downgrade();
+        let other = weak.clone();
+
+        let handle = thread::spawn(move || {
+            if let Some(arc) = weak.upgrade() {
+                arc.with(|value| unsafe { *value });
+            }
+        });
+        drop(other);
+        drop(arc);
+        handle.join().unwrap();
+
+        assert_eq!(TRACKER.live_bytes(), 0);
+    });
+}
+
+#[test]
+fn get_mut_ref_races_upgrade() {
+    static TRACKER: Tracking = Tracking::new();
+    loom::model(|| {
+        let mut arc = shared(&TRACKER);
+        let weak = arc.downgrade();
+
+        // Upgrading and then dropping the MyWeak leaves strong 2 and weak 1:
+        // get_mut_ref must not see the weak count after and the strong before
+        let handle = thread::spawn(move || {
+            let upgraded = weak.upgrade();
+            drop(weak);
+            if let Some(arc) = upgraded {
+                arc.with(|value| unsafe { *value });
+            }
+        });
+        if let Some(value) = arc.get_mut_ref() {
+            value.with_mut(|value| unsafe { *value += 1 });
+        }
+        handle.join().unwrap();
+        drop(arc);
+
+        assert_eq!(TRACKER.live_bytes(), 0);
+    });
+}
+
+#[test]
+fn get_mut_ref_races_downgrade() {
+    static TRACKER: Tracking = Tracking::new();
+    loom::model(|| {
+        let mut arc = shared(&TRACKER);
+        let other = arc.clone();
+
+        // The clone turns itself into a MyWeak and comes back through it
+        let handle = thread::spawn(move || {
+            let weak = other.downgrade();
+            drop(other);
+            if let Some(arc) = weak.upgrade() {
+                arc.with(|value| unsafe { *value });
+            }
+        });
+        if let Some(value) = arc.get_mut_ref() {
+            value.with_mut(|value| unsafe { *value += 1 });
+        }
+        handle.join().unwrap();
+
+        // Only ever the one pointer left, so this has to succeed now
+        assert!(arc.get_mut_ref().is_some());
+        drop(arc);
+        assert_eq!(TRACKER.live_bytes(), 0);
+    });
+}
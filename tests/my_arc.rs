@@ -0,0 +1,109 @@
+#![cfg(feature = "alloc")]
+
+#[cfg(feature = "std")]
+use std::thread;
+
+use my_std::my_arc::MyArc;
+
+#[test]
+fn strong_and_weak_counts() {
+    let arc = MyArc::new(5);
+    let weak = arc.downgrade();
+    assert_eq!(arc.get_strong_count(), 1);
+    assert_eq!(arc.get_weak_count(), 2);
+
+    let upgraded = weak.upgrade().unwrap();
+    assert_eq!(*upgraded, 5);
+    assert_eq!(arc.get_strong_count(), 2);
+
+    drop(upgraded);
+    drop(arc);
+    assert!(weak.upgrade().is_none());
+}
+
+#[test]
+#[cfg(feature = "std")]
+fn shared_across_threads() {
+    let arc = MyArc::new(vec![1, 2, 3]);
+    let handles: Vec<_> = (0..4)
+        .map(|_| {
+            let arc = arc.clone();
+            thread::spawn(move || arc.iter().sum::<i32>())
+        })
+        .collect();
+
+    for handle in handles {
+        assert_eq!(handle.join().unwrap(), 6);
+    }
+
+    let mut arc = arc;
+    arc.get_mut_ref().unwrap().push(4);
+    assert_eq!(arc.get_value_ref(), &vec![1, 2, 3, 4]);
+    assert_eq!(arc.try_unwrap().ok(), Some(vec![1, 2, 3, 4]));
+}
+
+#[test]
+fn copy_on_write_config() {
+    #[derive(Clone, Debug, PartialEq)]
+    struct Config {
+        name: String,
+        retries: u32,
+    }
+
+    let mut current = MyArc::new(Config {
+        name: String::from("svc"),
+        retries: 3,
+    });
+    let snapshot = current.clone();
+
+    // Readers keep the snapshot they took, the writer gets its own copy
+    current.make_mut().retries = 5;
+    assert_eq!(snapshot.retries, 3);
+    assert_eq!(current.retries, 5);
+
+    // Once the snapshot is gone, writes happen in place
+    drop(snapshot);
+    let before: *const Config = &*current;
+    current.make_mut().name.push_str("-v2");
+    assert!(std::ptr::eq(before, &*current));
+
+    let config = current.unwrap_or_clone();
+    assert_eq!(config.name, "svc-v2");
+}
+
+#[test]
+#[cfg(feature = "std")]
+fn dyn_handlers_across_threads() {
+    trait Handler: Send + Sync {
+        fn handle(&self, input: u32) -> u32;
+    }
+
+    struct Double;
+    impl Handler for Double {
+        fn handle(&self, input: u32) -> u32 {
+            input * 2
+        }
+    }
+
+    let boxed: Box<dyn Handler> = Box::new(Double);
+    let handlers: MyArc<[MyArc<dyn Handler>]> = [
+        MyArc::from(boxed),
+        my_std::coerce!(MyArc::new(Double) => dyn Handler),
+    ]
+    .into_iter()
+    .collect();
+
+    let results: Vec<u32> = thread::scope(|s| {
+        (1..=4)
+            .map(|i| {
+                let handlers = handlers.clone();
+                s.spawn(move || handlers.iter().map(|h| h.handle(i)).sum())
+            })
+            .collect::<Vec<_>>()
+            .into_iter()
+            .map(|handle| handle.join().unwrap())
+            .collect()
+    });
+    assert_eq!(results, [4, 8, 12, 16]);
+    assert_eq!(handlers.get_strong_count(), 1);
+}
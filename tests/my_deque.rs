@@ -0,0 +1,39 @@
+#![cfg(feature = "alloc")]
+
+use my_std::my_deque::MyDeque;
+
+#[test]
+fn push_pop_both_ends() {
+    let mut deque = MyDeque::new();
+    deque.push_back(2);
+    deque.push_front(1);
+    deque.push_back(3);
+
+    assert_eq!(deque.len(), 3);
+    assert_eq!(deque.peek_front(), Some(&1));
+    assert_eq!(deque.peek_back(), Some(&3));
+    assert_eq!(deque.pop_front(), Some(1));
+    assert_eq!(deque.pop_back(), Some(3));
+    assert_eq!(deque.pop_back(), Some(2));
+    assert!(deque.is_empty());
+}
+
+#[test]
+fn conversions_and_iterators() {
+    let mut deque = MyDeque::from(vec![1, 2, 3]);
+    deque.extend([4, 5]);
+    assert!(deque.contains(&5));
+    assert_eq!(deque.get(4), Some(&5));
+
+    for x in &mut deque {
+        *x += 1;
+    }
+    let copy = deque.clone();
+    assert_eq!(deque, copy);
+
+    let collected: Vec<_> = deque.into_iter().collect();
+    assert_eq!(collected, vec![2, 3, 4, 5, 6]);
+
+    let from_iter: MyDeque<_> = (0..3).collect();
+    assert!(from_iter < copy);
+}
@@ -0,0 +1,24 @@
+#![cfg(feature = "alloc")]
+
+use my_std::my_linked_list::LinkedList;
+
+#[test]
+fn push_pop_and_iterate() {
+    let mut list = LinkedList::new();
+    list.push_back(2);
+    list.push_front(1);
+    list.push_back(3);
+
+    assert_eq!(list.len(), 3);
+    assert_eq!(list.front(), Some(&1));
+    assert_eq!(list.back(), Some(&3));
+
+    for x in list.iter_mut() {
+        *x *= 2;
+    }
+    assert_eq!(
+        list.iter().rev().copied().collect::<Vec<_>>(),
+        vec![6, 4, 2]
+    );
+    assert_eq!(list.into_iter().collect::<Vec<_>>(), vec![2, 4, 6]);
+}
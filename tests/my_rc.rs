@@ -0,0 +1,83 @@
+#![cfg(feature = "alloc")]
+
+use my_std::my_rc::MyRc;
+
+#[test]
+fn clone_count_and_unwrap() {
+    let mut rc = MyRc::new(String::from("hello"));
+    assert_eq!(rc.get_count(), 1);
+
+    rc.get_mut_ref().unwrap().push('!');
+    let clone = rc.clone();
+    assert_eq!(rc.get_count(), 2);
+    assert!(rc.get_mut_ref().is_none());
+    assert_eq!(clone.get_value_ref(), "hello!");
+    assert_eq!(clone.len(), 6);
+
+    let rc = rc.try_unwrap().unwrap_err();
+    drop(clone);
+    assert_eq!(rc.try_unwrap().ok(), Some(String::from("hello!")));
+}
+
+#[test]
+fn parent_back_pointers_do_not_leak() {
+    use std::cell::RefCell;
+
+    use my_std::{my_rc::MyRcWeak, my_vec::MyVec, tracking::Tracking};
+
+    struct Node<'a> {
+        value: u32,
+        parent: MyRcWeak<Node<'a>, &'a Tracking>,
+        children: RefCell<MyVec<MyRc<Node<'a>, &'a Tracking>>>,
+    }
+
+    let tracker = Tracking::new();
+    {
+        let root = MyRc::new_in(
+            Node {
+                value: 0,
+                parent: MyRcWeak::new_in(&tracker),
+                children: RefCell::new(MyVec::new()),
+            },
+            &tracker,
+        );
+        for value in 1..=3 {
+            let child = MyRc::new_in(
+                Node {
+                    value,
+                    parent: root.downgrade(),
+                    children: RefCell::new(MyVec::new()),
+                },
+                &tracker,
+            );
+            root.children.borrow_mut().push(child);
+        }
+        assert_eq!(root.weak_count(), 3);
+
+        let children = root.children.borrow();
+        let parent = children[2].parent.upgrade().unwrap();
+        assert_eq!(parent.value, 0);
+        assert_eq!(children.iter().map(|c| c.value).sum::<u32>(), 6);
+    }
+    assert_eq!(tracker.live_bytes(), 0);
+}
+
+#[test]
+fn interned_strings_share_one_block() {
+    use std::collections::HashMap;
+
+    let mut interner: HashMap<String, MyRc<str>> = HashMap::new();
+    let mut intern = |s: &str| {
+        interner
+            .entry(s.to_string())
+            .or_insert_with(|| MyRc::from(s))
+            .clone()
+    };
+
+    let a = intern("alpha");
+    let b = intern("alpha");
+    let c = intern("beta");
+    assert!(std::ptr::eq(&*a, &*b));
+    assert_eq!(a.strong_count(), 3);
+    assert_eq!((&*b, &*c), ("alpha", "beta"));
+}
@@ -0,0 +1,128 @@
+#![cfg(feature = "alloc")]
+
+use my_std::{TryReserveError, my_vec::MyVec};
+
+#[test]
+fn push_pop_and_index() {
+    let mut vec = MyVec::new();
+    vec.push(1);
+    vec.push(2);
+    vec.push(3);
+
+    assert_eq!(vec.get_len(), 3);
+    assert_eq!(vec[1], 2);
+    assert_eq!(vec.get(3), None);
+    assert_eq!(vec.pop(), Some(3));
+    assert_eq!(vec.as_slice(), &[1, 2]);
+}
+
+#[test]
+fn insert_remove_and_slices() {
+    let mut vec = MyVec::default();
+    vec.push(1);
+    vec.push(3);
+    vec.insert(1, 2);
+    assert_eq!(&vec[0..=2], &[1, 2, 3]);
+
+    assert_eq!(vec.remove(0), 1);
+    vec.as_mut_slice()[0] = 20;
+    if let Some(last) = vec.get_mut(1) {
+        *last = 30;
+    }
+    assert_eq!(vec.as_slice(), &[20, 30]);
+    assert!(vec.get_capacity() >= vec.get_len());
+}
+
+#[test]
+fn iterators() {
+    let mut vec = MyVec::new();
+    for i in 0..5 {
+        vec.push(i);
+    }
+
+    for x in &mut vec {
+        *x *= 10;
+    }
+    let borrowed: Vec<_> = (&vec).into_iter().copied().collect();
+    assert_eq!(borrowed, vec![0, 10, 20, 30, 40]);
+
+    let owned: Vec<_> = vec.clone().into_iter().collect();
+    assert_eq!(owned, borrowed);
+}
+
+#[test]
+fn fallible_allocation() {
+    let mut vec = MyVec::try_with_capacity(1).unwrap();
+    vec.try_push(1u32).unwrap();
+    vec.try_push(2).unwrap();
+
+    let err = vec.try_reserve(usize::MAX).unwrap_err();
+    assert_eq!(err, TryReserveError::CapacityOverflow);
+    assert!(err.to_string().contains("capacity"));
+    assert_eq!(vec.as_slice(), &[1, 2]);
+}
+
+#[test]
+fn range_editing() {
+    let mut vec = MyVec::new();
+    for i in 0..10 {
+        vec.push(i);
+    }
+
+    let drained: Vec<_> = vec.drain(..3).collect();
+    assert_eq!(drained, [0, 1, 2]);
+    let removed: Vec<_> = vec.splice(0..2, [30, 31, 32]).collect();
+    assert_eq!(removed, [3, 4]);
+    assert_eq!(vec.as_slice(), &[30, 31, 32, 5, 6, 7, 8, 9]);
+
+    let odd: Vec<_> = vec.extract_if(|x| *x % 2 == 1).collect();
+    assert_eq!(odd, [31, 5, 7, 9]);
+    vec.retain(|x| *x != 6);
+    assert_eq!(vec.as_slice(), &[30, 32, 8]);
+
+    let mut tail = vec.split_off(1);
+    tail.dedup_by_key(|x| *x / 10);
+    tail.truncate(1);
+    vec.append(&mut tail);
+    assert_eq!(vec.as_slice(), &[30, 32]);
+}
+
+fn total<S: AsRef<[i32]>>(values: S) -> i32 {
+    values.as_ref().iter().sum()
+}
+
+fn largest<T: Ord + Clone>(items: &[T]) -> Option<T> {
+    items.iter().max().cloned()
+}
+
+#[test]
+fn drop_in_for_vec_in_generic_code() {
+    let vec = my_std::my_vec![4, 8, 15, 16, 23, 42];
+    assert_eq!(total(&vec), total(vec![4, 8, 15, 16, 23, 42]));
+    assert_eq!(largest(&vec), Some(42));
+
+    let mut nested: MyVec<MyVec<i32>> = (0..3).map(|i| my_std::my_vec![i; 2]).collect();
+    nested.sort_by(|a, b| b.cmp(a));
+    assert_eq!(nested[0], [2, 2]);
+
+    let as_std: Vec<i32> = vec.into();
+    assert_eq!(as_std.len(), 6);
+}
+
+#[test]
+fn batch_ingestion_fills_in_place() {
+    let batch: &[u8] = b"preallocated batch";
+    let mut buf: MyVec<u8> = MyVec::with_capacity(1024);
+    buf.reserve_exact(batch.len());
+
+    let spare = buf.spare_capacity_mut();
+    for (slot, byte) in spare.iter_mut().zip(batch) {
+        slot.write(*byte);
+    }
+    unsafe { buf.set_len(batch.len()) };
+
+    assert_eq!(buf.as_slice(), batch);
+    assert_eq!(buf.get_capacity(), 1024);
+    buf.shrink_to_fit();
+    assert_eq!(buf.get_capacity(), batch.len());
+}
@@ -0,0 +1,109 @@
+#![cfg(feature = "serde")]
+
+use my_std::{
+    array_vec::ArrayVec, inline_vec::InlineVec, my_arc::MyArc, my_deque::MyDeque,
+    my_linked_list::LinkedList, my_rc::MyRc, my_vec::MyVec,
+};
+use serde::{
+    Deserialize, Deserializer,
+    de::{IntoDeserializer, value::Error as ValueError},
+};
+use serde_test::{Token, assert_de_tokens_error, assert_ser_tokens, assert_tokens};
+
+fn seq_tokens(len: usize, items: &[Token]) -> Vec<Token> {
+    let mut tokens = vec![Token::Seq { len: Some(len) }];
+    tokens.extend_from_slice(items);
+    tokens.push(Token::SeqEnd);
+    tokens
+}
+
+#[test]
+fn my_vec_round_trip() {
+    let vec: MyVec<i32> = my_std::my_vec![1, 2, 3];
+    let tokens = seq_tokens(3, &[Token::I32(1), Token::I32(2), Token::I32(3)]);
+    assert_tokens(&vec, &tokens);
+
+    let empty: MyVec<i32> = MyVec::new();
+    assert_tokens(&empty, &seq_tokens(0, &[]));
+}
+
+#[test]
+fn wrapped_deque_round_trip() {
+    // Storage wraps around the end of the buffer; the tokens are in logical order
+    let mut deque = MyDeque::with_capacity(4);
+    deque.push_back(3u8);
+    deque.push_back(4);
+    deque.push_front(2);
+    deque.push_front(1);
+    let tokens = seq_tokens(4, &[Token::U8(1), Token::U8(2), Token::U8(3), Token::U8(4)]);
+    assert_tokens(&deque, &tokens);
+}
+
+#[test]
+fn linked_list_round_trip() {
+    let mut list = LinkedList::new();
+    list.push_back(String::from("a"));
+    list.push_back(String::from("b"));
+    assert_tokens(&list, &seq_tokens(2, &[Token::Str("a"), Token::Str("b")]));
+}
+
+#[test]
+fn inline_and_array_vec_round_trip() {
+    let inline: InlineVec<u16, 2> = InlineVec::from([7, 8, 9]);
+    assert_tokens(
+        &inline,
+        &seq_tokens(3, &[Token::U16(7), Token::U16(8), Token::U16(9)]),
+    );
+
+    let array: ArrayVec<u16, 2> = ArrayVec::from([7, 8]);
+    assert_tokens(&array, &seq_tokens(2, &[Token::U16(7), Token::U16(8)]));
+    assert_de_tokens_error::<ArrayVec<u16, 2>>(
+        &seq_tokens(3, &[Token::U16(7), Token::U16(8), Token::U16(9)]),
+        "invalid length 3, expected a sequence of at most 2 elements",
+    );
+}
+
+// Deserializes from serde's in-memory value deserializers.
+fn from_value<'de, T, D>(deserializer: D) -> T
+where
+    T: Deserialize<'de>,
+    D: Deserializer<'de, Error = ValueError>,
+{
+    T::deserialize(deserializer).unwrap()
+}
+
+#[test]
+fn pointers_are_transparent() {
+    assert_ser_tokens(&MyRc::new(5u32), &[Token::U32(5)]);
+    assert_ser_tokens(&MyArc::new(String::from("shared")), &[Token::Str("shared")]);
+
+    let rc: MyRc<u32> = from_value(5u32.into_deserializer());
+    assert_eq!(*rc, 5);
+    let arc: MyArc<String> = from_value("shared".into_deserializer());
+    assert_eq!(*arc, "shared");
+
+    // Sharing isn't preserved: both elements are written out in full
+    let shared = MyRc::new(true);
+    let nested: MyVec<MyRc<bool>> = my_std::my_vec![shared.clone(), shared];
+    assert_ser_tokens(
+        &nested,
+        &seq_tokens(2, &[Token::Bool(true), Token::Bool(true)]),
+    );
+    let back: MyVec<MyRc<bool>> = from_value(vec![true, true].into_deserializer());
+    assert_eq!(back[0].get_count(), 1);
+}
+
+#[test]
+fn preallocates_from_the_size_hint() {
+    let values: Vec<u64> = (0..100).collect();
+
+    let vec: MyVec<u64> = from_value(values.clone().into_deserializer());
+    assert_eq!(vec, values);
+    assert_eq!(vec.get_capacity(), 100);
+
+    let deque: MyDeque<u64> = from_value(values.clone().into_deserializer());
+    assert_eq!(deque.capacity(), 100);
+
+    let inline: InlineVec<u64, 4> = from_value(values.into_deserializer());
+    assert_eq!(inline.get_capacity(), 100);
+}
@@ -0,0 +1,184 @@
+// Property tests: every sort and select must agree with std on generated inputs.
+#![cfg(feature = "alloc")]
+
+use my_std::{my_deque::MyDeque, my_vec::MyVec};
+
+struct XorShift(u64);
+
+impl XorShift {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    fn below(&mut self, n: u64) -> u64 {
+        self.next() % n
+    }
+}
+
+// Shapes that exercise the different paths: random, presorted, reversed,
+// lots of duplicates, and runs that alternate direction.
+fn inputs() -> Vec<Vec<i64>> {
+    let mut rng = XorShift(0x2545_f491_4f6c_dd1d);
+    let mut out = Vec::new();
+    for len in [0, 1, 2, 3, 7, 19, 20, 21, 50, 64, 100, 257, 1000, 4096] {
+        for _ in 0..3 {
+            let random: Vec<i64> = (0..len).map(|_| rng.next() as i64).collect();
+            let few_unique: Vec<i64> = (0..len).map(|_| rng.below(4) as i64).collect();
+            let mut sorted = random.clone();
+            sorted.sort();
+            let reversed: Vec<i64> = sorted.iter().rev().copied().collect();
+            let run = 1 + rng.below(40) as usize;
+            let sawtooth: Vec<i64> = (0..len)
+                .map(|i| {
+                    let pos = (i % run) as i64;
+                    if (i / run).is_multiple_of(2) {
+                        pos
+                    } else {
+                        -pos
+                    }
+                })
+                .collect();
+            let mut nearly_sorted = sorted.clone();
+            for _ in 0..3 {
+                if len > 1 {
+                    let a = rng.below(len as u64) as usize;
+                    let b = rng.below(len as u64) as usize;
+                    nearly_sorted.swap(a, b);
+                }
+            }
+            out.extend([
+                random,
+                few_unique,
+                sorted,
+                reversed,
+                sawtooth,
+                nearly_sorted,
+            ]);
+        }
+    }
+    out
+}
+
+// The same values in a deque whose storage wraps around the buffer end.
+fn wrapped_deque(values: &[i64]) -> MyDeque<i64> {
+    let mut deque = MyDeque::with_capacity(values.len());
+    let split = values.len() / 3;
+    for value in values[split..].iter() {
+        deque.push_back(*value);
+    }
+    for value in values[..split].iter().rev() {
+        deque.push_front(*value);
+    }
+    deque
+}
+
+fn deque_contents(deque: &MyDeque<i64>) -> Vec<i64> {
+    deque.into_iter().copied().collect()
+}
+
+#[test]
+fn sort_matches_std() {
+    for input in inputs() {
+        let mut expected = input.clone();
+        expected.sort();
+
+        let mut vec = MyVec::from(&input[..]);
+        vec.sort();
+        assert_eq!(vec, expected);
+
+        let mut vec = MyVec::from(&input[..]);
+        vec.sort_unstable();
+        assert_eq!(vec, expected);
+
+        let mut deque = wrapped_deque(&input);
+        deque.sort();
+        assert_eq!(deque_contents(&deque), expected);
+
+        let mut deque = wrapped_deque(&input);
+        deque.sort_unstable();
+        assert_eq!(deque_contents(&deque), expected);
+    }
+}
+
+#[test]
+fn sort_is_stable() {
+    for input in inputs() {
+        // Coarse keys so plenty of elements tie; the index shows their order
+        let tagged: Vec<(i64, usize)> = input
+            .iter()
+            .enumerate()
+            .map(|(i, x)| (x.rem_euclid(8), i))
+            .collect();
+        let mut expected = tagged.clone();
+        expected.sort_by_key(|&(key, _)| key);
+
+        let mut vec = MyVec::from(&tagged[..]);
+        vec.sort_by_key(|&(key, _)| key);
+        assert_eq!(vec, expected);
+
+        let mut deque = MyDeque::new();
+        for (i, pair) in tagged.iter().enumerate() {
+            if i.is_multiple_of(2) {
+                deque.push_back(*pair);
+            }
+        }
+        for pair in tagged.iter().skip(1).step_by(2).rev() {
+            deque.push_front(*pair);
+        }
+        let mut std_order: Vec<_> = (&deque).into_iter().copied().collect();
+        std_order.sort_by_key(|&(key, _)| key);
+        deque.sort_by_key(|&(key, _)| key);
+        assert!((&deque).into_iter().eq(std_order.iter()));
+    }
+}
+
+#[test]
+fn sort_unstable_by_key_matches_std() {
+    for input in inputs() {
+        let key = |x: &i64| x.wrapping_mul(31) % 97;
+        let mut expected: Vec<i64> = input.iter().map(key).collect();
+        expected.sort();
+
+        let mut vec = MyVec::from(&input[..]);
+        vec.sort_unstable_by_key(key);
+        let keys: Vec<i64> = vec.iter().map(key).collect();
+        assert_eq!(keys, expected);
+    }
+}
+
+#[test]
+fn select_nth_matches_std() {
+    let mut rng = XorShift(0x9e37_79b9_7f4a_7c15);
+    for input in inputs() {
+        if input.is_empty() {
+            continue;
+        }
+        let mut sorted = input.clone();
+        sorted.sort();
+
+        let len = input.len() as u64;
+        for index in [0, input.len() - 1, rng.below(len) as usize] {
+            let mut vec = MyVec::from(&input[..]);
+            let (low, nth, high) = vec.select_nth_unstable(index);
+            assert_eq!(*nth, sorted[index]);
+            assert!(low.iter().all(|x| x <= nth));
+            assert!(high.iter().all(|x| x >= nth));
+
+            let mut deque = wrapped_deque(&input);
+            let (low, nth, high) = deque.select_nth_unstable(index);
+            assert_eq!(*nth, sorted[index]);
+            assert!(low.iter().all(|x| x <= nth));
+            assert!(high.iter().all(|x| x >= nth));
+        }
+    }
+}
+
+#[test]
+#[should_panic]
+fn select_nth_out_of_bounds() {
+    let mut vec = my_std::my_vec![1, 2, 3];
+    vec.select_nth_unstable(3);
+}
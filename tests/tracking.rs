@@ -0,0 +1,232 @@
+#![cfg(feature = "alloc")]
+
+#[cfg(feature = "std")]
+use std::thread;
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    cell::Cell,
+};
+
+use my_std::prelude::*;
+use my_std::tracking::Tracking;
+
+// Counts this thread's trips to the global heap, so a test can catch code
+// that goes around the allocator it was given.
+struct CountingGlobal;
+
+thread_local! {
+    static GLOBAL_ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+fn global_allocations() -> usize {
+    GLOBAL_ALLOCATIONS.get()
+}
+
+fn count_global() {
+    let _ = GLOBAL_ALLOCATIONS.try_with(|count| count.set(count.get() + 1));
+}
+
+unsafe impl GlobalAlloc for CountingGlobal {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        count_global();
+        unsafe { System.alloc(layout) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        unsafe { System.dealloc(ptr, layout) }
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        count_global();
+        unsafe { System.realloc(ptr, layout, new_size) }
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingGlobal = CountingGlobal;
+
+// Runs `f` against a fresh tracker and checks nothing it allocated is left.
+fn assert_no_leaks(f: impl FnOnce(&Tracking)) -> Tracking {
+    let tracker = Tracking::new();
+    f(&tracker);
+    assert_eq!(tracker.live_bytes(), 0, "{tracker:?}");
+    tracker
+}
+
+#[test]
+fn vec() {
+    let tracker = assert_no_leaks(|tracker| {
+        let mut vec = MyVec::new_in(tracker);
+        for i in 0..100 {
+            vec.push(i.to_string());
+        }
+        vec.remove(10);
+        vec.insert(0, String::from("first"));
+        let _ = vec.clone().into_iter().take(3).count();
+    });
+    assert!(tracker.peak_bytes() >= 100 * size_of::<String>());
+}
+
+#[test]
+fn deque() {
+    assert_no_leaks(|tracker| {
+        let mut deque = MyDeque::with_capacity_in(4, tracker);
+        for i in 0..50 {
+            deque.push_front(i);
+            deque.push_back(i);
+            deque.pop_front();
+        }
+        deque.shrink_to_fit();
+        let _ = deque.into_iter().next();
+    });
+}
+
+#[test]
+fn linked_list() {
+    let tracker = assert_no_leaks(|tracker| {
+        let mut list = LinkedList::new_in(tracker);
+        list.extend((0..20).map(|i| vec![i; 3]));
+        list.pop_front();
+        let _ = list.clone().into_iter().next();
+    });
+    // 20 nodes, 19 cloned ones
+    assert_eq!(tracker.allocation_count(), 39);
+}
+
+#[test]
+fn rc() {
+    assert_no_leaks(|tracker| {
+        let rc = MyRc::new_in(String::from("shared"), tracker);
+        let clones: Vec<_> = (0..5).map(|_| rc.clone()).collect();
+        drop(rc);
+        drop(clones);
+
+        let unique = MyRc::new_in(vec![1, 2], tracker);
+        assert_eq!(unique.try_unwrap().ok(), Some(vec![1, 2]));
+    });
+}
+
+#[test]
+#[cfg(feature = "std")]
+fn arc_and_weak() {
+    let tracker = assert_no_leaks(|tracker| {
+        let arc = MyArc::new_in(String::from("shared"), tracker);
+        let weak = arc.downgrade();
+        thread::scope(|s| {
+            for _ in 0..8 {
+                let arc = arc.clone();
+                s.spawn(move || assert_eq!(arc.len(), 6));
+            }
+        });
+        drop(arc);
+        assert!(weak.upgrade().is_none());
+        assert!(tracker.live_bytes() > 0);
+        drop(weak);
+
+        let unique = MyArc::new_in(5, tracker);
+        assert_eq!(unique.try_unwrap().ok(), Some(5));
+    });
+    assert_eq!(tracker.allocation_count(), 2);
+    assert!(
+        tracker
+            .call_sites()
+            .iter()
+            .all(|site| site.location.file() == file!())
+    );
+}
+
+#[test]
+fn call_sites_are_where_the_growth_was_asked_for() {
+    let tracker = assert_no_leaks(|tracker| {
+        let mut vec = MyVec::new_in(tracker);
+        vec.push(1);
+        vec.reserve(100);
+        vec.extend(0..200);
+        let _copy = vec.clone();
+
+        let mut deque = MyDeque::new_in(tracker);
+        deque.push_back(1);
+        deque.reserve_exact(10);
+
+        let mut inline: InlineVec<i32, 1, _> = InlineVec::new_in(tracker);
+        inline.push(1);
+        inline.insert(0, 0);
+
+        let mut list = LinkedList::new_in(tracker);
+        list.push_front(1);
+        let _rc = MyRc::new_in(1, tracker);
+        let _arc = MyArc::new_in(1, tracker);
+    });
+    // One site per allocating line above, none inside the crate
+    let sites = tracker.call_sites();
+    assert_eq!(sites.len(), 10, "{sites:?}");
+    assert!(
+        sites
+            .iter()
+            .all(|site| site.location.file() == file!() && site.count == 1),
+        "{sites:?}"
+    );
+}
+
+#[test]
+fn sort_allocates_only_through_the_vector() {
+    assert_no_leaks(|tracker| {
+        let mut vec = MyVec::new_in(tracker);
+        for i in 0..1000 {
+            vec.push((i * 7919) % 1000);
+        }
+        // The tracker's own call site list grows the first time a site is seen
+        vec.clone().sort();
+
+        let global = global_allocations();
+        let tracked = tracker.allocation_count();
+        vec.sort();
+        let tracked = tracker.allocation_count() - tracked;
+        // Tracking forwards to the global heap, so anything it didn't see
+        // went around it
+        assert_eq!(global_allocations() - global, tracked);
+        // The scratch buffer, then the run stack
+        assert!(tracked >= 2);
+        assert!(vec.windows(2).all(|w| w[0] <= w[1]));
+    });
+}
+
+#[test]
+fn splice_buffers_through_the_vector() {
+    assert_no_leaks(|tracker| {
+        let mut vec = MyVec::new_in(tracker);
+        vec.extend(0..10);
+        // No exact size hint, so the extra replacements get buffered
+        let splice = |vec: &mut MyVec<i32, _>| {
+            vec.splice(2..4, (100..120).filter(|_| true));
+        };
+        splice(&mut vec.clone());
+
+        let global = global_allocations();
+        let tracked = tracker.allocation_count();
+        splice(&mut vec);
+        assert_eq!(
+            global_allocations() - global,
+            tracker.allocation_count() - tracked
+        );
+        assert_eq!(vec[..4], [0, 1, 100, 101]);
+        assert_eq!(vec[22..], [4, 5, 6, 7, 8, 9]);
+
+        let mut inline = InlineVec::<i32, 4, _>::new_in(tracker);
+        inline.extend(0..3);
+        let splice = |inline: &mut InlineVec<i32, 4, _>| {
+            inline.splice(1..2, (100..120).filter(|_| true));
+        };
+        splice(&mut inline.clone());
+
+        let global = global_allocations();
+        let tracked = tracker.allocation_count();
+        splice(&mut inline);
+        assert_eq!(
+            global_allocations() - global,
+            tracker.allocation_count() - tracked
+        );
+        assert_eq!(inline[..2], [0, 100]);
+        assert_eq!(inline[20..], [119, 2]);
+    });
+}